@@ -0,0 +1,94 @@
+//! Shows a live view of a server's status in the terminal.
+//!
+//! Usage: `cargo run --example monitor -- <address>`, where the address is like `mc.hypixel.net` or
+//! `localhost:25566`.
+
+use elytra_ping::parse::ToAnsi;
+use elytra_ping::poller::{Availability, AvailabilityTracker};
+use elytra_ping::{ServerAddress, StatusPoller};
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::process::ExitCode;
+use std::time::Duration;
+
+/// How many latency samples the sparkline shows.
+const HISTORY: usize = 60;
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let Some(address) = std::env::args().nth(1) else {
+        eprintln!("Usage: monitor <address>");
+        return ExitCode::FAILURE;
+    };
+    let address: ServerAddress = match address.parse() {
+        Ok(address) => address,
+        Err(error) => {
+            eprintln!("{error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut poller = StatusPoller::new(address).with_interval(Duration::from_secs(2));
+    let mut latencies: VecDeque<Option<Duration>> = VecDeque::with_capacity(HISTORY);
+    // don't report the server as offline until a few pings in a row have failed
+    let mut tracker = AvailabilityTracker::new(3, 1);
+    loop {
+        let result = poller.next_status().await;
+        tracker.record(&result);
+        if latencies.len() == HISTORY {
+            latencies.pop_front();
+        }
+        latencies.push_back(result.as_ref().ok().map(|outcome| outcome.latency));
+
+        // clear the screen and move the cursor to the top left
+        let mut screen = String::from("\x1b[2J\x1b[H");
+        writeln!(screen, "\x1b[1m{}\x1b[0m", poller.target()).unwrap();
+        let status = match tracker.availability() {
+            Some(Availability::Online) => "\x1b[32mOnline\x1b[0m",
+            Some(Availability::Offline) => "\x1b[31mOffline\x1b[0m",
+            None => "\x1b[33mChecking\x1b[0m",
+        };
+        writeln!(screen, "Status: {status}").unwrap();
+        match &result {
+            Ok(outcome) => {
+                let players = outcome.info.players.as_ref();
+                writeln!(
+                    screen,
+                    "Players: {}/{}",
+                    players.map_or(0, |players| players.online),
+                    players.map_or(0, |players| players.max)
+                )
+                .unwrap();
+                writeln!(screen, "Latency: {}ms", outcome.latency.as_millis()).unwrap();
+                writeln!(screen).unwrap();
+                writeln!(screen, "{}", outcome.info.description.to_ansi()).unwrap();
+            }
+            Err(error) => writeln!(screen, "Latest ping failed: {error}").unwrap(),
+        }
+        writeln!(screen).unwrap();
+        writeln!(screen, "{}", sparkline(&latencies)).unwrap();
+        print!("{screen}");
+    }
+}
+
+/// Draws the latency history, with failed pings as gaps.
+fn sparkline(latencies: &VecDeque<Option<Duration>>) -> String {
+    let max = latencies
+        .iter()
+        .flatten()
+        .max()
+        .copied()
+        .unwrap_or_default();
+    latencies
+        .iter()
+        .map(|latency| match latency {
+            Some(latency) if !max.is_zero() => {
+                let level = latency.as_secs_f64() / max.as_secs_f64() * (SPARKS.len() - 1) as f64;
+                SPARKS[level.round() as usize]
+            }
+            Some(_) => SPARKS[0],
+            None => ' ',
+        })
+        .collect()
+}
@@ -1,3 +1,4 @@
+use elytra_ping::parse::{MarkdownOptions, ToMarkdown};
 use elytra_ping::ping;
 
 #[tokio::main]
@@ -20,6 +21,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let (info, latency) = ping((host, port)).await?;
     println!("Server info: {:#?}", info);
     println!("Latency: {}ms", latency.as_millis());
+    println!(
+        "Description (Markdown):\n{}",
+        info.description.to_markdown(&MarkdownOptions::default())
+    );
 
     Ok(())
 }
@@ -0,0 +1,97 @@
+use elytra_ping::PingReport;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+use std::time::Duration;
+
+create_exception!(elytra_ping, PingError, PyException);
+
+fn duration_from_secs(secs: f64) -> PyResult<Duration> {
+    if secs.is_finite() && secs > 0.0 {
+        Ok(Duration::from_secs_f64(secs))
+    } else {
+        Err(PyValueError::new_err("timeout must be a positive number"))
+    }
+}
+
+fn json_to_py<'py>(py: Python<'py>, value: &Value) -> PyResult<Bound<'py, PyAny>> {
+    Ok(match value {
+        Value::Null => py.None().into_bound(py),
+        Value::Bool(value) => value.into_pyobject(py)?.to_owned().into_any(),
+        Value::Number(number) => {
+            if let Some(number) = number.as_i64() {
+                number.into_pyobject(py)?.into_any()
+            } else if let Some(number) = number.as_u64() {
+                number.into_pyobject(py)?.into_any()
+            } else {
+                number
+                    .as_f64()
+                    .unwrap_or(f64::NAN)
+                    .into_pyobject(py)?
+                    .into_any()
+            }
+        }
+        Value::String(value) => value.into_pyobject(py)?.into_any(),
+        Value::Array(items) => {
+            let list = PyList::empty(py);
+            for item in items {
+                list.append(json_to_py(py, item)?)?;
+            }
+            list.into_any()
+        }
+        Value::Object(map) => {
+            let dict = PyDict::new(py);
+            for (key, value) in map {
+                dict.set_item(key, json_to_py(py, value)?)?;
+            }
+            dict.into_any()
+        }
+    })
+}
+
+fn report_to_py(py: Python<'_>, report: PingReport) -> PyResult<Bound<'_, PyAny>> {
+    let value =
+        serde_json::to_value(report).map_err(|error| PingError::new_err(error.to_string()))?;
+    json_to_py(py, &value)
+}
+
+/// Ping a Java Edition server, returning a dict with its `edition`, `latency_ms`, and status `info`.
+#[pyfunction]
+#[pyo3(signature = (host, port = 25565, timeout = 5.0))]
+fn ping_java(py: Python<'_>, host: String, port: u16, timeout: f64) -> PyResult<Bound<'_, PyAny>> {
+    let timeout = duration_from_secs(timeout)?;
+    let result = py
+        .allow_threads(|| elytra_ping::blocking::ping((host, port), timeout))
+        .map_err(|error| PingError::new_err(error.to_string()))?;
+    report_to_py(py, result.into())
+}
+
+/// Ping a Bedrock Edition server, returning a dict with its `edition`, `latency_ms`, and status `info`.
+///
+/// `timeout` is the number of seconds to wait for each of the `retries` attempts.
+#[pyfunction]
+#[pyo3(signature = (host, port = 19132, timeout = 2.0, retries = 3))]
+fn ping_bedrock(
+    py: Python<'_>,
+    host: String,
+    port: u16,
+    timeout: f64,
+    retries: u64,
+) -> PyResult<Bound<'_, PyAny>> {
+    let timeout = duration_from_secs(timeout)?;
+    let result = py
+        .allow_threads(|| elytra_ping::blocking::bedrock_ping((host, port), timeout, retries))
+        .map_err(|error| PingError::new_err(error.to_string()))?;
+    report_to_py(py, result.into())
+}
+
+#[pymodule]
+#[pyo3(name = "elytra_ping")]
+fn elytra_ping_module(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(ping_java, m)?)?;
+    m.add_function(wrap_pyfunction!(ping_bedrock, m)?)?;
+    m.add("PingError", m.py().get_type::<PingError>())?;
+    Ok(())
+}
@@ -0,0 +1,263 @@
+//! Parsing server addresses like `"mc.hypixel.net"`, `"localhost:25566"` or `"[::1]:19132"`.
+
+use alloc::{borrow::ToOwned, string::String};
+use core::{fmt, str::FromStr};
+use snafu::{Backtrace, OptionExt, Snafu};
+
+/// The port Java Edition servers listen on by default.
+pub const JAVA_DEFAULT_PORT: u16 = 25565;
+/// The port Bedrock Edition servers listen on by default.
+pub const BEDROCK_DEFAULT_PORT: u16 = 19132;
+
+/// The edition of Minecraft a server runs, which decides the port used when an address doesn't specify one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Edition {
+    Java,
+    Bedrock,
+}
+
+impl Edition {
+    pub fn default_port(self) -> u16 {
+        match self {
+            Edition::Java => JAVA_DEFAULT_PORT,
+            Edition::Bedrock => BEDROCK_DEFAULT_PORT,
+        }
+    }
+}
+
+#[derive(Snafu, Debug)]
+pub enum AddressParseError {
+    /// The address has no host.
+    #[snafu(display("Server address has no host"))]
+    MissingHost { backtrace: Backtrace },
+    /// The port is not a number between 0 and 65535.
+    #[snafu(display("Invalid port {port:?} in server address"))]
+    InvalidPort { port: String, backtrace: Backtrace },
+    /// An IPv6 address was opened with `[` but never closed.
+    #[snafu(display("Unclosed bracket in server address"))]
+    UnclosedBracket { backtrace: Backtrace },
+    /// Something other than a port follows a bracketed IPv6 address.
+    #[snafu(display("Unexpected {rest:?} after IPv6 address"))]
+    TrailingCharacters { rest: String, backtrace: Backtrace },
+}
+
+/// A server's hostname or IP address, and port.
+///
+/// Parsing with [`FromStr`] uses the Java Edition default port when the address doesn't include one;
+/// use [`ServerAddress::parse`] for Bedrock servers. IPv6 addresses must be wrapped in brackets to include a port,
+/// as in `"[::1]:25565"`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ServerAddress {
+    /// The hostname or IP address, without brackets.
+    pub host: String,
+    pub port: u16,
+}
+
+impl ServerAddress {
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+        }
+    }
+
+    /// Parses an address, using the default port of `edition` if it doesn't include one.
+    pub fn parse(address: &str, edition: Edition) -> Result<Self, AddressParseError> {
+        Self::parse_with_default_port(address, edition.default_port())
+    }
+
+    /// Parses an address, using `default_port` if it doesn't include one.
+    pub fn parse_with_default_port(
+        address: &str,
+        default_port: u16,
+    ) -> Result<Self, AddressParseError> {
+        let address = address.trim();
+        let (host, port) = if let Some(bracketed) = address.strip_prefix('[') {
+            let (host, rest) = bracketed.split_once(']').context(UnclosedBracketSnafu)?;
+            let port = match rest.strip_prefix(':') {
+                Some(port) => Some(port),
+                None if rest.is_empty() => None,
+                None => return TrailingCharactersSnafu { rest }.fail(),
+            };
+            (host, port)
+        } else {
+            match address.split_once(':') {
+                // more than one colon is a bare IPv6 address, which can't have a port
+                Some((_, rest)) if rest.contains(':') => (address, None),
+                Some((host, port)) => (host, Some(port)),
+                None => (address, None),
+            }
+        };
+        if host.is_empty() {
+            return MissingHostSnafu.fail();
+        }
+        let port = match port {
+            Some(port) => port.parse().ok().context(InvalidPortSnafu { port })?,
+            None => default_port,
+        };
+        Ok(Self::new(host, port))
+    }
+}
+
+impl FromStr for ServerAddress {
+    type Err = AddressParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, Edition::Java)
+    }
+}
+
+impl fmt::Display for ServerAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.host.contains(':') {
+            write!(f, "[{}]:{}", self.host, self.port)
+        } else {
+            write!(f, "{}:{}", self.host, self.port)
+        }
+    }
+}
+
+impl From<(String, u16)> for ServerAddress {
+    fn from((host, port): (String, u16)) -> Self {
+        Self { host, port }
+    }
+}
+
+impl From<(&str, u16)> for ServerAddress {
+    fn from((host, port): (&str, u16)) -> Self {
+        Self::new(host.to_owned(), port)
+    }
+}
+
+impl From<ServerAddress> for (String, u16) {
+    fn from(address: ServerAddress) -> Self {
+        (address.host, address.port)
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<std::net::SocketAddr> for ServerAddress {
+    fn from(address: std::net::SocketAddr) -> Self {
+        Self::new(address.ip().to_string(), address.port())
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<(std::net::IpAddr, u16)> for ServerAddress {
+    fn from((ip, port): (std::net::IpAddr, u16)) -> Self {
+        Self::new(ip.to_string(), port)
+    }
+}
+
+/// Types that can be used as the address of a server to ping, such as `"localhost:25566"`, `("localhost", 25566)`
+/// or a [`SocketAddr`](std::net::SocketAddr).
+///
+/// Strings are parsed with [`ServerAddress::parse`], so the port defaults to the pinged edition's default port.
+pub trait IntoServerAddress {
+    fn into_server_address(self, edition: Edition) -> Result<ServerAddress, AddressParseError>;
+}
+
+impl<T: Into<ServerAddress>> IntoServerAddress for T {
+    fn into_server_address(self, _edition: Edition) -> Result<ServerAddress, AddressParseError> {
+        Ok(self.into())
+    }
+}
+
+impl IntoServerAddress for &str {
+    fn into_server_address(self, edition: Edition) -> Result<ServerAddress, AddressParseError> {
+        ServerAddress::parse(self, edition)
+    }
+}
+
+impl IntoServerAddress for String {
+    fn into_server_address(self, edition: Edition) -> Result<ServerAddress, AddressParseError> {
+        ServerAddress::parse(&self, edition)
+    }
+}
+
+impl IntoServerAddress for &String {
+    fn into_server_address(self, edition: Edition) -> Result<ServerAddress, AddressParseError> {
+        ServerAddress::parse(self, edition)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let parse = |address: &str| address.parse::<ServerAddress>().map(<(String, u16)>::from);
+        assert_eq!(parse("localhost").unwrap(), ("localhost".into(), 25565));
+        assert_eq!(
+            parse(" localhost:25566 ").unwrap(),
+            ("localhost".into(), 25566)
+        );
+        assert_eq!(parse("[::1]:25566").unwrap(), ("::1".into(), 25566));
+        assert_eq!(parse("[::1]").unwrap(), ("::1".into(), 25565));
+        assert_eq!(parse("::1").unwrap(), ("::1".into(), 25565));
+        assert_eq!(
+            ServerAddress::parse("play.example.com", Edition::Bedrock).unwrap(),
+            ServerAddress::new("play.example.com", 19132)
+        );
+
+        assert!(matches!(
+            parse(""),
+            Err(AddressParseError::MissingHost { .. })
+        ));
+        assert!(matches!(
+            parse(":25565"),
+            Err(AddressParseError::MissingHost { .. })
+        ));
+        assert!(matches!(
+            parse("localhost:"),
+            Err(AddressParseError::InvalidPort { .. })
+        ));
+        assert!(matches!(
+            parse("localhost:65536"),
+            Err(AddressParseError::InvalidPort { .. })
+        ));
+        assert!(matches!(
+            parse("[::1:25565"),
+            Err(AddressParseError::UnclosedBracket { .. })
+        ));
+        assert!(matches!(
+            parse("[::1]25565"),
+            Err(AddressParseError::TrailingCharacters { .. })
+        ));
+    }
+
+    #[test]
+    fn into_server_address() {
+        let java = |address: &str| address.into_server_address(Edition::Java).unwrap();
+        assert_eq!(java("localhost"), ServerAddress::new("localhost", 25565));
+        assert_eq!(
+            "localhost".into_server_address(Edition::Bedrock).unwrap(),
+            ServerAddress::new("localhost", 19132)
+        );
+        assert_eq!(
+            ("localhost", 1)
+                .into_server_address(Edition::Bedrock)
+                .unwrap(),
+            ServerAddress::new("localhost", 1)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn socket_address() {
+        let socket: std::net::SocketAddr = "[::1]:1".parse().unwrap();
+        assert_eq!(
+            socket.into_server_address(Edition::Java).unwrap(),
+            ServerAddress::new("::1", 1)
+        );
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for address in ["localhost:25565", "[::1]:19132", "127.0.0.1:1"] {
+            let parsed: ServerAddress = address.parse().unwrap();
+            assert_eq!(parsed.to_string(), address);
+        }
+    }
+}
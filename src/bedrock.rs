@@ -1,21 +1,33 @@
+use crate::address::{AddressParseError, Edition, IntoServerAddress, ServerAddress};
+use crate::log::{debug, trace};
+use crate::net::{lookup_host, UdpSocket};
 use bytes::{Buf, BufMut};
 use chrono::Utc;
 use snafu::{Backtrace, OptionExt, ResultExt, Snafu};
+#[cfg(feature = "java_parse")]
 use std::fmt::Write;
 use std::{
     io::{Cursor, Read},
-    net::AddrParseError,
+    net::{AddrParseError, SocketAddr},
     str::FromStr,
-    time::Duration,
+    time::{Duration, Instant},
     vec,
 };
-use tokio::net::{lookup_host, UdpSocket};
-use tracing::{debug, trace};
+
+pub use self::scanner::BedrockScanner;
+
+#[cfg(feature = "nethernet")]
+pub mod nethernet;
+mod scanner;
+#[cfg(test)]
+pub(crate) mod test_support;
 
 #[derive(Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "java_parse", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub struct BedrockServerInfo {
-    /// Usually "MCPE" for bedrock or "MCEE" for education edition.
+    /// Usually "MCPE" for bedrock or "MCEE" for education edition. See [`BedrockServerInfo::edition()`].
     pub edition: String,
     pub name: String,
     pub protocol_version: u32,
@@ -31,15 +43,204 @@ pub struct BedrockServerInfo {
     pub extra: Vec<String>,
 }
 
+/// The port Bedrock Edition servers listen on by default.
+pub const DEFAULT_PORT: u16 = crate::address::BEDROCK_DEFAULT_PORT;
+/// The port Education Edition servers listen on by default.
+pub const EDUCATION_DEFAULT_PORT: u16 = 19132;
+
+/// The edition of a Bedrock server, as reported in the first field of its MOTD.
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BedrockEdition {
+    /// "MCPE", sent by Bedrock Edition servers.
+    Bedrock,
+    /// "MCEE", sent by Education Edition servers.
+    EducationEdition,
+    /// An edition that isn't recognized, as the server reported it.
+    Other(String),
+}
+
+impl FromStr for BedrockEdition {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "MCPE" => Self::Bedrock,
+            "MCEE" => Self::EducationEdition,
+            _ => Self::Other(s.to_owned()),
+        })
+    }
+}
+
+impl std::fmt::Display for BedrockEdition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bedrock => f.write_str("MCPE"),
+            Self::EducationEdition => f.write_str("MCEE"),
+            Self::Other(edition) => f.write_str(edition),
+        }
+    }
+}
+
+/// The default game mode of a Bedrock server.
+#[derive(Debug, Hash, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BedrockGameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+    /// A game mode that isn't recognized, as the server reported it.
+    Other(String),
+}
+
+impl BedrockGameMode {
+    /// Returns the game mode for a numeric game mode ID.
+    pub fn from_id(id: u64) -> Self {
+        match id {
+            0 => Self::Survival,
+            1 => Self::Creative,
+            2 => Self::Adventure,
+            6 => Self::Spectator,
+            id => Self::Other(id.to_string()),
+        }
+    }
+}
+
+impl FromStr for BedrockGameMode {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_ascii_lowercase().as_str() {
+            "survival" => Self::Survival,
+            "creative" => Self::Creative,
+            "adventure" => Self::Adventure,
+            "spectator" => Self::Spectator,
+            _ => Self::Other(s.to_owned()),
+        })
+    }
+}
+
+impl std::fmt::Display for BedrockGameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Survival => f.write_str("Survival"),
+            Self::Creative => f.write_str("Creative"),
+            Self::Adventure => f.write_str("Adventure"),
+            Self::Spectator => f.write_str("Spectator"),
+            Self::Other(mode) => f.write_str(mode),
+        }
+    }
+}
+
+impl BedrockServerInfo {
+    /// Returns the server's edition.
+    pub fn edition(&self) -> BedrockEdition {
+        match self.edition.parse() {
+            Ok(edition) => edition,
+            Err(infallible) => match infallible {},
+        }
+    }
+
+    /// Returns the server's game mode, from the game mode name if there is one, or the numeric ID otherwise.
+    pub fn parsed_game_mode(&self) -> Option<BedrockGameMode> {
+        match self.game_mode.as_deref() {
+            Some(name) if !name.is_empty() => name.parse().ok(),
+            _ => self.numeric_game_mode.map(BedrockGameMode::from_id),
+        }
+    }
+
+    /// Formats the info as the semicolon-delimited string sent in unconnected pong packets.
+    ///
+    /// Missing optional fields at the end are omitted, while missing fields followed by present ones are left empty.
+    pub fn to_motd_string(&self) -> String {
+        fn optional<T: ToString>(value: &Option<T>) -> String {
+            value.as_ref().map(ToString::to_string).unwrap_or_default()
+        }
+
+        let mut components = vec![
+            self.edition.clone(),
+            self.name.clone(),
+            self.protocol_version.to_string(),
+            self.mc_version.clone(),
+            self.online_players.to_string(),
+            self.max_players.to_string(),
+            optional(&self.server_id),
+            optional(&self.map_name),
+            optional(&self.game_mode),
+            optional(&self.numeric_game_mode),
+            optional(&self.ipv4_port),
+            optional(&self.ipv6_port),
+        ];
+        if self.extra.is_empty() {
+            while components.len() > 6 && components.last().map_or(false, String::is_empty) {
+                components.pop();
+            }
+        }
+        components.extend(self.extra.iter().cloned());
+        components.join(";")
+    }
+}
+
 #[cfg(feature = "java_parse")]
-impl From<BedrockServerInfo> for crate::JavaServerInfo {
-    fn from(value: BedrockServerInfo) -> Self {
-        let mut description = value.name;
-        if let Some(map_name) = value.map_name {
+impl BedrockServerInfo {
+    /// Returns the server name with its legacy formatting codes parsed into a text component.
+    pub fn name_component(&self) -> crate::parse::TextComponent {
+        crate::parse::parse_legacy_codes(&self.name)
+    }
+
+    /// Returns the server name and, on a second line, the map name, with their legacy formatting codes parsed
+    /// into a text component. This is how the server list shows them.
+    pub fn description_component(&self) -> crate::parse::TextComponent {
+        crate::parse::parse_legacy_codes(&self.description())
+    }
+
+    /// Returns the server name without any legacy formatting codes.
+    pub fn plain_name(&self) -> String {
+        crate::parse::strip_legacy_codes(&self.name)
+    }
+
+    /// Returns the map name without any legacy formatting codes.
+    pub fn plain_map_name(&self) -> Option<String> {
+        self.map_name
+            .as_deref()
+            .map(crate::parse::strip_legacy_codes)
+    }
+
+    fn description(&self) -> String {
+        let mut description = self.name.clone();
+        if let Some(map_name) = &self.map_name {
             write!(description, "\n§r{map_name}").unwrap();
         }
+        description
+    }
+}
+
+/// Renders the server name and map name, like [`BedrockServerInfo::description_component`].
+#[cfg(feature = "java_parse")]
+impl crate::parse::ToMarkdown for BedrockServerInfo {
+    fn to_markdown(&self, options: &crate::parse::MarkdownOptions) -> String {
+        self.description_component().to_markdown(options)
+    }
+}
+
+/// Renders the server name and map name, like [`BedrockServerInfo::description_component`].
+#[cfg(feature = "java_parse")]
+impl crate::parse::ToAnsi for BedrockServerInfo {
+    fn to_ansi_with(&self, obfuscated: crate::parse::ObfuscatedText) -> String {
+        self.description_component().to_ansi_with(obfuscated)
+    }
+}
+
+#[cfg(feature = "java_parse")]
+impl From<BedrockServerInfo> for crate::JavaServerInfo {
+    fn from(value: BedrockServerInfo) -> Self {
+        let description = value.description();
         crate::JavaServerInfo {
-            version: None,
+            version: Some(crate::parse::ServerVersion {
+                name: value.mc_version,
+                protocol: value.protocol_version.into(),
+            }),
             players: Some(crate::parse::ServerPlayers {
                 max: value.max_players,
                 online: value.online_players,
@@ -48,41 +249,118 @@ impl From<BedrockServerInfo> for crate::JavaServerInfo {
             description: crate::parse::TextComponent::Plain(description),
             favicon: None,
             mod_info: None,
+            forge_data: None,
             enforces_secure_chat: None,
+            modpack_data: None,
             prevents_chat_reports: None,
             previews_chat: None,
         }
     }
 }
 
+/// A best-effort conversion, where the first line of the description becomes the name and the second line becomes the
+/// map name. Fields that Java Edition servers don't report are left empty.
+#[cfg(feature = "java_parse")]
+impl From<crate::JavaServerInfo> for BedrockServerInfo {
+    fn from(value: crate::JavaServerInfo) -> Self {
+        let description = match value.description {
+            crate::parse::TextComponent::Plain(text) => text,
+            description => description.to_plain_text(),
+        };
+        let mut lines = description.lines();
+        let name = lines.next().unwrap_or_default().to_owned();
+        let map_name = lines
+            .next()
+            .map(|line| line.strip_prefix("§r").unwrap_or(line).to_owned());
+        let (online_players, max_players) = value
+            .players
+            .map_or((0, 0), |players| (players.online, players.max));
+        // a protocol number that isn't a real version becomes 0, as if there were no version
+        let (mc_version, protocol_version) = value.version.map_or((String::new(), 0), |version| {
+            (
+                version.name,
+                version.protocol.try_into().unwrap_or_default(),
+            )
+        });
+        BedrockServerInfo {
+            edition: "MCPE".to_owned(),
+            name,
+            protocol_version,
+            mc_version,
+            online_players,
+            max_players,
+            server_id: None,
+            map_name,
+            game_mode: None,
+            numeric_game_mode: None,
+            ipv4_port: None,
+            ipv6_port: None,
+            extra: Vec::new(),
+        }
+    }
+}
+
 /// Server MOTD string is missing information.
 #[derive(Debug, Snafu)]
-pub struct ServerInfoParseError;
+#[snafu(display("Invalid or missing {field} (field {index}) in MOTD {motd:?}"))]
+pub struct ServerInfoParseError {
+    /// The name of the field that failed to parse.
+    pub field: &'static str,
+    /// The position of the field in the MOTD, starting from zero.
+    pub index: usize,
+    /// The MOTD that failed to parse.
+    pub motd: String,
+}
 
 impl FromStr for BedrockServerInfo {
     type Err = ServerInfoParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        fn parse_impl(s: &str) -> Option<BedrockServerInfo> {
-            let mut components = s.split(';').map(|component| component.to_owned());
-            Some(BedrockServerInfo {
-                edition: components.next()?,
-                name: components.next()?,
-                protocol_version: components.next().and_then(|s| s.parse().ok())?,
-                mc_version: components.next()?,
-                online_players: components.next().and_then(|s| s.parse().ok())?,
-                max_players: components.next().and_then(|s| s.parse().ok())?,
-                server_id: components.next().and_then(|s| s.parse().ok()),
-                map_name: components.next(),
-                game_mode: components.next(),
-                numeric_game_mode: components.next().and_then(|s| s.parse().ok()),
-                ipv4_port: components.next().and_then(|s| s.parse().ok()),
-                ipv6_port: components.next().and_then(|s| s.parse().ok()),
-                extra: components.collect(),
-            })
-        }
+        let mut fields = MotdFields {
+            components: s.split(';'),
+            index: 0,
+            motd: s,
+        };
+        Ok(BedrockServerInfo {
+            edition: fields.required("edition")?,
+            name: fields.required("name")?,
+            protocol_version: fields.required("protocol version")?,
+            mc_version: fields.required("version")?,
+            online_players: fields.required("online players")?,
+            max_players: fields.required("max players")?,
+            server_id: fields.optional(),
+            map_name: fields.optional(),
+            game_mode: fields.optional(),
+            numeric_game_mode: fields.optional(),
+            ipv4_port: fields.optional(),
+            ipv6_port: fields.optional(),
+            extra: fields
+                .components
+                .map(|component| component.to_owned())
+                .collect(),
+        })
+    }
+}
+
+struct MotdFields<'a> {
+    components: std::str::Split<'a, char>,
+    index: usize,
+    motd: &'a str,
+}
+
+impl MotdFields<'_> {
+    fn optional<T: FromStr>(&mut self) -> Option<T> {
+        self.index += 1;
+        self.components.next().and_then(|s| s.parse().ok())
+    }
 
-        parse_impl(s).ok_or(ServerInfoParseError)
+    fn required<T: FromStr>(&mut self, field: &'static str) -> Result<T, ServerInfoParseError> {
+        let index = self.index;
+        self.optional().context(ServerInfoParseSnafu {
+            field,
+            index,
+            motd: self.motd,
+        })
     }
 }
 
@@ -95,8 +373,19 @@ pub enum BedrockPingError {
         address: String,
         backtrace: Backtrace,
     },
+    /// The server address could not be parsed.
+    #[snafu(display("Invalid server address: {source}"), context(false))]
+    InvalidAddress {
+        #[snafu(backtrace)]
+        source: AddressParseError,
+    },
     /// The server did not respond to the ping request.
-    NoResponse { backtrace: Backtrace },
+    #[snafu(display("{address} did not respond to the ping request"))]
+    NoResponse {
+        /// The address that was pinged.
+        address: SocketAddr,
+        backtrace: Backtrace,
+    },
     /// Failed to parse server info.
     #[snafu(display("Failed to parse server info: {source}"), context(false))]
     ServerInfoParse {
@@ -115,6 +404,15 @@ pub enum BedrockPingError {
         address: String,
         backtrace: Backtrace,
     },
+    /// The ping did not finish before the timeout.
+    #[snafu(display("Pinging {target} did not finish in time"))]
+    Timeout {
+        /// The server that was being pinged.
+        target: ServerAddress,
+        backtrace: Backtrace,
+    },
+    /// The server's response was not a valid pong packet.
+    InvalidPong { backtrace: Backtrace },
     /// Failed to open socket.
     #[snafu(display("Failed to open socket: {source}"))]
     ConnectFailed {
@@ -129,14 +427,23 @@ pub type BedrockPingResult<T> = Result<T, BedrockPingError>;
 /// https://wiki.vg/Raknet_Protocol#Data_types
 const MAGIC: u128 = 0x00ffff00fefefefefdfdfdfd12345678;
 
-struct PingRequestFrame {
-    time: i64,
+/// The largest payload a UDP datagram can carry over IPv4, so that long MOTDs are never truncated.
+const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+pub(crate) struct PingRequestFrame {
+    pub(crate) time: i64,
     magic: u128,
-    guid: i64,
+    pub(crate) guid: i64,
 }
 
 impl PingRequestFrame {
     const PACKET_ID: u8 = 0x01;
+    /// Sent instead by clients that only want a reply if the server has open player slots.
+    #[cfg(feature = "server")]
+    const OPEN_CONNECTIONS_PACKET_ID: u8 = 0x02;
+    #[cfg(feature = "server")]
+    const SIZE: usize = 1 + 8 + 16 + 8;
+
     pub fn to_vec(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(1028);
         buf.put_u8(Self::PACKET_ID);
@@ -145,18 +452,63 @@ impl PingRequestFrame {
         buf.put_i64(self.guid);
         buf
     }
+
+    #[cfg(feature = "server")]
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if bytes.len() < Self::SIZE {
+            return None;
+        }
+        let mut cursor = Cursor::new(bytes);
+        let packet_id = cursor.get_u8();
+        if packet_id != Self::PACKET_ID && packet_id != Self::OPEN_CONNECTIONS_PACKET_ID {
+            return None;
+        }
+        let time = cursor.get_i64();
+        let magic = cursor.get_u128();
+        if magic != MAGIC {
+            return None;
+        }
+        let guid = cursor.get_i64();
+        Some(Self { time, magic, guid })
+    }
 }
 
-struct PingResponseFrame {
-    time: i64,
-    /// "Server ID string" on wiki.vg
-    motd: String,
+/// An unconnected pong packet, as sent by the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BedrockPong {
+    /// Echo of the request's time, which identifies the request being replied to.
+    pub time: i64,
+    /// The server's RakNet GUID.
+    pub server_guid: i64,
+    /// The unparsed MOTD, called "Server ID string" on wiki.vg.
+    pub motd: String,
 }
 
-impl PingResponseFrame {
-    const SIZE: usize = 8 + 8 + 16 + 2;
+impl BedrockPong {
+    /// Parses the server info from the MOTD.
+    pub fn info(&self) -> Result<BedrockServerInfo, ServerInfoParseError> {
+        self.motd.parse()
+    }
+
+    const SIZE: usize = 1 + 8 + 8 + 16 + 2;
     const PACKET_ID: u8 = 0x1c;
-    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+
+    #[cfg(feature = "server")]
+    pub(crate) fn to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(Self::SIZE + self.motd.len());
+        buf.put_u8(Self::PACKET_ID);
+        buf.put_i64(self.time);
+        buf.put_i64(self.server_guid);
+        buf.put_u128(MAGIC);
+        // the length is a u16, so a longer MOTD can't be sent
+        let motd = &self.motd.as_bytes()[..self.motd.len().min(u16::MAX as usize)];
+        buf.put_u16(motd.len() as u16);
+        buf.put_slice(motd);
+        buf
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
         if bytes.len() < Self::SIZE {
             return None;
         }
@@ -168,7 +520,7 @@ impl PingResponseFrame {
         }
 
         let time = cursor.get_i64();
-        let _guid = cursor.get_i64();
+        let server_guid = cursor.get_i64();
         let magic = cursor.get_u128();
 
         if magic != MAGIC {
@@ -180,68 +532,699 @@ impl PingResponseFrame {
         cursor.read_exact(&mut motd_bytes).ok()?;
         let motd = String::from_utf8(motd_bytes).ok()?;
 
-        Some(PingResponseFrame { time, motd })
+        Some(BedrockPong {
+            time,
+            server_guid,
+            motd,
+        })
+    }
+}
+
+/// Options for pinging a Bedrock server, such as the fields to send in outgoing ping packets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BedrockPingOptions {
+    /// The client GUID to send. Defaults to a random GUID for each attempt.
+    pub guid: Option<i64>,
+    /// The time to send, which the server echoes back. Defaults to the current Unix time in milliseconds.
+    pub time: Option<i64>,
+    /// If set, another ping is sent at this interval while still accepting replies to earlier ones, until the
+    /// total timeout of `retry_timeout * retries` passes. By default, each ping waits for the full `retry_timeout`.
+    pub stagger: Option<Duration>,
+    /// If set, only pongs with this server GUID are accepted, such as the GUID returned by an earlier ping, so
+    /// that replies from a different server at the same address are ignored.
+    pub server_guid: Option<i64>,
+}
+
+impl BedrockPingOptions {
+    /// Sends `guid` as the client GUID, such as to use the same GUID for every ping in a session.
+    pub fn with_guid(mut self, guid: i64) -> Self {
+        self.guid = Some(guid);
+        self
+    }
+
+    /// Sends `time` as the ping time.
+    pub fn with_time(mut self, time: i64) -> Self {
+        self.time = Some(time);
+        self
+    }
+
+    /// Sends another ping every `interval`, instead of waiting for the full retry timeout before retrying.
+    pub fn with_stagger(mut self, interval: Duration) -> Self {
+        self.stagger = Some(interval);
+        self
+    }
+
+    /// Only accepts pongs with `guid` as the server GUID.
+    pub fn with_server_guid(mut self, guid: i64) -> Self {
+        self.server_guid = Some(guid);
+        self
     }
 }
 
 /// Ping a bedrock server and return the info and latency. Timeout is `retry_timeout * retries`.
 pub async fn ping(
-    address: (String, u16),
+    address: impl IntoServerAddress,
+    retry_timeout: Duration,
+    retries: u64,
+) -> BedrockPingResult<(BedrockServerInfo, Duration)> {
+    ping_with_options(
+        address,
+        retry_timeout,
+        retries,
+        BedrockPingOptions::default(),
+    )
+    .await
+}
+
+/// Ping a bedrock server, retrying every `retry_timeout` until the server responds or `timeout` passes.
+///
+/// Unlike [`ping`], the timeout covers the whole ping, including the DNS lookup.
+pub async fn ping_or_timeout(
+    address: impl IntoServerAddress,
+    retry_timeout: Duration,
+    timeout: Duration,
+) -> BedrockPingResult<(BedrockServerInfo, Duration)> {
+    let target = address
+        .into_server_address(Edition::Bedrock)
+        .map_err(BedrockPingError::from);
+    #[cfg(feature = "metrics")]
+    if let Err(error) = &target {
+        crate::metrics::ping_started(Edition::Bedrock);
+        crate::metrics::bedrock_ping_failed(error);
+    }
+    let target = target?;
+    match tokio::time::timeout(timeout, ping(target.clone(), retry_timeout, u64::MAX)).await {
+        Ok(result) => result,
+        Err(_) => {
+            #[cfg(feature = "metrics")]
+            crate::metrics::ping_failed(Edition::Bedrock, "timeout");
+            TimeoutSnafu { target }.fail()
+        }
+    }
+}
+
+/// Like [`ping`], but sends the fields in `options` in the ping packets.
+pub async fn ping_with_options(
+    address: impl IntoServerAddress,
     retry_timeout: Duration,
     retries: u64,
+    options: BedrockPingOptions,
 ) -> BedrockPingResult<(BedrockServerInfo, Duration)> {
-    let resolved = lookup_host(address.clone())
+    let resolved = resolve(address).await;
+    #[cfg(feature = "metrics")]
+    if let Err(error) = &resolved {
+        // the ping never started, but it still failed
+        crate::metrics::ping_started(Edition::Bedrock);
+        crate::metrics::bedrock_ping_failed(error);
+    }
+    ping_addr_with_options(resolved?, retry_timeout, retries, options).await
+}
+
+async fn resolve(address: impl IntoServerAddress) -> BedrockPingResult<SocketAddr> {
+    let address = address.into_server_address(Edition::Bedrock)?;
+    let resolved = lookup_host((address.host.as_str(), address.port))
         .await?
-        .next()
-        .context(DNSLookupFailedSnafu { address: address.0 })?;
-    trace!("host resolved to {resolved}");
+        .next();
+    let resolved = resolved.context(DNSLookupFailedSnafu {
+        address: address.host,
+    })?;
+    trace!(address = %resolved, "Host resolved");
+    Ok(resolved)
+}
+
+/// Like [`ping`], but pings a socket address directly instead of looking up a hostname.
+pub async fn ping_addr(
+    address: SocketAddr,
+    retry_timeout: Duration,
+    retries: u64,
+) -> BedrockPingResult<(BedrockServerInfo, Duration)> {
+    ping_addr_with_options(
+        address,
+        retry_timeout,
+        retries,
+        BedrockPingOptions::default(),
+    )
+    .await
+}
 
-    let socket = UdpSocket::bind("0.0.0.0:0")
+/// Like [`ping_addr`], but sends the fields in `options` in the ping packets.
+pub async fn ping_addr_with_options(
+    address: SocketAddr,
+    retry_timeout: Duration,
+    retries: u64,
+    options: BedrockPingOptions,
+) -> BedrockPingResult<(BedrockServerInfo, Duration)> {
+    let (pong, latency) = ping_addr_raw(address, retry_timeout, retries, options).await?;
+    Ok((pong.info()?, latency))
+}
+
+/// Like [`ping_addr_with_options`], but returns the pong packet without parsing its MOTD.
+pub async fn ping_addr_raw(
+    address: SocketAddr,
+    retry_timeout: Duration,
+    retries: u64,
+    options: BedrockPingOptions,
+) -> BedrockPingResult<(BedrockPong, Duration)> {
+    #[cfg(feature = "metrics")]
+    crate::metrics::ping_started(Edition::Bedrock);
+    let result = ping_addr_once(address, retry_timeout, retries, options).await;
+    #[cfg(feature = "metrics")]
+    match &result {
+        Ok((_, latency)) => crate::metrics::ping_succeeded(Edition::Bedrock, *latency),
+        Err(error) => crate::metrics::bedrock_ping_failed(error),
+    }
+    result
+}
+
+async fn ping_addr_once(
+    address: SocketAddr,
+    retry_timeout: Duration,
+    retries: u64,
+    options: BedrockPingOptions,
+) -> BedrockPingResult<(BedrockPong, Duration)> {
+    let bind_address = match address {
+        SocketAddr::V4(_) => "0.0.0.0:0",
+        SocketAddr::V6(_) => "[::]:0",
+    };
+    let socket = UdpSocket::bind(bind_address)
         .await
         .context(ConnectFailedSnafu)?;
-    socket.connect(resolved).await.context(ConnectFailedSnafu)?;
     trace!("opened udp socket");
 
-    let mut response = None;
-    for retry in 0..retries {
-        debug!("pinging raknet server, attempt {}", retry + 1);
-        tokio::select! {
-            biased;
-            _ = tokio::time::sleep(retry_timeout) => continue,
-            res = attempt_ping(&socket) => response = res,
+    // allocated once, since a ping may receive many datagrams
+    let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+    let response = match options.stagger {
+        Some(interval) => {
+            staggered_ping(
+                &socket,
+                &mut buffer,
+                address,
+                retry_timeout,
+                retries,
+                interval,
+                options,
+            )
+            .await
         }
-        if response.is_some() {
-            break;
+        None => {
+            // a late reply to an earlier attempt is as good as a reply to the latest one
+            let mut sent = Vec::new();
+            let mut response = None;
+            for retry in 0..retries {
+                debug!(attempt = retry + 1, "Pinging RakNet server");
+                let attempt_end = tokio::time::Instant::now() + retry_timeout;
+                sent.extend(send_ping(&socket, address, options).await);
+                if !sent.is_empty() {
+                    let receive = receive_pong(&socket, &mut buffer, address, &sent, options);
+                    if let Ok(res) = tokio::time::timeout_at(attempt_end, receive).await {
+                        response = res;
+                    }
+                    if response.is_some() {
+                        break;
+                    }
+                }
+                // if sending or receiving failed, such as when the network is unreachable, wait out the attempt
+                // instead of retrying straight away
+                tokio::time::sleep_until(attempt_end).await;
+            }
+            response
         }
-    }
-    let (response, latency) = response.context(NoResponseSnafu)?;
+    };
+    let response = response.context(NoResponseSnafu { address })?;
 
     trace!("ping finished");
 
-    Ok((response.motd.parse()?, latency))
+    Ok(response)
+}
+
+/// One of a server's endpoints, probed by [`probe_endpoints`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ProbedEndpoint {
+    pub address: SocketAddr,
+    /// The endpoint's latency, or `None` if it didn't respond.
+    pub latency: Option<Duration>,
+}
+
+/// The endpoints a server advertises, and whether they respond.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct EndpointReport {
+    /// The server info returned by the initial ping.
+    pub info: BedrockServerInfo,
+    /// The advertised IPv4 endpoint, or `None` if there is no IPv4 port or address to probe.
+    pub ipv4: Option<ProbedEndpoint>,
+    /// The advertised IPv6 endpoint, or `None` if there is no IPv6 port or address to probe.
+    pub ipv6: Option<ProbedEndpoint>,
 }
 
+/// Pings a server, then probes the IPv4 and IPv6 ports it reports to find out which ones respond.
+pub async fn probe_endpoints(
+    address: impl IntoServerAddress,
+    retry_timeout: Duration,
+    retries: u64,
+) -> BedrockPingResult<EndpointReport> {
+    let address = address.into_server_address(Edition::Bedrock)?;
+    let addresses: Vec<SocketAddr> = lookup_host((address.host.as_str(), address.port))
+        .await?
+        .collect();
+    let first = addresses.first().copied().context(DNSLookupFailedSnafu {
+        address: address.host,
+    })?;
+    let (info, _) = ping_addr(first, retry_timeout, retries).await?;
+
+    let endpoint = |ipv6: bool, port: Option<u16>| {
+        let ip = addresses
+            .iter()
+            .map(SocketAddr::ip)
+            .find(|ip| ip.is_ipv6() == ipv6)?;
+        Some(SocketAddr::new(ip, port?))
+    };
+    let probe = |endpoint: Option<SocketAddr>| async move {
+        let address = endpoint?;
+        let latency = ping_addr(address, retry_timeout, retries)
+            .await
+            .ok()
+            .map(|(_, latency)| latency);
+        Some(ProbedEndpoint { address, latency })
+    };
+    let (ipv4, ipv6) = tokio::join!(
+        probe(endpoint(false, info.ipv4_port)),
+        probe(endpoint(true, info.ipv6_port)),
+    );
+    debug!(?ipv4, ?ipv6, "Probed endpoints");
+
+    Ok(EndpointReport { info, ipv4, ipv6 })
+}
+
+/// Parses the server info from an unconnected pong packet, without needing a socket.
+///
+/// Malformed input results in an error rather than a panic, so this is suitable as a fuzzing target.
+pub fn parse_pong(bytes: &[u8]) -> BedrockPingResult<BedrockServerInfo> {
+    let pong = BedrockPong::from_bytes(bytes).context(InvalidPongSnafu)?;
+    Ok(pong.info()?)
+}
+
+/// Sends a ping every `interval` until a reply to any of them is received or the total timeout passes.
+async fn staggered_ping(
+    socket: &UdpSocket,
+    buffer: &mut [u8],
+    target: SocketAddr,
+    retry_timeout: Duration,
+    retries: u64,
+    interval: Duration,
+    options: BedrockPingOptions,
+) -> Option<(BedrockPong, Duration)> {
+    let timeout = retry_timeout.saturating_mul(u32::try_from(retries).unwrap_or(u32::MAX));
+    let deadline = tokio::time::sleep(timeout);
+    tokio::pin!(deadline);
+    // intervals can't be zero
+    let mut ticks = tokio::time::interval(interval.max(Duration::from_millis(1)));
+    // if a tick is late, don't make up for it by sending a burst of pings
+    ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut sent = Vec::new();
+    loop {
+        tokio::select! {
+            biased;
+            _ = &mut deadline => return None,
+            res = receive_pong(socket, buffer, target, &sent, options), if !sent.is_empty() => return res,
+            _ = ticks.tick() => {}
+        }
+        debug!(attempt = sent.len() + 1, "Pinging RakNet server");
+        if let Some(ping) = send_ping(socket, target, options).await {
+            sent.push(ping);
+        }
+    }
+}
+
+/// Sends a ping, returning its time and when it was sent.
+///
 /// See: https://wiki.vg/Raknet_Protocol#Unconnected_Ping
-async fn attempt_ping(socket: &UdpSocket) -> Option<(PingResponseFrame, Duration)> {
+async fn send_ping(
+    socket: &UdpSocket,
+    target: SocketAddr,
+    options: BedrockPingOptions,
+) -> Option<(i64, Instant)> {
+    let start = Instant::now();
     let outgoing_packet = PingRequestFrame {
-        time: Utc::now().timestamp_millis(),
+        time: options
+            .time
+            .unwrap_or_else(|| Utc::now().timestamp_millis()),
         magic: MAGIC,
-        guid: rand::random(),
+        guid: options.guid.unwrap_or_else(rand::random),
     };
-    socket.send(&outgoing_packet.to_vec()).await.ok()?;
-    let mut buffer = Vec::with_capacity(1024);
-    socket.recv_buf(&mut buffer).await.ok()?;
-    let incoming_packet = PingResponseFrame::from_bytes(&buffer)?;
-    let latency_millis = Utc::now().timestamp_millis() - incoming_packet.time;
-    let latency = Duration::from_millis(latency_millis as u64);
+    let _sent = socket
+        .send_to(&outgoing_packet.to_vec(), target)
+        .await
+        .ok()?;
+    #[cfg(feature = "metrics")]
+    crate::metrics::bytes_sent(Edition::Bedrock, _sent);
+    Some((outgoing_packet.time, start))
+}
 
-    Some((incoming_packet, latency))
+/// Waits for a reply to one of the `sent` pings, returning it with the latency since that ping was sent.
+///
+/// `buffer` should hold [`MAX_DATAGRAM_SIZE`] bytes, so that long pongs aren't truncated.
+async fn receive_pong(
+    socket: &UdpSocket,
+    buffer: &mut [u8],
+    target: SocketAddr,
+    sent: &[(i64, Instant)],
+    options: BedrockPingOptions,
+) -> Option<(BedrockPong, Duration)> {
+    // keep waiting if the socket receives datagrams that aren't a reply to a request
+    loop {
+        let (len, source) = socket.recv_from(buffer).await.ok()?;
+        #[cfg(feature = "metrics")]
+        crate::metrics::bytes_received(Edition::Bedrock, len);
+        if source != target {
+            trace!(%source, "Ignoring datagram from unexpected address");
+            continue;
+        }
+        let Some(incoming_packet) = BedrockPong::from_bytes(&buffer[..len]) else {
+            trace!("ignoring invalid pong");
+            continue;
+        };
+        let Some((_, start)) = sent.iter().find(|(time, _)| *time == incoming_packet.time) else {
+            trace!("ignoring pong that doesn't echo a request's time");
+            continue;
+        };
+        if matches!(options.server_guid, Some(guid) if guid != incoming_packet.server_guid) {
+            trace!(
+                server_guid = incoming_packet.server_guid,
+                "ignoring pong from a different server"
+            );
+            continue;
+        }
+        return Some((incoming_packet, start.elapsed()));
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::test_support::{mock_server, pong_packet, pong_server};
     use super::*;
 
+    #[test]
+    fn motd_string_round_trips() {
+        for motd in [
+            "MCPE;Dedicated Server;390;1.14.60;0;10;13253860892328930865;Bedrock level;Survival;1;19132;19133;",
+            "MCEE;Classroom;390;1.14.60;3;30;13253860892328930865;Lesson",
+            "MCPE;Minimal;390;1.14.60;0;10",
+        ] {
+            let info: BedrockServerInfo = motd.parse().unwrap();
+            assert_eq!(info.to_motd_string(), motd);
+        }
+    }
+
+    #[cfg(feature = "java_parse")]
+    #[test]
+    fn formatted_names() {
+        let info: BedrockServerInfo = "MCPE;§e§lThe Hive;390;1.14.60;0;10;1;§aLobby"
+            .parse()
+            .unwrap();
+        assert_eq!(info.plain_name(), "The Hive");
+        assert_eq!(info.plain_map_name().as_deref(), Some("Lobby"));
+        assert_eq!(info.name_component().to_plain_text(), "The Hive");
+        assert_eq!(
+            crate::parse::ToMarkdown::to_markdown(&info, &Default::default()),
+            "**The Hive**\nLobby"
+        );
+        assert_eq!(
+            crate::parse::ToAnsi::to_ansi(&info),
+            "\x1b[0;1;93mThe Hive\n\x1b[0;92mLobby\x1b[0m"
+        );
+    }
+
+    #[cfg(feature = "java_parse")]
+    #[test]
+    fn java_info_conversion() {
+        let info: BedrockServerInfo = "MCPE;Dedicated Server;390;1.14.60;2;10;1;Bedrock level"
+            .parse()
+            .unwrap();
+        let java = crate::JavaServerInfo::from(info);
+        let version = java.version.as_ref().unwrap();
+        assert_eq!(version.name, "1.14.60");
+        assert_eq!(version.protocol, 390);
+        assert_eq!(java.players.as_ref().unwrap().online, 2);
+
+        let bedrock = BedrockServerInfo::from(java);
+        assert_eq!(bedrock.name, "Dedicated Server");
+        assert_eq!(bedrock.map_name.as_deref(), Some("Bedrock level"));
+        assert_eq!(bedrock.mc_version, "1.14.60");
+        assert_eq!(bedrock.online_players, 2);
+        assert_eq!(bedrock.max_players, 10);
+    }
+
+    #[test]
+    fn game_mode() {
+        let parse = |motd: &str| {
+            motd.parse::<BedrockServerInfo>()
+                .unwrap()
+                .parsed_game_mode()
+        };
+        assert_eq!(
+            parse("MCPE;Server;390;1.14.60;0;10;1;Level;Creative;1"),
+            Some(BedrockGameMode::Creative)
+        );
+        assert_eq!(
+            parse("MCPE;Server;390;1.14.60;0;10;1;Level;;2"),
+            Some(BedrockGameMode::Adventure)
+        );
+        assert_eq!(
+            parse("MCPE;Server;390;1.14.60;0;10;1;Level;Hardcore;0"),
+            Some(BedrockGameMode::Other("Hardcore".into()))
+        );
+        assert_eq!(parse("MCPE;Server;390;1.14.60;0;10"), None);
+    }
+
+    #[test]
+    fn edition() {
+        let parse = |motd: &str| motd.parse::<BedrockServerInfo>().unwrap().edition();
+        assert_eq!(
+            parse("MCPE;Server;390;1.14.60;0;10"),
+            BedrockEdition::Bedrock
+        );
+        assert_eq!(
+            parse("MCEE;Classroom;390;1.14.60;0;10"),
+            BedrockEdition::EducationEdition
+        );
+        assert_eq!(
+            parse("MCXX;Server;390;1.14.60;0;10"),
+            BedrockEdition::Other("MCXX".into())
+        );
+    }
+
+    #[test]
+    fn motd_parse_error() {
+        let error = "MCPE;Dedicated Server;390;1.14.60;many;10"
+            .parse::<BedrockServerInfo>()
+            .unwrap_err();
+        assert_eq!(error.field, "online players");
+        assert_eq!(error.index, 4);
+        assert_eq!(error.motd, "MCPE;Dedicated Server;390;1.14.60;many;10");
+
+        let error = "MCPE;Dedicated Server"
+            .parse::<BedrockServerInfo>()
+            .unwrap_err();
+        assert_eq!(error.field, "protocol version");
+    }
+
+    #[test]
+    fn parse_pong_bytes() {
+        let pong = pong_packet(&[0; 8], "MCPE;Dedicated Server;390;1.14.60;0;10");
+        assert_eq!(parse_pong(&pong).unwrap().name, "Dedicated Server");
+
+        for len in 0..pong.len() {
+            assert!(parse_pong(&pong[..len]).is_err());
+        }
+    }
+
+    #[tokio::test]
+    async fn ignores_unrelated_datagrams() {
+        let address = mock_server(|request, client| {
+            let time = &request[1..9];
+            let stranger = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+            let spoofed = pong_packet(time, "MCPE;Spoofed;390;1.14.60;0;10");
+            stranger.send_to(&spoofed, client).unwrap();
+            vec![
+                pong_packet(&[0; 8], "MCPE;Stale;390;1.14.60;0;10"),
+                pong_packet(time, "MCPE;Genuine;390;1.14.60;0;10"),
+            ]
+        })
+        .await;
+
+        let (info, _) = ping_addr(address, Duration::from_secs(2), 1).await.unwrap();
+        assert_eq!(info.name, "Genuine");
+    }
+
+    #[tokio::test]
+    async fn long_motd() {
+        let name = "§a".repeat(2000);
+        let extra = vec!["extra"; 500].join(";");
+        let motd = format!("MCPE;{name};390;1.14.60;0;10;1;Level;Survival;1;19132;19133;{extra}");
+        let address = pong_server(motd.clone()).await;
+
+        let address = (address.ip().to_string(), address.port());
+        let (info, _) = ping(address, Duration::from_secs(2), 1).await.unwrap();
+        assert_eq!(info.name, name);
+        assert_eq!(info.extra.len(), 500);
+        assert_eq!(info.to_motd_string(), motd);
+    }
+
+    #[tokio::test]
+    async fn probes_advertised_endpoints() {
+        let endpoint = pong_server("MCPE;Endpoint;390;1.14.60;0;10").await;
+        let address = pong_server(format!(
+            "MCPE;Probe;390;1.14.60;0;10;1;Level;Survival;1;{};19133",
+            endpoint.port()
+        ))
+        .await;
+
+        let report = probe_endpoints(
+            ("127.0.0.1".to_owned(), address.port()),
+            Duration::from_millis(200),
+            1,
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.info.name, "Probe");
+        let ipv4 = report.ipv4.unwrap();
+        assert_eq!(ipv4.address, endpoint);
+        assert!(ipv4.latency.is_some());
+        // the host has no IPv6 address to probe the advertised port on
+        assert_eq!(report.ipv6, None);
+    }
+
+    #[tokio::test]
+    async fn raw_pong() {
+        let address = mock_server(|request, _| {
+            let mut pong = pong_packet(&request[1..9], "Not a valid MOTD");
+            pong[9..17].copy_from_slice(&7i64.to_be_bytes());
+            vec![pong]
+        })
+        .await;
+
+        let options = BedrockPingOptions::default().with_time(99);
+        let (pong, _) = ping_addr_raw(address, Duration::from_secs(2), 1, options)
+            .await
+            .unwrap();
+        assert_eq!(pong.time, 99);
+        assert_eq!(pong.server_guid, 7);
+        assert_eq!(pong.motd, "Not a valid MOTD");
+        assert!(pong.info().is_err());
+    }
+
+    #[tokio::test]
+    async fn staggered_retries() {
+        let address = late_reply_server(3, "MCPE;Staggered;390;1.14.60;0;10").await;
+
+        let options = BedrockPingOptions::default().with_stagger(Duration::from_millis(20));
+        let (info, latency) = ping_addr_with_options(address, Duration::from_secs(2), 1, options)
+            .await
+            .unwrap();
+        assert_eq!(info.name, "Staggered");
+        // the reply to the first ping comes once the third is sent, about 40ms later
+        assert!(latency >= Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn late_reply_to_earlier_attempt() {
+        let address = late_reply_server(2, "MCPE;Late;390;1.14.60;0;10").await;
+
+        let (info, latency) = ping_addr(address, Duration::from_millis(100), 3)
+            .await
+            .unwrap();
+        assert_eq!(info.name, "Late");
+        assert!(latency >= Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn matches_server_guid() {
+        let address = mock_server(|request, _| {
+            [7i64, 8]
+                .iter()
+                .map(|guid| {
+                    let mut pong =
+                        pong_packet(&request[1..9], &format!("MCPE;{guid};390;1.14.60;0;10"));
+                    pong[9..17].copy_from_slice(&guid.to_be_bytes());
+                    pong
+                })
+                .collect()
+        })
+        .await;
+
+        let options = BedrockPingOptions::default().with_server_guid(8);
+        let (info, _) = ping_addr_with_options(address, Duration::from_secs(2), 1, options)
+            .await
+            .unwrap();
+        assert_eq!(info.name, "8");
+    }
+
+    /// Starts a server that waits for `count` pings, then only replies to the first one.
+    async fn late_reply_server(count: usize, motd: &'static str) -> SocketAddr {
+        let mut times = Vec::new();
+        mock_server(move |request, _| {
+            times.push(request[1..9].to_vec());
+            if times.len() == count {
+                vec![pong_packet(&times[0], motd)]
+            } else {
+                vec![]
+            }
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn waits_after_send_errors() {
+        // sending to the broadcast address fails, since the socket doesn't allow broadcasts
+        let address = "255.255.255.255:19132".parse().unwrap();
+        let start = Instant::now();
+        let result = ping_addr(address, Duration::from_millis(50), 4).await;
+        assert!(matches!(result, Err(BedrockPingError::NoResponse { .. })));
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn overall_timeout() {
+        let silent = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let address_port = silent.local_addr().unwrap().port();
+        let address = ("127.0.0.1".to_owned(), address_port);
+        let start = Instant::now();
+        let result = ping_or_timeout(
+            address,
+            Duration::from_millis(50),
+            Duration::from_millis(300),
+        )
+        .await;
+        let error = result.unwrap_err();
+        assert!(matches!(error, BedrockPingError::Timeout { .. }));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert_eq!(
+            error.to_string(),
+            format!("Pinging 127.0.0.1:{address_port} did not finish in time")
+        );
+    }
+
+    #[tokio::test]
+    async fn sends_options() {
+        let address = mock_server(|request, _| {
+            assert_eq!(request.len(), 33);
+            assert_eq!(request[1..9], 1234i64.to_be_bytes());
+            assert_eq!(request[25..33], 42i64.to_be_bytes());
+            vec![pong_packet(&request[1..9], "MCPE;Options;390;1.14.60;0;10")]
+        })
+        .await;
+
+        let address = (address.ip().to_string(), address.port());
+        let options = BedrockPingOptions::default().with_guid(42).with_time(1234);
+        let (info, _) = ping_with_options(address, Duration::from_secs(2), 1, options)
+            .await
+            .unwrap();
+        assert_eq!(info.name, "Options");
+    }
+
     #[tokio::test]
     async fn cubecraft() {
         ping(
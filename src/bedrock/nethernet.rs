@@ -0,0 +1,335 @@
+//! The LAN discovery packets sent over NetherNet, the WebRTC-based transport that recent Bedrock Edition versions
+//! use instead of RakNet for LAN worlds.
+//!
+//! Clients broadcast a [`DiscoveryPacket::Request`] to [`DISCOVERY_PORT`], and each world replies with a
+//! [`DiscoveryPacket::Response`] holding its [`NetherNetServerData`]. Every packet is encrypted with a key that's
+//! the same for all games, so this only hides them from casual inspection.
+
+use super::BedrockServerInfo;
+use aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use aes::Aes256;
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+use snafu::{Backtrace, Snafu};
+use std::fmt::Write;
+
+/// The port LAN discovery packets are broadcast to.
+pub const DISCOVERY_PORT: u16 = 7551;
+
+const CHECKSUM_LEN: usize = 32;
+const BLOCK_LEN: usize = 16;
+
+#[derive(Snafu, Debug)]
+pub enum DiscoveryError {
+    /// The packet is too short, or its encrypted data isn't a whole number of blocks.
+    #[snafu(display("Discovery packet has an invalid length"))]
+    InvalidLength { backtrace: Backtrace },
+    /// The packet's padding is invalid, so it wasn't encrypted with the discovery key.
+    #[snafu(display("Failed to decrypt discovery packet"))]
+    Decrypt { backtrace: Backtrace },
+    /// The packet's checksum doesn't match its contents.
+    #[snafu(display("Discovery packet checksum does not match"))]
+    Checksum { backtrace: Backtrace },
+    /// The packet ended before all of its fields were read.
+    #[snafu(display("Discovery packet is truncated"))]
+    Truncated { backtrace: Backtrace },
+    /// The packet has an ID this crate doesn't know.
+    #[snafu(display("Unknown discovery packet ID {id}"))]
+    UnknownPacket { id: u16, backtrace: Backtrace },
+    /// A response's application data isn't valid hex.
+    #[snafu(display("Discovery response data is not valid hex"))]
+    InvalidHex { backtrace: Backtrace },
+}
+
+/// A LAN discovery packet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DiscoveryPacket {
+    /// Asks every world on the network to respond.
+    Request,
+    /// Describes a world. The data is usually [`NetherNetServerData`].
+    Response { application_data: Vec<u8> },
+    /// Carries a WebRTC signal, such as an offer to connect, to the network ID `recipient_id`.
+    Message { recipient_id: u64, data: String },
+}
+
+impl DiscoveryPacket {
+    const REQUEST_ID: u16 = 0;
+    const RESPONSE_ID: u16 = 1;
+    const MESSAGE_ID: u16 = 2;
+
+    /// Encrypts the packet as sent by the game with the network ID `sender_id`.
+    pub fn encode(&self, sender_id: u64) -> Vec<u8> {
+        let mut body = Vec::new();
+        let id = match self {
+            DiscoveryPacket::Request => Self::REQUEST_ID,
+            DiscoveryPacket::Response { application_data } => {
+                let mut hex = String::with_capacity(application_data.len() * 2);
+                for byte in application_data {
+                    write!(hex, "{byte:02x}").unwrap();
+                }
+                body.extend_from_slice(&(hex.len() as u32).to_le_bytes());
+                body.extend_from_slice(hex.as_bytes());
+                Self::RESPONSE_ID
+            }
+            DiscoveryPacket::Message { recipient_id, data } => {
+                body.extend_from_slice(&recipient_id.to_le_bytes());
+                body.extend_from_slice(&(data.len() as u32).to_le_bytes());
+                body.extend_from_slice(data.as_bytes());
+                Self::MESSAGE_ID
+            }
+        };
+        // the length includes itself, then the header is followed by 8 bytes of padding
+        let mut payload = Vec::with_capacity(2 + 2 + 8 + 8 + body.len());
+        payload.extend_from_slice(&((2 + 2 + 8 + 8 + body.len()) as u16).to_le_bytes());
+        payload.extend_from_slice(&id.to_le_bytes());
+        payload.extend_from_slice(&sender_id.to_le_bytes());
+        payload.extend_from_slice(&[0; 8]);
+        payload.extend_from_slice(&body);
+
+        let mut packet = checksum(&payload).finalize().into_bytes().to_vec();
+        packet.extend_from_slice(&encrypt(payload));
+        packet
+    }
+
+    /// Decrypts a packet, returning it with the network ID of the game that sent it.
+    pub fn decode(bytes: &[u8]) -> Result<(u64, Self), DiscoveryError> {
+        if bytes.len() < CHECKSUM_LEN + BLOCK_LEN || (bytes.len() - CHECKSUM_LEN) % BLOCK_LEN != 0 {
+            return InvalidLengthSnafu.fail();
+        }
+        let (expected, encrypted) = bytes.split_at(CHECKSUM_LEN);
+        let payload = decrypt(encrypted)?;
+        if checksum(&payload).verify_slice(expected).is_err() {
+            return ChecksumSnafu.fail();
+        }
+
+        // the length is redundant with the encrypted size, so it isn't checked
+        let mut reader = Reader(&payload);
+        reader.take(2)?;
+        let id = u16::from_le_bytes(reader.array()?);
+        let sender_id = u64::from_le_bytes(reader.array()?);
+        reader.take(8)?;
+        let packet = match id {
+            Self::REQUEST_ID => DiscoveryPacket::Request,
+            Self::RESPONSE_ID => {
+                let len = u32::from_le_bytes(reader.array()?) as usize;
+                let hex = reader.take(len)?;
+                DiscoveryPacket::Response {
+                    application_data: decode_hex(hex)?,
+                }
+            }
+            Self::MESSAGE_ID => {
+                let recipient_id = u64::from_le_bytes(reader.array()?);
+                let len = u32::from_le_bytes(reader.array()?) as usize;
+                let data = String::from_utf8_lossy(reader.take(len)?).into_owned();
+                DiscoveryPacket::Message { recipient_id, data }
+            }
+            id => return UnknownPacketSnafu { id }.fail(),
+        };
+        Ok((sender_id, packet))
+    }
+}
+
+/// The world described by a [`DiscoveryPacket::Response`].
+///
+/// Fields added by versions newer than this crate are kept in `extra`, so they're sent back unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct NetherNetServerData {
+    /// The version of this format.
+    pub version: u8,
+    /// The host's name, shown as the world's name in the friends tab.
+    pub server_name: String,
+    pub level_name: String,
+    pub game_type: i32,
+    pub player_count: i32,
+    pub max_player_count: i32,
+    pub editor_world: bool,
+    pub hardcore: bool,
+    pub transport_layer: i32,
+    pub extra: Vec<u8>,
+}
+
+impl NetherNetServerData {
+    /// Parses the application data of a [`DiscoveryPacket::Response`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DiscoveryError> {
+        let mut reader = Reader(bytes);
+        let version = reader.array::<1>()?[0];
+        let mut string = || -> Result<String, DiscoveryError> {
+            let len = reader.array::<1>()?[0];
+            Ok(String::from_utf8_lossy(reader.take(len.into())?).into_owned())
+        };
+        let server_name = string()?;
+        let level_name = string()?;
+        Ok(Self {
+            version,
+            server_name,
+            level_name,
+            game_type: i32::from_le_bytes(reader.array()?),
+            player_count: i32::from_le_bytes(reader.array()?),
+            max_player_count: i32::from_le_bytes(reader.array()?),
+            editor_world: reader.array::<1>()?[0] != 0,
+            hardcore: reader.array::<1>()?[0] != 0,
+            transport_layer: i32::from_le_bytes(reader.array()?),
+            extra: reader.0.to_vec(),
+        })
+    }
+
+    /// Encodes the data to send in a [`DiscoveryPacket::Response`]. Names longer than 255 bytes are truncated.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.version];
+        for name in [&self.server_name, &self.level_name] {
+            let name = &name.as_bytes()[..name.len().min(u8::MAX.into())];
+            bytes.push(name.len() as u8);
+            bytes.extend_from_slice(name);
+        }
+        bytes.extend_from_slice(&self.game_type.to_le_bytes());
+        bytes.extend_from_slice(&self.player_count.to_le_bytes());
+        bytes.extend_from_slice(&self.max_player_count.to_le_bytes());
+        bytes.push(self.editor_world.into());
+        bytes.push(self.hardcore.into());
+        bytes.extend_from_slice(&self.transport_layer.to_le_bytes());
+        bytes.extend_from_slice(&self.extra);
+        bytes
+    }
+}
+
+/// Describes a NetherNet world like a RakNet one. Fields that NetherNet doesn't report, like the version, are
+/// left empty.
+impl From<NetherNetServerData> for BedrockServerInfo {
+    fn from(value: NetherNetServerData) -> Self {
+        BedrockServerInfo {
+            edition: "MCPE".to_owned(),
+            name: value.server_name,
+            protocol_version: 0,
+            mc_version: String::new(),
+            online_players: value.player_count.try_into().unwrap_or_default(),
+            max_players: value.max_player_count.try_into().unwrap_or_default(),
+            server_id: None,
+            map_name: Some(value.level_name),
+            game_mode: None,
+            numeric_game_mode: value.game_type.try_into().ok(),
+            ipv4_port: None,
+            ipv6_port: None,
+            extra: Vec::new(),
+        }
+    }
+}
+
+/// The key every game uses, which is the SHA-256 hash of `0xdeadbeef` as a little-endian `u64`.
+fn key() -> [u8; 32] {
+    Sha256::digest(0xdead_beef_u64.to_le_bytes()).into()
+}
+
+fn checksum(payload: &[u8]) -> Hmac<Sha256> {
+    let mut mac =
+        <Hmac<Sha256> as Mac>::new_from_slice(&key()).expect("HMAC accepts any key length");
+    mac.update(payload);
+    mac
+}
+
+/// Encrypts with AES-256 in ECB mode and PKCS #7 padding.
+fn encrypt(mut payload: Vec<u8>) -> Vec<u8> {
+    let padding = BLOCK_LEN - payload.len() % BLOCK_LEN;
+    payload.resize(payload.len() + padding, padding as u8);
+    let cipher = Aes256::new(&key().into());
+    for block in payload.chunks_exact_mut(BLOCK_LEN) {
+        cipher.encrypt_block(GenericArray::from_mut_slice(block));
+    }
+    payload
+}
+
+fn decrypt(encrypted: &[u8]) -> Result<Vec<u8>, DiscoveryError> {
+    let mut payload = encrypted.to_vec();
+    let cipher = Aes256::new(&key().into());
+    for block in payload.chunks_exact_mut(BLOCK_LEN) {
+        cipher.decrypt_block(GenericArray::from_mut_slice(block));
+    }
+    let padding = payload.last().copied().unwrap_or_default();
+    let valid = (1..=BLOCK_LEN as u8).contains(&padding)
+        && payload[payload.len() - usize::from(padding)..]
+            .iter()
+            .all(|&byte| byte == padding);
+    if !valid {
+        return DecryptSnafu.fail();
+    }
+    payload.truncate(payload.len() - usize::from(padding));
+    Ok(payload)
+}
+
+fn decode_hex(hex: &[u8]) -> Result<Vec<u8>, DiscoveryError> {
+    if hex.len() % 2 != 0 {
+        return InvalidHexSnafu.fail();
+    }
+    hex.chunks_exact(2)
+        .map(|pair| {
+            std::str::from_utf8(pair)
+                .ok()
+                .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+                .ok_or_else(|| InvalidHexSnafu.build())
+        })
+        .collect()
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DiscoveryError> {
+        if self.0.len() < len {
+            return TruncatedSnafu.fail();
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], DiscoveryError> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let data = NetherNetServerData {
+            version: 4,
+            server_name: "Steve".to_owned(),
+            level_name: "My World".to_owned(),
+            game_type: 1,
+            player_count: 1,
+            max_player_count: 8,
+            editor_world: false,
+            hardcore: true,
+            transport_layer: 2,
+            extra: vec![0, 0, 0, 0],
+        };
+        let packet = DiscoveryPacket::Response {
+            application_data: data.to_bytes(),
+        };
+        let encoded = packet.encode(42);
+        assert_eq!((encoded.len() - CHECKSUM_LEN) % BLOCK_LEN, 0);
+
+        let (sender_id, decoded) = DiscoveryPacket::decode(&encoded).unwrap();
+        assert_eq!((sender_id, &decoded), (42, &packet));
+        let DiscoveryPacket::Response { application_data } = decoded else {
+            unreachable!()
+        };
+        assert_eq!(
+            NetherNetServerData::from_bytes(&application_data).unwrap(),
+            data
+        );
+
+        let info = BedrockServerInfo::from(data);
+        assert_eq!(info.map_name.as_deref(), Some("My World"));
+
+        let mut tampered = DiscoveryPacket::Request.encode(42);
+        tampered[0] ^= 1;
+        assert!(matches!(
+            DiscoveryPacket::decode(&tampered),
+            Err(DiscoveryError::Checksum { .. })
+        ));
+    }
+}
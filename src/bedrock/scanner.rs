@@ -0,0 +1,239 @@
+use super::{
+    send_ping, BedrockPingOptions, BedrockPingResult, BedrockPong, BedrockServerInfo,
+    ConnectFailedSnafu, NoResponseSnafu, MAX_DATAGRAM_SIZE,
+};
+use crate::log::{debug, trace};
+use crate::net::UdpSocket;
+use chrono::Utc;
+use snafu::{OptionExt, ResultExt};
+use std::{
+    collections::HashMap,
+    io::ErrorKind,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::{sync::oneshot, task::JoinHandle};
+
+type Response = (BedrockPong, Duration);
+
+/// How long to wait before receiving again after receiving failed for a reason other than an undelivered ping,
+/// so a broken socket doesn't spin.
+const RECEIVE_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Pings many Bedrock servers over a single UDP socket, instead of opening a socket for each one.
+///
+/// Responses are matched to pings by the address they came from and the time they echo.
+#[derive(Debug)]
+pub struct BedrockScanner {
+    socket: Arc<UdpSocket>,
+    pending: Arc<Mutex<HashMap<SocketAddr, Vec<Waiter>>>>,
+    next_id: AtomicU64,
+    options: BedrockPingOptions,
+    task: JoinHandle<()>,
+}
+
+/// A ping waiting for a response.
+#[derive(Debug)]
+struct Waiter {
+    id: u64,
+    sent: Vec<(i64, Instant)>,
+    sender: Option<oneshot::Sender<Response>>,
+}
+
+impl BedrockScanner {
+    /// Creates a scanner with a socket bound to `address`, such as `0.0.0.0:0` to ping IPv4 servers.
+    pub async fn bind(address: SocketAddr) -> BedrockPingResult<Self> {
+        let socket = Arc::new(UdpSocket::bind(address).await.context(ConnectFailedSnafu)?);
+        let pending = Arc::new(Mutex::new(HashMap::new()));
+        let task = tokio::spawn(receive(Arc::clone(&socket), Arc::clone(&pending)));
+        Ok(Self {
+            socket,
+            pending,
+            next_id: AtomicU64::new(0),
+            options: BedrockPingOptions::default(),
+            task,
+        })
+    }
+
+    /// Sends the fields in `options` in ping packets.
+    ///
+    /// [`BedrockPingOptions::stagger`] is ignored, since the scanner retries each ping once it times out.
+    /// [`BedrockPingOptions::server_guid`] is ignored too, since the scanner pings many different servers.
+    pub fn with_options(mut self, options: BedrockPingOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Pings a server and returns its info and latency. Timeout is `retry_timeout * retries`.
+    ///
+    /// This can be called concurrently to ping many servers at once.
+    pub async fn ping(
+        &self,
+        target: SocketAddr,
+        retry_timeout: Duration,
+        retries: u64,
+    ) -> BedrockPingResult<(BedrockServerInfo, Duration)> {
+        let (pong, latency) = self.ping_raw(target, retry_timeout, retries).await?;
+        Ok((pong.info()?, latency))
+    }
+
+    /// Like [`BedrockScanner::ping`], but returns the pong packet without parsing its MOTD.
+    pub async fn ping_raw(
+        &self,
+        target: SocketAddr,
+        retry_timeout: Duration,
+        retries: u64,
+    ) -> BedrockPingResult<(BedrockPong, Duration)> {
+        let (sender, mut receiver) = oneshot::channel();
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.lock().entry(target).or_default().push(Waiter {
+            id,
+            sent: Vec::new(),
+            sender: Some(sender),
+        });
+        let _guard = WaiterGuard {
+            scanner: self,
+            target,
+            id,
+        };
+
+        for retry in 0..retries {
+            debug!(%target, attempt = retry + 1, "Pinging server");
+            // record the ping before sending it, so a quick response isn't mistaken for an unexpected one
+            let time = self
+                .options
+                .time
+                .unwrap_or_else(|| Utc::now().timestamp_millis());
+            self.record_sent(target, id, (time, Instant::now()));
+            let options = BedrockPingOptions {
+                time: Some(time),
+                ..self.options
+            };
+            send_ping(&self.socket, target, options).await;
+            if let Ok(response) = tokio::time::timeout(retry_timeout, &mut receiver).await {
+                return response.ok().context(NoResponseSnafu { address: target });
+            }
+        }
+        NoResponseSnafu { address: target }.fail()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<SocketAddr, Vec<Waiter>>> {
+        self.pending
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+    }
+
+    fn record_sent(&self, target: SocketAddr, id: u64, ping: (i64, Instant)) {
+        let mut pending = self.lock();
+        let waiter = pending
+            .get_mut(&target)
+            .and_then(|waiters| waiters.iter_mut().find(|waiter| waiter.id == id));
+        if let Some(waiter) = waiter {
+            waiter.sent.push(ping);
+        }
+    }
+}
+
+impl Drop for BedrockScanner {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Stops waiting for a response when a ping finishes or is cancelled.
+struct WaiterGuard<'a> {
+    scanner: &'a BedrockScanner,
+    target: SocketAddr,
+    id: u64,
+}
+
+impl Drop for WaiterGuard<'_> {
+    fn drop(&mut self) {
+        let mut pending = self.scanner.lock();
+        if let Some(waiters) = pending.get_mut(&self.target) {
+            waiters.retain(|waiter| waiter.id != self.id);
+            if waiters.is_empty() {
+                pending.remove(&self.target);
+            }
+        }
+    }
+}
+
+/// Receives pongs and hands them to the pings waiting for them.
+async fn receive(socket: Arc<UdpSocket>, pending: Arc<Mutex<HashMap<SocketAddr, Vec<Waiter>>>>) {
+    let mut buffer = vec![0; MAX_DATAGRAM_SIZE];
+    loop {
+        let (len, source) = match socket.recv_from(&mut buffer).await {
+            Ok(received) => received,
+            // on Windows, a ping that couldn't be delivered fails the next receive
+            Err(error) if error.kind() == ErrorKind::ConnectionReset => {
+                trace!(%error, "Ignoring undelivered ping");
+                continue;
+            }
+            Err(error) => {
+                debug!(%error, "Scanner socket failed to receive");
+                tokio::time::sleep(RECEIVE_BACKOFF).await;
+                continue;
+            }
+        };
+        let Some(pong) = BedrockPong::from_bytes(&buffer[..len]) else {
+            trace!(%source, "Ignoring invalid pong");
+            continue;
+        };
+        let mut pending = pending.lock().unwrap_or_else(|error| error.into_inner());
+        let waiter = pending.get_mut(&source).and_then(|waiters| {
+            waiters.iter_mut().find_map(|waiter| {
+                let latency = waiter
+                    .sent
+                    .iter()
+                    .find(|(time, _)| *time == pong.time)
+                    .map(|(_, start)| start.elapsed())?;
+                Some((waiter, latency))
+            })
+        });
+        match waiter {
+            Some((waiter, latency)) => {
+                if let Some(sender) = waiter.sender.take() {
+                    // the ping may have just been cancelled
+                    let _ = sender.send((pong, latency));
+                }
+            }
+            None => trace!(%source, "Ignoring unexpected pong"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bedrock::{test_support::pong_server, BedrockPingError};
+
+    async fn server(name: &str) -> SocketAddr {
+        pong_server(format!("MCPE;{name};390;1.14.60;0;10")).await
+    }
+
+    #[tokio::test]
+    async fn scans_many_servers() {
+        let scanner = BedrockScanner::bind("127.0.0.1:0".parse().unwrap())
+            .await
+            .unwrap();
+        let first = server("First").await;
+        let second = server("Second").await;
+        let silent = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let timeout = Duration::from_millis(200);
+
+        let (first, second, silent) = tokio::join!(
+            scanner.ping(first, timeout, 2),
+            scanner.ping(second, timeout, 2),
+            scanner.ping(silent.local_addr().unwrap(), timeout, 2),
+        );
+        assert_eq!(first.unwrap().0.name, "First");
+        assert_eq!(second.unwrap().0.name, "Second");
+        assert!(matches!(silent, Err(BedrockPingError::NoResponse { .. })));
+        assert!(scanner.lock().is_empty());
+    }
+}
@@ -0,0 +1,42 @@
+//! Fixtures shared by the Bedrock tests.
+
+use super::{BedrockPong, MAGIC};
+use std::net::SocketAddr;
+use tokio::net::UdpSocket;
+
+/// Builds a pong packet echoing `time`, with a server GUID of 0.
+pub(crate) fn pong_packet(time: &[u8], motd: &str) -> Vec<u8> {
+    let mut pong = vec![BedrockPong::PACKET_ID];
+    pong.extend_from_slice(time);
+    pong.extend_from_slice(&[0; 8]);
+    pong.extend_from_slice(&MAGIC.to_be_bytes());
+    pong.extend_from_slice(&(motd.len() as u16).to_be_bytes());
+    pong.extend_from_slice(motd.as_bytes());
+    pong
+}
+
+/// Starts a server on `127.0.0.1` that answers every ping with `motd`, returning its address.
+pub(crate) async fn pong_server(motd: impl Into<String>) -> SocketAddr {
+    let motd = motd.into();
+    mock_server(move |request, _| vec![pong_packet(&request[1..9], &motd)]).await
+}
+
+/// Starts a server on `127.0.0.1` that calls `reply` with each request and the client that sent it, and sends
+/// the datagrams it returns back to the client. Returns the server's address.
+pub(crate) async fn mock_server<F>(mut reply: F) -> SocketAddr
+where
+    F: FnMut(&[u8], SocketAddr) -> Vec<Vec<u8>> + Send + 'static,
+{
+    let server = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let address = server.local_addr().unwrap();
+    tokio::spawn(async move {
+        let mut request = [0; 64];
+        loop {
+            let (len, client) = server.recv_from(&mut request).await.unwrap();
+            for datagram in reply(&request[..len], client) {
+                server.send_to(&datagram, client).await.unwrap();
+            }
+        }
+    });
+    address
+}
@@ -0,0 +1,132 @@
+//! Checks addresses against Mojang's list of blocked servers, which the vanilla client refuses to join.
+//!
+//! The list is a SHA-1 hash of each blocked pattern, one per line, and can be downloaded from
+//! [`BLOCKED_SERVERS_URL`]. Patterns can be wildcards like `*.example.com` or `192.168.*`, so the hashes of every
+//! pattern that could match an address are checked.
+//!
+//! ```
+//! use elytra_ping::blocked_servers::BlockedServers;
+//!
+//! // the hash of `*.example.com`
+//! let list = BlockedServers::parse("8c7122d652cb7be22d1986f1f30b07fd5108d9c0\n");
+//! assert_eq!(list.len(), 1);
+//! assert!(list.is_blocked("mc.example.com"));
+//! assert!(!list.is_blocked("example.com"));
+//! ```
+
+use sha1::{Digest, Sha1};
+use std::collections::HashSet;
+
+/// Where Mojang publishes the list of blocked servers.
+pub const BLOCKED_SERVERS_URL: &str = "https://sessionserver.mojang.com/blockedservers";
+
+/// A SHA-1 hash of a blocked pattern.
+pub type PatternHash = [u8; 20];
+
+/// Mojang's list of blocked servers.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockedServers {
+    hashes: HashSet<PatternHash>,
+}
+
+impl BlockedServers {
+    /// Parses the list as downloaded from [`BLOCKED_SERVERS_URL`]. Lines that aren't a SHA-1 hash are ignored.
+    pub fn parse(list: &str) -> Self {
+        list.lines()
+            .filter_map(|line| parse_hash(line.trim()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hashes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// Returns whether the vanilla client would refuse to join `address`, which is a hostname or IP address
+    /// without a port.
+    pub fn is_blocked(&self, address: &str) -> bool {
+        self.matching_pattern(address).is_some()
+    }
+
+    /// Returns the pattern that blocks `address`, such as `*.example.com`, or `None` if it isn't blocked.
+    pub fn matching_pattern(&self, address: &str) -> Option<String> {
+        patterns(address)
+            .into_iter()
+            .find(|pattern| self.hashes.contains(&hash_pattern(pattern)))
+    }
+}
+
+impl FromIterator<PatternHash> for BlockedServers {
+    fn from_iter<I: IntoIterator<Item = PatternHash>>(iter: I) -> Self {
+        Self {
+            hashes: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Hashes a pattern like `*.example.com` as it appears in the list.
+pub fn hash_pattern(pattern: &str) -> PatternHash {
+    Sha1::digest(pattern.as_bytes()).into()
+}
+
+/// Lists every pattern that would block `address`, like the vanilla client.
+///
+/// An IP address is matched by itself and by replacing its last parts with `*`, like `192.168.*`. A hostname is
+/// matched by itself and by replacing its first parts with `*`, like `*.example.com`.
+fn patterns(address: &str) -> Vec<String> {
+    let address = address.trim_end_matches('.').to_lowercase();
+    let mut patterns = vec![address.clone()];
+    let parts: Vec<_> = address.split('.').collect();
+    let is_ipv4 = parts.len() == 4 && parts.iter().all(|part| part.parse::<u8>().is_ok());
+    for i in 1..parts.len() {
+        if is_ipv4 {
+            patterns.push(format!("{}.*", parts[..parts.len() - i].join(".")));
+        } else {
+            patterns.push(format!("*.{}", parts[i..].join(".")));
+        }
+    }
+    patterns
+}
+
+fn parse_hash(hex: &str) -> Option<PatternHash> {
+    if hex.len() != 40 {
+        return None;
+    }
+    let mut hash = [0; 20];
+    for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocked() {
+        let list: BlockedServers = ["*.blocked.example", "10.0.*", "exact.example"]
+            .into_iter()
+            .map(hash_pattern)
+            .collect();
+        assert!(list.is_blocked("play.blocked.example"));
+        assert!(list.is_blocked("A.B.Blocked.Example."));
+        assert!(list.is_blocked("10.0.1.2"));
+        assert!(list.is_blocked("exact.example"));
+        assert!(!list.is_blocked("blocked.example"));
+        assert!(!list.is_blocked("sub.exact.example"));
+        assert!(!list.is_blocked("10.1.0.0"));
+        assert_eq!(
+            list.matching_pattern("mc.blocked.example").as_deref(),
+            Some("*.blocked.example")
+        );
+
+        let hex = "da39a3ee5e6b4b0d3255bfef95601890afd80709";
+        let parsed = BlockedServers::parse(&format!("{hex}\nnot a hash\n"));
+        assert_eq!(parsed.len(), 1);
+        assert!(parsed.is_blocked(""));
+    }
+}
@@ -0,0 +1,53 @@
+//! Synchronous versions of the ping functions, for programs that don't use async Rust.
+//!
+//! Each function starts a single-threaded Tokio runtime to run the ping on, so they must not be called from
+//! within an async context.
+
+use crate::bedrock::{BedrockPingResult, BedrockServerInfo};
+use crate::{IntoServerAddress, JavaServerInfo, PingError, RuntimeSnafu};
+use snafu::ResultExt;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+
+fn runtime() -> std::io::Result<Runtime> {
+    Builder::new_current_thread().enable_all().build()
+}
+
+/// Ping a Java Edition server and return its status and latency, aborting if it takes longer than `timeout`.
+///
+/// See [`ping_or_timeout`](crate::ping_or_timeout).
+pub fn ping(
+    addrs: impl IntoServerAddress,
+    timeout: Duration,
+) -> Result<(JavaServerInfo, Duration), PingError> {
+    runtime()
+        .context(RuntimeSnafu)?
+        .block_on(crate::ping_or_timeout(addrs, timeout))
+}
+
+/// Ping a Bedrock Edition server and return its status and latency. Timeout is `retry_timeout * retries`.
+///
+/// See [`bedrock::ping`](crate::bedrock::ping).
+pub fn bedrock_ping(
+    address: impl IntoServerAddress,
+    retry_timeout: Duration,
+    retries: u64,
+) -> BedrockPingResult<(BedrockServerInfo, Duration)> {
+    runtime()?.block_on(crate::bedrock::ping(address, retry_timeout, retries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocking_connection_refused() {
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let result = ping(("127.0.0.1".to_owned(), port), Duration::from_secs(5));
+        assert!(matches!(result, Err(PingError::Protocol { .. })));
+    }
+}
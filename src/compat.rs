@@ -0,0 +1,181 @@
+//! Finding out which client versions a server supports.
+
+use crate::log::debug;
+use crate::parse::ServerVersion;
+use crate::protocol::{self, ResolvedAddress};
+use crate::{IntoServerAddress, PingError, PingPhase, PingTarget, ProtocolSnafu};
+use snafu::ResultExt;
+use std::ops::RangeInclusive;
+use std::time::Duration;
+
+/// The version a server reported when pinged by a client using a particular protocol version.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ProtocolResponse {
+    /// The protocol version sent in the handshake.
+    pub requested: i32,
+    /// The version in the server's status, or `None` if the ping failed or the status didn't include one.
+    pub version: Option<ServerVersion>,
+}
+
+impl ProtocolResponse {
+    /// Returns whether the server reported the requested protocol version, meaning it accepts that client.
+    pub fn is_supported(&self) -> bool {
+        self.version.as_ref().map_or(false, |version| {
+            version.protocol == i64::from(self.requested)
+        })
+    }
+}
+
+/// The responses collected by [`probe_protocols`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ProtocolMatrix {
+    /// One response per requested protocol version, in the order they were requested.
+    pub responses: Vec<ProtocolResponse>,
+}
+
+impl ProtocolMatrix {
+    /// Returns whether the server reported different protocol versions to different clients.
+    ///
+    /// Vanilla servers always report their own version, while servers running ViaVersion or a similar plugin
+    /// report the client's version if they can translate it.
+    pub fn is_multi_version(&self) -> bool {
+        let mut protocols = self
+            .responses
+            .iter()
+            .filter_map(|response| Some(response.version.as_ref()?.protocol));
+        let Some(first) = protocols.next() else {
+            return false;
+        };
+        protocols.any(|protocol| protocol != first)
+    }
+
+    /// Returns the requested protocol versions the server reported back, in the order they were requested.
+    pub fn supported(&self) -> Vec<i32> {
+        self.responses
+            .iter()
+            .filter(|response| response.is_supported())
+            .map(|response| response.requested)
+            .collect()
+    }
+
+    /// Returns the lowest and highest supported protocol versions, or `None` if none were supported.
+    ///
+    /// Versions between them that weren't requested aren't known to be supported.
+    pub fn supported_range(&self) -> Option<RangeInclusive<i32>> {
+        let supported = self.supported();
+        Some(*supported.iter().min()?..=*supported.iter().max()?)
+    }
+}
+
+/// Pings a server once for each of `protocols`, claiming to be a client using that protocol version, and
+/// collects the version the server reports each time.
+///
+/// The address is only resolved once, and each ping must finish within `timeout`. A failed ping is recorded
+/// as a response without a version, but failing to resolve the address is an error.
+pub async fn probe_protocols(
+    addrs: impl IntoServerAddress,
+    protocols: &[i32],
+    timeout: Duration,
+) -> Result<ProtocolMatrix, PingError> {
+    let addrs = crate::parse_target(addrs)?;
+    let resolved = protocol::resolve(addrs.clone())
+        .await
+        .context(ProtocolSnafu {
+            phase: Some(PingPhase::Resolve),
+            elapsed: Duration::ZERO,
+            target: PingTarget {
+                server: Some(addrs),
+                address: None,
+            },
+        })?;
+
+    let mut responses = Vec::with_capacity(protocols.len());
+    for &requested in protocols {
+        let version =
+            match tokio::time::timeout(timeout, status_version(&resolved, requested)).await {
+                Ok(Ok(version)) => version,
+                Ok(Err(error)) => {
+                    debug!(protocol = requested, %error, "Ping failed");
+                    None
+                }
+                Err(_) => {
+                    debug!(protocol = requested, "Ping timed out");
+                    None
+                }
+            };
+        responses.push(ProtocolResponse { requested, version });
+    }
+    Ok(ProtocolMatrix { responses })
+}
+
+async fn status_version(
+    resolved: &ResolvedAddress,
+    protocol: i32,
+) -> Result<Option<ServerVersion>, protocol::ProtocolError> {
+    let mut client = protocol::connect_resolved(resolved.clone()).await?;
+    client.handshake_with_protocol(protocol).await?;
+    let info = client.get_status().await?;
+    client.disconnect().await?;
+    Ok(info.version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(reported: &[(i32, Option<i64>)]) -> ProtocolMatrix {
+        let responses = reported
+            .iter()
+            .map(|&(requested, protocol)| ProtocolResponse {
+                requested,
+                version: protocol.map(|protocol| ServerVersion::new("1.21", protocol)),
+            })
+            .collect();
+        ProtocolMatrix { responses }
+    }
+
+    #[test]
+    fn supported_versions() {
+        let vanilla = matrix(&[(47, Some(767)), (760, Some(767)), (767, Some(767))]);
+        assert!(!vanilla.is_multi_version());
+        assert_eq!(vanilla.supported(), vec![767]);
+        assert_eq!(vanilla.supported_range(), Some(767..=767));
+
+        let via = matrix(&[(47, Some(47)), (760, None), (767, Some(767))]);
+        assert!(via.is_multi_version());
+        assert_eq!(via.supported(), vec![47, 767]);
+        assert_eq!(via.supported_range(), Some(47..=767));
+
+        let offline = matrix(&[(47, None), (767, None)]);
+        assert!(!offline.is_multi_version());
+        assert_eq!(offline.supported_range(), None);
+    }
+
+    #[cfg(feature = "test_util")]
+    #[tokio::test]
+    async fn protocol_matrix() {
+        use crate::test_util::{MockServer, MockServerConfig};
+
+        let probe = |echo_protocol| async move {
+            let server = MockServer::start(MockServerConfig {
+                echo_protocol,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+            probe_protocols(server.address(), &[47, 760, 767], Duration::from_secs(1))
+                .await
+                .unwrap()
+        };
+
+        let vanilla = probe(false).await;
+        assert!(!vanilla.is_multi_version());
+        assert_eq!(vanilla.supported(), vec![767]);
+
+        let via = probe(true).await;
+        assert!(via.is_multi_version());
+        assert_eq!(via.supported_range(), Some(47..=767));
+    }
+}
@@ -0,0 +1,360 @@
+//! Checking whether a server is healthy, such as for a readiness probe.
+
+use crate::{ping_outcome_or_timeout, IntoServerAddress, PingError, PingOutcome, PingPhase};
+use std::fmt;
+use std::time::Duration;
+
+/// Limits that decide whether a server is healthy, passed to [`health_check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HealthThresholds {
+    /// How long the ping can take before the server is considered unreachable. Defaults to 5 seconds.
+    pub timeout: Duration,
+    /// The highest latency a healthy server can have.
+    pub max_latency: Option<Duration>,
+    /// The fewest players a healthy server can have online, such as to notice when players can't join.
+    pub min_players: Option<u32>,
+    /// Whether a server that is full is degraded, since no more players can join. Defaults to `false`.
+    pub full_is_degraded: bool,
+}
+
+impl Default for HealthThresholds {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(5),
+            max_latency: None,
+            min_players: None,
+            full_is_degraded: false,
+        }
+    }
+}
+
+impl HealthThresholds {
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_max_latency(mut self, max_latency: Duration) -> Self {
+        self.max_latency = Some(max_latency);
+        self
+    }
+
+    pub fn with_min_players(mut self, min_players: u32) -> Self {
+        self.min_players = Some(min_players);
+        self
+    }
+
+    pub fn with_full_is_degraded(mut self, full_is_degraded: bool) -> Self {
+        self.full_is_degraded = full_is_degraded;
+        self
+    }
+
+    /// Decides how healthy a server is from the result of pinging it, such as a result from
+    /// [`StatusPoller`](crate::StatusPoller).
+    pub fn evaluate(&self, result: &Result<PingOutcome, PingError>) -> HealthStatus {
+        let outcome = match result {
+            Ok(outcome) => outcome,
+            Err(PingError::Protocol {
+                phase: Some(phase), ..
+            }) => {
+                return HealthStatus::Unreachable {
+                    reason: UnreachableReason::Failed { phase: *phase },
+                }
+            }
+            Err(PingError::Timeout { .. }) => {
+                return HealthStatus::Unreachable {
+                    reason: UnreachableReason::TimedOut,
+                }
+            }
+            Err(_) => {
+                return HealthStatus::Unreachable {
+                    reason: UnreachableReason::Other,
+                }
+            }
+        };
+
+        let mut reasons = Vec::new();
+        if let Some(max) = self.max_latency {
+            if outcome.latency > max {
+                reasons.push(DegradedReason::HighLatency {
+                    latency: outcome.latency,
+                    max,
+                });
+            }
+        }
+        if let Some(players) = &outcome.info.players {
+            if let Some(min) = self.min_players {
+                if players.online < min {
+                    reasons.push(DegradedReason::TooFewPlayers {
+                        online: players.online,
+                        min,
+                    });
+                }
+            }
+            if self.full_is_degraded && players.online >= players.max {
+                reasons.push(DegradedReason::Full {
+                    online: players.online,
+                    max: players.max,
+                });
+            }
+        }
+
+        if reasons.is_empty() {
+            HealthStatus::Healthy
+        } else {
+            HealthStatus::Degraded { reasons }
+        }
+    }
+}
+
+/// How healthy a server is, returned by [`health_check`].
+///
+/// The [`Display`](fmt::Display) implementation gives a one-line summary, suitable as the body of a probe
+/// response.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HealthStatus {
+    /// The server responded within every threshold.
+    Healthy,
+    /// The server responded, but exceeded at least one threshold.
+    Degraded { reasons: Vec<DegradedReason> },
+    /// The server couldn't be pinged.
+    Unreachable { reason: UnreachableReason },
+}
+
+impl HealthStatus {
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, HealthStatus::Healthy)
+    }
+
+    /// Returns whether the server responded, even if it's degraded.
+    pub fn is_reachable(&self) -> bool {
+        !matches!(self, HealthStatus::Unreachable { .. })
+    }
+}
+
+impl fmt::Display for HealthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HealthStatus::Healthy => f.write_str("healthy"),
+            HealthStatus::Degraded { reasons } => {
+                f.write_str("degraded: ")?;
+                for (i, reason) in reasons.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{reason}")?;
+                }
+                Ok(())
+            }
+            HealthStatus::Unreachable { reason } => write!(f, "unreachable: {reason}"),
+        }
+    }
+}
+
+/// A threshold that a reachable server exceeded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DegradedReason {
+    HighLatency { latency: Duration, max: Duration },
+    TooFewPlayers { online: u32, min: u32 },
+    Full { online: u32, max: u32 },
+}
+
+impl fmt::Display for DegradedReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DegradedReason::HighLatency { latency, max } => write!(
+                f,
+                "latency of {}ms is above {}ms",
+                latency.as_millis(),
+                max.as_millis()
+            ),
+            DegradedReason::TooFewPlayers { online, min } => {
+                write!(f, "{online} players online is below {min}")
+            }
+            DegradedReason::Full { online, max } => write!(f, "full with {online}/{max} players"),
+        }
+    }
+}
+
+/// Why a server couldn't be pinged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum UnreachableReason {
+    /// The ping didn't finish within the timeout.
+    TimedOut,
+    /// The ping failed during `phase`.
+    Failed { phase: PingPhase },
+    /// The ping failed outside of any phase, such as when the async runtime for a blocking ping couldn't be
+    /// started.
+    Other,
+}
+
+impl fmt::Display for UnreachableReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UnreachableReason::TimedOut => f.write_str("timed out"),
+            UnreachableReason::Failed { phase } => write!(f, "failed while {phase}"),
+            UnreachableReason::Other => f.write_str("failed"),
+        }
+    }
+}
+
+/// Pings a server and decides how healthy it is.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use elytra_ping::health::{health_check, HealthThresholds};
+/// use std::time::Duration;
+///
+/// let thresholds = HealthThresholds::default().with_max_latency(Duration::from_millis(500));
+/// let status = health_check("mc.hypixel.net", &thresholds).await;
+/// println!("{status}");
+/// # }
+/// ```
+pub async fn health_check(
+    addrs: impl IntoServerAddress,
+    thresholds: &HealthThresholds,
+) -> HealthStatus {
+    thresholds.evaluate(&ping_outcome_or_timeout(addrs, thresholds.timeout).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{JavaServerInfo, ServerPlayers};
+    use crate::protocol::{ConnectionInfo, ProtocolError, ResolutionTrace};
+    use crate::{PingTarget, ServerAddress, Timings};
+    use snafu::GenerateImplicitData;
+    use std::time::SystemTime;
+
+    fn outcome(
+        latency: Duration,
+        players: Option<ServerPlayers>,
+    ) -> Result<PingOutcome, PingError> {
+        let mut info = JavaServerInfo::new("A Minecraft Server");
+        info.players = players;
+        let address = "127.0.0.1:25565".parse().unwrap();
+        Ok(PingOutcome {
+            info,
+            raw_json: String::new(),
+            latency,
+            timestamp: SystemTime::now(),
+            target: ("localhost".to_owned(), 25565),
+            connection: ConnectionInfo {
+                srv_record: None,
+                address,
+                srv_time: Duration::ZERO,
+                dns_time: Duration::ZERO,
+                trace: ResolutionTrace {
+                    srv_records: Vec::new(),
+                    srv_record: None,
+                    resolved_host: ("localhost".to_owned(), 25565),
+                    addresses: vec![address],
+                },
+                connect_time: Duration::ZERO,
+            },
+            attempts: 1,
+            timings: Timings::default(),
+        })
+    }
+
+    fn connection_closed() -> ProtocolError {
+        ProtocolError::ConnectionClosed {
+            backtrace: GenerateImplicitData::generate(),
+        }
+    }
+
+    #[test]
+    fn unreachable() {
+        let thresholds = HealthThresholds::default();
+        let timeout = Err(PingError::Timeout {
+            target: ServerAddress::new("localhost", 25565),
+            backtrace: GenerateImplicitData::generate(),
+        });
+        assert_eq!(
+            thresholds.evaluate(&timeout),
+            HealthStatus::Unreachable {
+                reason: UnreachableReason::TimedOut
+            }
+        );
+
+        let failed = Err(PingError::Protocol {
+            source: connection_closed(),
+            phase: Some(PingPhase::Connect),
+            elapsed: Duration::ZERO,
+            target: Box::<PingTarget>::default(),
+        });
+        let status = thresholds.evaluate(&failed);
+        assert_eq!(
+            status,
+            HealthStatus::Unreachable {
+                reason: UnreachableReason::Failed {
+                    phase: PingPhase::Connect
+                }
+            }
+        );
+        assert_eq!(status.to_string(), "unreachable: failed while connecting");
+
+        let unknown_phase = Err(PingError::from(connection_closed()));
+        assert_eq!(
+            thresholds.evaluate(&unknown_phase),
+            HealthStatus::Unreachable {
+                reason: UnreachableReason::Other
+            }
+        );
+    }
+
+    #[test]
+    fn degraded() {
+        let thresholds = HealthThresholds::default();
+        let result = outcome(Duration::from_millis(300), Some(ServerPlayers::new(20, 20)));
+        assert_eq!(thresholds.evaluate(&result), HealthStatus::Healthy);
+
+        let status = thresholds
+            .with_max_latency(Duration::from_millis(100))
+            .evaluate(&result);
+        assert_eq!(
+            status,
+            HealthStatus::Degraded {
+                reasons: vec![DegradedReason::HighLatency {
+                    latency: Duration::from_millis(300),
+                    max: Duration::from_millis(100),
+                }]
+            }
+        );
+
+        let status = thresholds
+            .with_min_players(25)
+            .with_full_is_degraded(true)
+            .evaluate(&result);
+        assert_eq!(
+            status,
+            HealthStatus::Degraded {
+                reasons: vec![
+                    DegradedReason::TooFewPlayers {
+                        online: 20,
+                        min: 25
+                    },
+                    DegradedReason::Full {
+                        online: 20,
+                        max: 20
+                    },
+                ]
+            }
+        );
+    }
+
+    #[test]
+    fn no_player_counts() {
+        // without player counts, the player thresholds can't be checked
+        let thresholds = HealthThresholds::default()
+            .with_min_players(1)
+            .with_full_is_degraded(true);
+        let result = outcome(Duration::from_millis(10), None);
+        assert_eq!(thresholds.evaluate(&result), HealthStatus::Healthy);
+    }
+}
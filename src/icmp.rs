@@ -0,0 +1,63 @@
+//! ICMP echo latency, for telling network issues apart from a server that is slow to respond.
+//!
+//! Sending ICMP echo requests requires permission to open raw sockets, or on Linux, to open unprivileged ICMP
+//! sockets (see the `net.ipv4.ping_group_range` sysctl).
+//!
+//! Unlike the rest of the crate, this module requires Rust 1.85 to build.
+
+use crate::{IntoServerAddress, PingError, PingOutcome};
+use snafu::{Backtrace, ResultExt, Snafu};
+use std::net::IpAddr;
+use std::time::Duration;
+
+#[derive(Debug, Snafu)]
+pub enum IcmpError {
+    /// Failed to send or receive the ICMP echo.
+    #[snafu(display("ICMP ping failed: {source}"))]
+    Icmp {
+        source: surge_ping::SurgeError,
+        backtrace: Backtrace,
+    },
+    /// The host did not reply before the timeout.
+    #[snafu(display("ICMP echo timed out"))]
+    Timeout { backtrace: Backtrace },
+}
+
+/// Sends an ICMP echo request to `ip` and returns the round-trip time.
+pub async fn icmp_latency(ip: IpAddr, timeout: Duration) -> Result<Duration, IcmpError> {
+    let payload = [0; 8];
+    match tokio::time::timeout(timeout, surge_ping::ping(ip, &payload)).await {
+        Ok(result) => result.map(|(_, latency)| latency).context(IcmpSnafu),
+        Err(_) => TimeoutSnafu.fail(),
+    }
+}
+
+/// Pings a server, then measures the ICMP latency to the address that was connected to.
+///
+/// The ICMP measurement is returned separately, since many hosts don't reply to ICMP even when the server is up.
+pub async fn ping_with_icmp(
+    addrs: impl IntoServerAddress,
+    timeout: Duration,
+) -> Result<(PingOutcome, Result<Duration, IcmpError>), PingError> {
+    let outcome = crate::ping_outcome_or_timeout(addrs, timeout).await?;
+    let icmp = icmp_latency(outcome.connection.address.ip(), timeout).await;
+    Ok((outcome, icmp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn loopback() {
+        match icmp_latency("127.0.0.1".parse().unwrap(), Duration::from_secs(1)).await {
+            Ok(latency) => assert!(latency < Duration::from_secs(1)),
+            // the sandbox running the tests may not allow ICMP sockets
+            Err(IcmpError::Icmp {
+                source: surge_ping::SurgeError::IOError(error),
+                ..
+            }) if error.kind() == std::io::ErrorKind::PermissionDenied => {}
+            Err(error) => panic!("{error}"),
+        }
+    }
+}
@@ -11,13 +11,21 @@
 //! # #[tokio::main]
 //! # async fn main() {
 //! let (ping_info, latency) = elytra_ping::ping_or_timeout(
-//!     ("mc.hypixel.net".to_string(), 25565),
+//!     "mc.hypixel.net",
 //!     Duration::from_secs(1),
 //! ).await.unwrap();
 //! println!("{ping_info:#?}, {latency:?}");
 //! # }
 //! ```
 //!
+//! Addresses can be given as strings like `"localhost:25566"` or `"[::1]"`, tuples, or socket addresses;
+//! see [`IntoServerAddress`]. Strings can also be parsed ahead of time with [`ServerAddress`].
+//!
+//! ```
+//! let address: elytra_ping::ServerAddress = "[::1]:25566".parse().unwrap();
+//! assert_eq!(<(String, u16)>::from(address), ("::1".to_string(), 25566));
+//! ```
+//!
 //! Use the [`bedrock::ping`] function to retrieve a Bedrock Edition server's status and latency, specifying the number of retries
 //! if the operation fails initially and the amount of time to spend before timing out on a single retry.
 //!
@@ -66,7 +74,7 @@
 //!     .expect("connection closed by server");
 //!
 //! let status: String = match frame {
-//!     elytra_ping::protocol::Frame::StatusResponse { json } => json,
+//!     elytra_ping::protocol::Frame::StatusResponse { json } => json.into(),
 //!     _ => panic!("expected status packet"),
 //! };
 //!
@@ -76,62 +84,568 @@
 //! # Ok(())
 //! # }
 //! ```
-use snafu::{Backtrace, Snafu};
-use std::time::Duration;
+//!
+//! [`SlpProtocol::new`] accepts any transport implementing Tokio's `AsyncRead` and `AsyncWrite` traits,
+//! such as a relay connection. With only the `java_protocol` and `java_parse` features enabled, Elytra Ping
+//! does not depend on DNS or TCP support and can be compiled for `wasm32-unknown-unknown`.
+//!
+//! Programs that only need to parse status JSON can disable default features and enable `java_parse`, which
+//! makes Elytra Ping a `no_std` crate that only depends on `alloc`.
+#![cfg_attr(not(feature = "std"), no_std)]
+extern crate alloc;
 
-#[cfg(feature = "java_connect")]
+#[cfg(feature = "simple")]
+use snafu::{Backtrace, ResultExt, Snafu};
+#[cfg(feature = "simple")]
+use std::net::SocketAddr;
+#[cfg(feature = "simple")]
+use std::time::{Duration, Instant, SystemTime};
+
+pub mod address;
+pub use address::{IntoServerAddress, ServerAddress};
+pub mod varint;
+
+#[cfg(feature = "java_protocol")]
 pub mod mc_string;
-#[cfg(feature = "java_connect")]
+#[cfg(feature = "java_protocol")]
 pub mod protocol;
 #[cfg(feature = "java_connect")]
 pub use crate::protocol::connect;
-#[cfg(feature = "java_connect")]
-pub use protocol::SlpProtocol;
+#[cfg(feature = "java_protocol")]
+pub use protocol::{ConnectionInfo, FrameMetadata, SlpProtocol};
 
 #[cfg(feature = "java_parse")]
 pub mod parse;
 #[cfg(feature = "java_parse")]
 pub use parse::JavaServerInfo;
+#[cfg(feature = "java_parse")]
+pub mod report;
+#[cfg(feature = "java_parse")]
+pub use report::PingReport;
+#[cfg(all(feature = "std", feature = "java_parse"))]
+pub mod sink;
+
+#[cfg(feature = "simple")]
+pub mod compat;
+#[cfg(feature = "simple")]
+pub mod health;
+#[cfg(feature = "simple")]
+pub mod poller;
+#[cfg(feature = "simple")]
+pub use poller::StatusPoller;
+#[cfg(feature = "simple")]
+pub mod prometheus;
 
 #[cfg(feature = "bedrock")]
 pub mod bedrock;
 
+#[cfg(feature = "icmp")]
+pub mod icmp;
+
+#[cfg(any(feature = "java_protocol", feature = "bedrock"))]
+mod log;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(any(feature = "java_connect", feature = "bedrock"))]
+mod net;
+
+#[cfg(feature = "blocked_servers")]
+pub mod blocked_servers;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+#[cfg(feature = "server")]
+pub mod server;
+
+#[cfg(feature = "test_util")]
+pub mod test_util;
+#[cfg(feature = "test_util")]
+pub mod test_vectors;
+
 #[cfg(feature = "simple")]
 #[derive(Snafu, Debug)]
 pub enum PingError {
     /// Connection failed.
-    #[snafu(display("Connection failed: {source}"), context(false))]
+    #[snafu(display("{}: {source}", describe_failure(target, *phase, *elapsed)))]
     Protocol {
         #[snafu(backtrace)]
         source: crate::protocol::ProtocolError,
+        /// The part of the ping that failed, or `None` if the error was converted from a
+        /// [`ProtocolError`](crate::protocol::ProtocolError) outside of a ping.
+        phase: Option<PingPhase>,
+        /// How long the ping had been running when it failed, or zero if the phase isn't known.
+        elapsed: Duration,
+        /// Where the ping was going.
+        target: Box<PingTarget>,
     },
     /// The connection did not finish in time.
-    Timeout { backtrace: Backtrace },
+    #[snafu(display("Pinging {target} did not finish in time"))]
+    Timeout {
+        /// The server that was being pinged.
+        target: ServerAddress,
+        backtrace: Backtrace,
+    },
+    /// Failed to start the async runtime for a blocking ping.
+    #[cfg(feature = "blocking")]
+    #[snafu(display("Failed to start the async runtime: {source}"))]
+    Runtime {
+        source: std::io::Error,
+        backtrace: Backtrace,
+    },
+}
+
+#[cfg(feature = "simple")]
+impl From<crate::protocol::ProtocolError> for PingError {
+    fn from(source: crate::protocol::ProtocolError) -> Self {
+        PingError::Protocol {
+            source,
+            phase: None,
+            elapsed: Duration::ZERO,
+            target: Box::default(),
+        }
+    }
+}
+
+/// Describes where and when a ping failed, leaving out whatever isn't known.
+#[cfg(feature = "simple")]
+fn describe_failure(target: &PingTarget, phase: Option<PingPhase>, elapsed: Duration) -> String {
+    let mut description = String::from("Connection");
+    if target.server.is_some() || target.address.is_some() {
+        description += &format!(" to {target}");
+    }
+    description += " failed";
+    if let Some(phase) = phase {
+        description += &format!(" while {phase} after {elapsed:?}");
+    }
+    description
+}
+
+#[cfg(feature = "simple")]
+impl PingError {
+    /// Returns the server that was being pinged, unless its address couldn't be parsed.
+    pub fn target(&self) -> Option<&ServerAddress> {
+        match self {
+            PingError::Protocol { target, .. } => target.server.as_ref(),
+            PingError::Timeout { target, .. } => Some(target),
+            #[cfg(feature = "blocking")]
+            PingError::Runtime { .. } => None,
+        }
+    }
+}
+
+/// Where a failed ping was going, so errors from many concurrent pings can be told apart.
+///
+/// The [`Display`](std::fmt::Display) implementation writes `{server} ({address})`, leaving out whatever
+/// isn't known.
+#[cfg(feature = "simple")]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct PingTarget {
+    /// The server that was being pinged, unless its address couldn't be parsed.
+    pub server: Option<ServerAddress>,
+    /// The address the server's hostname resolved to, if it got that far.
+    pub address: Option<SocketAddr>,
+}
+
+#[cfg(feature = "simple")]
+impl std::fmt::Display for PingTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match (&self.server, &self.address) {
+            (Some(server), Some(address)) => write!(f, "{server} ({address})"),
+            (Some(server), None) => write!(f, "{server}"),
+            (None, Some(address)) => write!(f, "{address}"),
+            (None, None) => f.write_str("an unknown server"),
+        }
+    }
+}
+
+/// A part of the ping process.
+#[cfg(feature = "simple")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PingPhase {
+    /// Looking up the SRV record and resolving the hostname.
+    Resolve,
+    /// Opening the TCP connection.
+    Connect,
+    /// Encoding the handshake packet.
+    ///
+    /// The handshake is sent in the same write as the status request, so a failure to send it is reported as
+    /// [`PingPhase::Status`].
+    Handshake,
+    /// Sending the handshake and status request, and receiving the server's status.
+    Status,
+    /// Measuring latency with a ping packet.
+    Latency,
+    /// Starting a login to find out whether the server authenticates players.
+    Login,
+    /// Closing the connection.
+    Disconnect,
+}
+
+#[cfg(feature = "simple")]
+impl std::fmt::Display for PingPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            PingPhase::Resolve => "resolving the address",
+            PingPhase::Connect => "connecting",
+            PingPhase::Handshake => "encoding the handshake",
+            PingPhase::Status => "requesting the status",
+            PingPhase::Latency => "measuring latency",
+            PingPhase::Login => "logging in",
+            PingPhase::Disconnect => "disconnecting",
+        })
+    }
+}
+
+/// A successful ping, along with details about how it was carried out.
+#[cfg(feature = "simple")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PingOutcome {
+    /// The server's status.
+    pub info: JavaServerInfo,
+    /// The JSON the server's status was parsed from.
+    pub raw_json: String,
+    /// The round-trip time of the ping packet, which includes the time the server took to respond.
+    ///
+    /// See [`PingOutcome::connect_latency`] for a measurement closer to the network latency.
+    pub latency: Duration,
+    /// When the ping was started.
+    pub timestamp: SystemTime,
+    /// The address that was passed to the ping function.
+    pub target: (String, u16),
+    /// How the connection to the server was established.
+    pub connection: ConnectionInfo,
+    /// The number of connection attempts that were made.
+    pub attempts: u32,
+    /// How long each part of the ping took.
+    pub timings: Timings,
+}
+
+#[cfg(feature = "simple")]
+impl PingOutcome {
+    /// Returns how long opening the TCP connection took.
+    ///
+    /// Unlike [`PingOutcome::latency`], this doesn't depend on how quickly the server handles packets, so it's
+    /// closer to the network latency.
+    pub fn connect_latency(&self) -> Duration {
+        self.connection.connect_time
+    }
+}
+
+/// How long each part of a ping took.
+#[cfg(feature = "simple")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub struct Timings {
+    /// Resolving the hostname to an IP address.
+    pub dns: Duration,
+    /// Looking up the SRV record.
+    pub srv: Duration,
+    /// Opening the TCP connection.
+    pub connect: Duration,
+    /// Sending the handshake and status request, which go out in one write, and receiving the status response.
+    pub handshake_to_status: Duration,
+    /// Sending the ping packet and receiving the response.
+    pub ping_rtt: Duration,
+}
+
+#[cfg(feature = "simple")]
+impl Timings {
+    /// Returns the total time spent across every part of the ping.
+    pub fn total(&self) -> Duration {
+        self.dns + self.srv + self.connect + self.handshake_to_status + self.ping_rtt
+    }
 }
 
 #[cfg(feature = "simple")]
-pub async fn ping(addrs: (String, u16)) -> Result<(JavaServerInfo, Duration), PingError> {
-    let mut client = connect(addrs).await?;
-    client.handshake().await?;
-    let status = client.get_status().await?;
-    let latency = client.get_latency().await?;
-    client.disconnect().await?;
-    Ok((status, latency))
+pub async fn ping(addrs: impl IntoServerAddress) -> Result<(JavaServerInfo, Duration), PingError> {
+    let outcome = ping_outcome(addrs).await?;
+    Ok((outcome.info, outcome.latency))
 }
 
 #[cfg(feature = "simple")]
 pub async fn ping_or_timeout(
-    addrs: (String, u16),
+    addrs: impl IntoServerAddress,
+    timeout: Duration,
+) -> Result<(JavaServerInfo, Duration), PingError> {
+    let target = parse_target(addrs)?;
+    or_timeout(ping(target.clone()), timeout, target).await
+}
+
+/// Ping a server and return its status along with details about the connection and how long each step took.
+#[cfg(feature = "simple")]
+pub async fn ping_outcome(addrs: impl IntoServerAddress) -> Result<PingOutcome, PingError> {
+    ping_outcome_with_options(addrs, JavaPingOptions::default()).await
+}
+
+/// Options for pinging a Java Edition server.
+#[cfg(feature = "simple")]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct JavaPingOptions {
+    /// Whether to ping the way the vanilla client does, for servers behind anti-bot filters that drop pings
+    /// from other clients. The handshake contains the address as it was given rather than the SRV target,
+    /// small packets are sent immediately, and the packets are grouped and filled in like the vanilla
+    /// client's. See [`SlpProtocol::set_mimic_vanilla`]. Defaults to `false`.
+    pub mimic_vanilla: bool,
+}
+
+#[cfg(feature = "simple")]
+impl JavaPingOptions {
+    pub fn with_mimic_vanilla(mut self, mimic_vanilla: bool) -> Self {
+        self.mimic_vanilla = mimic_vanilla;
+        self
+    }
+}
+
+/// Like [`ping_outcome`], but pings the server as described by `options`.
+#[cfg(feature = "simple")]
+pub async fn ping_outcome_with_options(
+    addrs: impl IntoServerAddress,
+    options: JavaPingOptions,
+) -> Result<PingOutcome, PingError> {
+    #[cfg(feature = "metrics")]
+    metrics::ping_started(address::Edition::Java);
+    let result = ping_outcome_once(addrs, options).await;
+    #[cfg(feature = "metrics")]
+    match &result {
+        Ok(outcome) => metrics::ping_succeeded(address::Edition::Java, outcome.latency),
+        Err(error) => metrics::java_ping_failed(error),
+    }
+    result
+}
+
+#[cfg(feature = "simple")]
+async fn ping_outcome_once(
+    addrs: impl IntoServerAddress,
+    options: JavaPingOptions,
+) -> Result<PingOutcome, PingError> {
+    let timestamp = SystemTime::now();
+    let start = Instant::now();
+    let target = parse_target(addrs)?;
+    let in_phase = |phase, address| {
+        let target = target.clone();
+        move |_: &mut protocol::ProtocolError| ProtocolSnafu {
+            phase: Some(phase),
+            elapsed: start.elapsed(),
+            target: PingTarget {
+                server: Some(target),
+                address,
+            },
+        }
+    };
+
+    let addrs: (String, u16) = target.clone().into();
+    let resolved = protocol::resolve(target.clone())
+        .await
+        .with_context(in_phase(PingPhase::Resolve, None))?;
+    let address = Some(resolved.address);
+    let mut client = protocol::connect_resolved(resolved)
+        .await
+        .with_context(in_phase(PingPhase::Connect, address))?;
+    let connection = client
+        .connection_info()
+        .cloned()
+        .expect("connect always records connection info");
+    // the connection may have fallen back to another SRV record
+    let address = Some(connection.address);
+    if options.mimic_vanilla {
+        client.set_mimic_vanilla(true);
+        client.set_handshake_address(addrs.0.clone(), addrs.1);
+        client
+            .set_nodelay(true)
+            .with_context(in_phase(PingPhase::Connect, address))?;
+    }
+
+    let handshake_start = Instant::now();
+    // the handshake goes out in the same write as the status request
+    client
+        .queue_frame(client.create_handshake_frame())
+        .with_context(in_phase(PingPhase::Handshake, address))?;
+    let (info, raw_json) = client
+        .get_status_with_json()
+        .await
+        .with_context(in_phase(PingPhase::Status, address))?;
+    let handshake_to_status = handshake_start.elapsed();
+
+    let latency = client
+        .get_latency()
+        .await
+        .with_context(in_phase(PingPhase::Latency, address))?;
+    client
+        .disconnect()
+        .await
+        .with_context(in_phase(PingPhase::Disconnect, address))?;
+
+    Ok(PingOutcome {
+        info,
+        raw_json,
+        latency,
+        timestamp,
+        target: addrs,
+        timings: Timings {
+            dns: connection.dns_time,
+            srv: connection.srv_time,
+            connect: connection.connect_time,
+            handshake_to_status,
+            ping_rtt: latency,
+        },
+        connection,
+        attempts: 1,
+    })
+}
+
+#[cfg(feature = "simple")]
+pub async fn ping_outcome_or_timeout(
+    addrs: impl IntoServerAddress,
+    timeout: Duration,
+) -> Result<PingOutcome, PingError> {
+    let target = parse_target(addrs)?;
+    or_timeout(ping_outcome(target.clone()), timeout, target).await
+}
+
+/// Ping a server, reconnecting and trying again if an attempt doesn't finish within `timeout` or fails with an
+/// I/O error, such as the connection being refused or closed.
+///
+/// Up to `attempts` attempts are made, but always at least one. Other errors, like an invalid response, are
+/// returned straight away, since trying again is unlikely to help. Otherwise, the error from the last attempt is
+/// returned if they all fail.
+#[cfg(feature = "simple")]
+pub async fn ping_with_retries(
+    addrs: impl IntoServerAddress,
+    attempts: u32,
     timeout: Duration,
 ) -> Result<(JavaServerInfo, Duration), PingError> {
+    let outcome = ping_outcome_with_retries(addrs, attempts, timeout).await?;
+    Ok((outcome.info, outcome.latency))
+}
+
+/// Like [`ping_with_retries`], but returns details about the successful attempt.
+#[cfg(feature = "simple")]
+pub async fn ping_outcome_with_retries(
+    addrs: impl IntoServerAddress,
+    attempts: u32,
+    timeout: Duration,
+) -> Result<PingOutcome, PingError> {
+    let addrs = parse_target(addrs)?;
+    let mut attempt = 1;
+    loop {
+        match ping_outcome_or_timeout(addrs.clone(), timeout).await {
+            Ok(mut outcome) => {
+                outcome.attempts = attempt;
+                return Ok(outcome);
+            }
+            Err(error) if attempt < attempts && is_transient(&error) => {
+                crate::log::debug!(attempt, %error, "Ping attempt failed");
+                attempt += 1;
+            }
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+/// Returns whether a ping that failed with `error` might succeed if it was tried again.
+#[cfg(feature = "simple")]
+fn is_transient(error: &PingError) -> bool {
+    use crate::protocol::ProtocolError;
+    match error {
+        PingError::Timeout { .. } => true,
+        PingError::Protocol { source, .. } => matches!(
+            source,
+            ProtocolError::Io { .. } | ProtocolError::ConnectionClosed { .. }
+        ),
+        #[cfg(feature = "blocking")]
+        PingError::Runtime { .. } => false,
+    }
+}
+
+/// The username used by [`probe_online_mode`].
+#[cfg(feature = "simple")]
+pub const PROBE_USERNAME: &str = "ElytraPing";
+
+/// Finds out whether a server authenticates players by starting a login, which isn't shown in its status.
+///
+/// The server is pinged first to find its protocol version, then a second connection starts a login as
+/// [`PROBE_USERNAME`] and abandons it once the server responds. See [`SlpProtocol::probe_login`].
+#[cfg(feature = "simple")]
+pub async fn probe_online_mode(
+    addrs: impl IntoServerAddress,
+    timeout: Duration,
+) -> Result<protocol::LoginProbe, PingError> {
+    let addrs = parse_target(addrs)?;
+    or_timeout(
+        async {
+            let start = Instant::now();
+            let outcome = ping_outcome(addrs.clone()).await?;
+            let protocol = outcome
+                .info
+                .version
+                .as_ref()
+                .and_then(|version| i32::try_from(version.protocol).ok())
+                .unwrap_or(protocol::Frame::PROTOCOL_VERSION);
+            let address = outcome.connection.address;
+            let in_phase = |phase| {
+                let target = addrs.clone();
+                move |_: &mut protocol::ProtocolError| ProtocolSnafu {
+                    phase: Some(phase),
+                    elapsed: start.elapsed(),
+                    target: PingTarget {
+                        server: Some(target),
+                        address: Some(address),
+                    },
+                }
+            };
+            let mut client = protocol::connect_resolved(outcome.connection.into())
+                .await
+                .with_context(in_phase(PingPhase::Connect))?;
+            let probe = client
+                .probe_login(protocol, PROBE_USERNAME)
+                .await
+                .with_context(in_phase(PingPhase::Login))?;
+            // the server may have already closed the connection
+            let _ = client.disconnect().await;
+            Ok(probe)
+        },
+        timeout,
+        addrs.clone(),
+    )
+    .await
+}
+
+/// Parses the address of the server to ping, failing while resolving it if it's invalid.
+#[cfg(feature = "simple")]
+fn parse_target(addrs: impl IntoServerAddress) -> Result<ServerAddress, PingError> {
+    addrs
+        .into_server_address(address::Edition::Java)
+        .map_err(protocol::ProtocolError::from)
+        .context(ProtocolSnafu {
+            phase: Some(PingPhase::Resolve),
+            elapsed: Duration::ZERO,
+            target: PingTarget::default(),
+        })
+}
+
+#[cfg(feature = "simple")]
+async fn or_timeout<T>(
+    future: impl std::future::Future<Output = Result<T, PingError>>,
+    timeout: Duration,
+    target: ServerAddress,
+) -> Result<T, PingError> {
     use tokio::{select, time};
     let sleep = time::sleep(timeout);
     tokio::pin!(sleep);
+    tokio::pin!(future);
 
     select! {
         biased;
-        info = ping(addrs) => info,
-        _ = sleep => TimeoutSnafu.fail(),
+        result = &mut future => result,
+        _ = sleep => {
+            #[cfg(feature = "metrics")]
+            metrics::ping_failed(address::Edition::Java, "timeout");
+            TimeoutSnafu { target }.fail()
+        }
     }
 }
 
@@ -153,6 +667,75 @@ mod tests {
 
     const PING_TIMEOUT: Duration = Duration::from_secs(5);
 
+    #[tokio::test]
+    async fn connection_refused_phase() {
+        // bind then drop a listener to find a port nothing is listening on
+        let port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let error = ping(("127.0.0.1".to_owned(), port)).await.unwrap_err();
+        assert!(matches!(
+            error,
+            PingError::Protocol {
+                phase: Some(PingPhase::Connect),
+                source: protocol::ProtocolError::Io { .. },
+                ..
+            }
+        ));
+        assert!(error.to_string().starts_with(&format!(
+            "Connection to 127.0.0.1:{port} (127.0.0.1:{port}) failed while connecting"
+        )));
+    }
+
+    #[tokio::test]
+    async fn invalid_address_phase() {
+        let error = ping("localhost:port").await.unwrap_err();
+        assert!(matches!(
+            error,
+            PingError::Protocol {
+                phase: Some(PingPhase::Resolve),
+                source: protocol::ProtocolError::InvalidAddress { .. },
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn ping_target_display() {
+        let server: ServerAddress = "example.com".parse().unwrap();
+        let address: SocketAddr = "203.0.113.7:25565".parse().unwrap();
+        let target = PingTarget {
+            server: Some(server.clone()),
+            address: Some(address),
+        };
+        assert_eq!(target.to_string(), "example.com:25565 (203.0.113.7:25565)");
+        let target = PingTarget {
+            server: None,
+            address: Some(address),
+        };
+        assert_eq!(target.to_string(), "203.0.113.7:25565");
+        assert_eq!(PingTarget::default().to_string(), "an unknown server");
+    }
+
+    #[test]
+    fn protocol_error_converts() {
+        fn status() -> Result<(), PingError> {
+            Err(protocol::ProtocolError::ConnectionClosed {
+                backtrace: snafu::GenerateImplicitData::generate(),
+            })?;
+            Ok(())
+        }
+        let error = status().unwrap_err();
+        assert!(matches!(error, PingError::Protocol { phase: None, .. }));
+        assert_eq!(error.target(), None);
+        assert_eq!(
+            error.to_string(),
+            "Connection failed: Connection closed unexpectedly."
+        );
+    }
+
     #[tokio::test]
     async fn hypixel() {
         let address = "mc.hypixel.net".to_owned();
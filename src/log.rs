@@ -0,0 +1,46 @@
+//! Logging macros that forward to `tracing` when the `tracing` feature is enabled, and otherwise expand to
+//! nothing.
+
+// not every combination of features logs at every level
+#[cfg(feature = "tracing")]
+#[allow(unused_imports)]
+pub(crate) use tracing::{debug, error, info, trace};
+
+/// Accepts the same fields and message as the `tracing` macros, but never evaluates them. They're still
+/// type checked, so variables that are only logged don't become unused.
+#[cfg(not(feature = "tracing"))]
+macro_rules! disabled {
+    (@fields $message:literal $(, $arg:expr)* $(,)?) => {
+        let _ = format_args!($message $(, $arg)*);
+    };
+    (@fields $name:ident = % $value:expr, $($rest:tt)*) => {
+        $crate::log::disabled!(@fields $value, $($rest)*);
+    };
+    (@fields $name:ident = ? $value:expr, $($rest:tt)*) => {
+        $crate::log::disabled!(@fields $value, $($rest)*);
+    };
+    (@fields $name:ident = $value:expr, $($rest:tt)*) => {
+        $crate::log::disabled!(@fields $value, $($rest)*);
+    };
+    (@fields % $value:expr, $($rest:tt)*) => {
+        $crate::log::disabled!(@fields $value, $($rest)*);
+    };
+    (@fields ? $value:expr, $($rest:tt)*) => {
+        $crate::log::disabled!(@fields $value, $($rest)*);
+    };
+    (@fields $value:expr, $($rest:tt)*) => {
+        let _ = &$value;
+        $crate::log::disabled!(@fields $($rest)*);
+    };
+    ($($arg:tt)*) => {
+        if false {
+            $crate::log::disabled!(@fields $($arg)*);
+        }
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+#[allow(unused_imports)]
+pub(crate) use {
+    disabled, disabled as debug, disabled as error, disabled as info, disabled as trace,
+};
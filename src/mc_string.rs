@@ -1,7 +1,7 @@
-use bytes::Buf;
-use mc_varint::{VarInt, VarIntRead, VarIntWrite};
+use crate::varint::{VarIntBuf, VarIntBufMut, VarIntError, MAX_VAR_INT_LEN};
+use bytes::{Buf, Bytes};
 use snafu::{OptionExt, Snafu};
-use std::io::Cursor;
+use std::{fmt, io::Cursor, ops::Deref};
 
 #[derive(Snafu, Debug)]
 pub enum McStringError {
@@ -11,48 +11,220 @@ pub enum McStringError {
         backtrace: snafu::Backtrace,
     },
     #[snafu(display(
-        "string is too long (is {length} bytes, but expected less than {} bytes)",
-        MAX_LEN
+        "string is too long (is {length} characters, but the limit is {max} characters)"
     ))]
     TooLong {
         length: usize,
+        max: usize,
         backtrace: snafu::Backtrace,
     },
     #[snafu(display("invalid string format"))]
     InvalidFormat { backtrace: snafu::Backtrace },
+    /// The buffer ends before the string does, so more data must be read before it can be decoded.
+    #[snafu(display("incomplete string"))]
+    Incomplete { backtrace: snafu::Backtrace },
 }
 
+/// The longest length a string's length prefix can hold.
 pub const MAX_LEN: i32 = i32::MAX;
+/// The longest string allowed in most fields, such as a status response that is sent, in UTF-16 code units.
+pub const MAX_STRING_LEN: usize = 32767;
+/// The longest status response JSON that will be decoded, in UTF-16 code units.
+///
+/// Servers with an embedded favicon or a long mod list often send more than [`MAX_STRING_LEN`], which vanilla
+/// clients accept, so this is as long as a packet can be.
+pub const MAX_STATUS_RESPONSE_LEN: usize = 2097151;
+/// The longest server address allowed in a handshake, in UTF-16 code units.
+pub const MAX_ADDRESS_LEN: usize = 255;
+/// The longest username allowed in a login, in UTF-16 code units.
+pub const MAX_USERNAME_LEN: usize = 16;
+/// The longest disconnect reason allowed during login, in UTF-16 code units.
+pub const MAX_LOGIN_DISCONNECT_LEN: usize = 262144;
 
+/// Encodes a string that is at most [`MAX_STRING_LEN`] characters long.
 pub fn encode_mc_string(string: &str) -> Result<Vec<u8>, McStringError> {
+    encode_mc_string_with_limit(string, MAX_STRING_LEN)
+}
+
+/// Encodes a string, failing if it's longer than `max_len` UTF-16 code units, which is how the protocol
+/// measures strings.
+pub fn encode_mc_string_with_limit(string: &str, max_len: usize) -> Result<Vec<u8>, McStringError> {
+    check_length(string, max_len)?;
     let len = string.len();
-    // VarInt max length is 5 bytes
-    let mut bytes = Vec::with_capacity(len + 5);
-    bytes.write_var_int(VarInt::from(
-        i32::try_from(len)
-            .ok()
-            .context(TooLongSnafu { length: len })?,
-    ))?;
+    let mut bytes = Vec::with_capacity(len + MAX_VAR_INT_LEN);
+    bytes.put_var_int(i32::try_from(len).ok().context(TooLongSnafu {
+        length: len,
+        max: max_len,
+    })?);
     bytes.extend_from_slice(string.as_bytes());
     Ok(bytes)
 }
 
+/// Decodes a string that is at most [`MAX_STRING_LEN`] characters long.
 pub fn decode_mc_string(cursor: &mut Cursor<&[u8]>) -> Result<String, McStringError> {
-    let len: i32 = cursor.read_var_int()?.into();
-    let len = usize::try_from(len).ok().context(InvalidFormatSnafu)?;
+    decode_mc_string_with_limit(cursor, MAX_STRING_LEN)
+}
 
-    let bytes = cursor.chunk();
-    if len > bytes.len() {
-        return InvalidFormatSnafu.fail();
+/// Decodes a string, failing if it's longer than `max_len` UTF-16 code units.
+///
+/// If the buffer ends before the string does, [`McStringError::Incomplete`] is returned and the cursor is left
+/// where it was, so decoding can be retried once more data has been read.
+pub fn decode_mc_string_with_limit(
+    cursor: &mut Cursor<&[u8]>,
+    max_len: usize,
+) -> Result<String, McStringError> {
+    decode_mc_str(cursor, max_len).map(str::to_owned)
+}
+
+/// Like [`decode_mc_string_with_limit`], but borrows the string from the buffer instead of copying it.
+pub fn decode_mc_str<'a>(
+    cursor: &mut Cursor<&'a [u8]>,
+    max_len: usize,
+) -> Result<&'a str, McStringError> {
+    let start = cursor.position();
+    let result = decode_str(cursor, max_len);
+    if result.is_err() {
+        cursor.set_position(start);
     }
-    let string = std::str::from_utf8(bytes.get(..len).context(InvalidFormatSnafu)?)
+    result
+}
+
+fn decode_str<'a>(cursor: &mut Cursor<&'a [u8]>, max_len: usize) -> Result<&'a str, McStringError> {
+    let len = match cursor.get_var_int() {
+        Ok(len) => len,
+        Err(VarIntError::Incomplete { .. }) => return IncompleteSnafu.fail(),
+        Err(VarIntError::TooLong { .. }) => return InvalidFormatSnafu.fail(),
+    };
+    let len = usize::try_from(len).ok().context(InvalidFormatSnafu)?;
+    // each UTF-16 code unit takes at most 3 bytes in UTF-8
+    if len > max_len.saturating_mul(3) {
+        return TooLongSnafu {
+            length: len,
+            max: max_len,
+        }
+        .fail();
+    }
+
+    let buffer: &'a [u8] = cursor.get_ref();
+    let start = cursor.position() as usize;
+    let bytes = buffer
+        .get(start..)
+        .and_then(|rest| rest.get(..len))
+        .context(IncompleteSnafu)?;
+    let string = std::str::from_utf8(bytes)
         .ok()
-        .context(InvalidFormatSnafu)?
-        .to_string();
+        .context(InvalidFormatSnafu)?;
+    check_length(string, max_len)?;
     cursor.advance(len);
     Ok(string)
 }
 
+/// A string stored in [`Bytes`], so it can share the buffer it was received in instead of being copied out of it.
+///
+/// The bytes are always valid UTF-8: every constructor either checks them or takes them from a `str`.
+#[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct BytesStr(Bytes);
+
+impl BytesStr {
+    /// Wraps bytes that are valid UTF-8, or returns them if they aren't.
+    pub fn from_utf8(bytes: Bytes) -> Result<Self, Bytes> {
+        match std::str::from_utf8(&bytes) {
+            Ok(_) => Ok(Self(bytes)),
+            Err(_) => Err(bytes),
+        }
+    }
+
+    /// Returns the part of `bytes` that `string` was borrowed from.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `string` isn't a slice of `bytes`.
+    pub(crate) fn slice_ref(bytes: &Bytes, string: &str) -> Self {
+        Self(bytes.slice_ref(string.as_bytes()))
+    }
+
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the bytes are checked to be UTF-8 or taken from a `str` when the `BytesStr` is created, and
+        // they can't be changed afterwards
+        unsafe { std::str::from_utf8_unchecked(&self.0) }
+    }
+
+    pub fn into_bytes(self) -> Bytes {
+        self.0
+    }
+}
+
+impl Deref for BytesStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for BytesStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl fmt::Debug for BytesStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for BytesStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<String> for BytesStr {
+    fn from(string: String) -> Self {
+        Self(Bytes::from(string))
+    }
+}
+
+impl From<&'static str> for BytesStr {
+    fn from(string: &'static str) -> Self {
+        Self(Bytes::from_static(string.as_bytes()))
+    }
+}
+
+impl From<BytesStr> for String {
+    fn from(string: BytesStr) -> Self {
+        string.as_str().to_owned()
+    }
+}
+
+impl PartialEq<str> for BytesStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for BytesStr {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+pub(crate) fn check_length(string: &str, max_len: usize) -> Result<(), McStringError> {
+    // UTF-8 is never shorter than UTF-16, so most strings don't need counting
+    if string.len() > max_len {
+        let length = string.encode_utf16().count();
+        if length > max_len {
+            return TooLongSnafu {
+                length,
+                max: max_len,
+            }
+            .fail();
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,4 +237,44 @@ mod tests {
         let decoded_string = decode_mc_string(&mut cursor).unwrap();
         assert_eq!(decoded_string, STRING);
     }
+
+    #[test]
+    fn incomplete_string() {
+        let bytes = encode_mc_string("hello").unwrap();
+        for len in 0..bytes.len() {
+            let mut cursor = Cursor::new(&bytes[..len]);
+            assert!(matches!(
+                decode_mc_string(&mut cursor),
+                Err(McStringError::Incomplete { .. })
+            ));
+            assert_eq!(cursor.position(), 0);
+        }
+        let invalid = [2, 0xff, 0xff];
+        assert!(matches!(
+            decode_mc_string(&mut Cursor::new(&invalid[..])),
+            Err(McStringError::InvalidFormat { .. })
+        ));
+    }
+
+    #[test]
+    fn length_limits() {
+        // the limit counts UTF-16 code units, not bytes
+        let bytes = encode_mc_string_with_limit("ééé", 3).unwrap();
+        assert_eq!(
+            decode_mc_string_with_limit(&mut Cursor::new(bytes.as_slice()), 3).unwrap(),
+            "ééé"
+        );
+        assert!(matches!(
+            decode_mc_string_with_limit(&mut Cursor::new(bytes.as_slice()), 2),
+            Err(McStringError::TooLong {
+                length: 3,
+                max: 2,
+                ..
+            })
+        ));
+        assert!(matches!(
+            encode_mc_string(&"a".repeat(MAX_STRING_LEN + 1)),
+            Err(McStringError::TooLong { .. })
+        ));
+    }
 }
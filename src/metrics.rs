@@ -0,0 +1,96 @@
+//! Metrics reported through the [`metrics`] facade.
+//!
+//! Nothing is collected unless a recorder is installed, such as one from `metrics-exporter-prometheus`. Every
+//! metric has an `edition` label, which is either `java` or `bedrock`.
+
+// which metrics are recorded depends on the editions that are enabled
+#![cfg_attr(not(all(feature = "simple", feature = "bedrock")), allow(dead_code))]
+
+use crate::address::Edition;
+use std::time::Duration;
+
+/// Counter of pings that were started.
+pub const PINGS: &str = "elytra_ping_pings_total";
+/// Counter of pings that failed, with a `kind` label.
+///
+/// For Java Edition, the kind is `timeout` or the phase the ping failed in, such as `connect`. For Bedrock
+/// Edition, it's the kind of error, such as `no_response`.
+pub const FAILURES: &str = "elytra_ping_failures_total";
+/// Histogram of the latency of successful pings, in seconds.
+pub const LATENCY: &str = "elytra_ping_latency_seconds";
+/// Counter of bytes sent to servers.
+///
+/// Only pings are counted, not the traffic of the status servers in the `server` module.
+pub const BYTES_SENT: &str = "elytra_ping_sent_bytes_total";
+/// Counter of bytes received from servers.
+pub const BYTES_RECEIVED: &str = "elytra_ping_received_bytes_total";
+
+fn edition_label(edition: Edition) -> &'static str {
+    match edition {
+        Edition::Java => "java",
+        Edition::Bedrock => "bedrock",
+    }
+}
+
+pub(crate) fn ping_started(edition: Edition) {
+    metrics::counter!(PINGS, "edition" => edition_label(edition)).increment(1);
+}
+
+pub(crate) fn ping_succeeded(edition: Edition, latency: Duration) {
+    metrics::histogram!(LATENCY, "edition" => edition_label(edition)).record(latency);
+}
+
+pub(crate) fn ping_failed(edition: Edition, kind: &'static str) {
+    metrics::counter!(FAILURES, "edition" => edition_label(edition), "kind" => kind).increment(1);
+}
+
+pub(crate) fn bytes_sent(edition: Edition, bytes: usize) {
+    metrics::counter!(BYTES_SENT, "edition" => edition_label(edition)).increment(bytes as u64);
+}
+
+pub(crate) fn bytes_received(edition: Edition, bytes: usize) {
+    metrics::counter!(BYTES_RECEIVED, "edition" => edition_label(edition)).increment(bytes as u64);
+}
+
+/// Records why a Java Edition ping failed.
+#[cfg(feature = "simple")]
+pub(crate) fn java_ping_failed(error: &crate::PingError) {
+    use crate::{PingError, PingPhase};
+
+    let kind = match error {
+        PingError::Protocol { phase, .. } => match phase {
+            Some(PingPhase::Resolve) => "resolve",
+            Some(PingPhase::Connect) => "connect",
+            Some(PingPhase::Handshake) => "handshake",
+            Some(PingPhase::Status) => "status",
+            Some(PingPhase::Latency) => "latency",
+            Some(PingPhase::Login) => "login",
+            Some(PingPhase::Disconnect) => "disconnect",
+            None => "other",
+        },
+        PingError::Timeout { .. } => "timeout",
+        #[allow(unreachable_patterns)]
+        _ => "other",
+    };
+    ping_failed(Edition::Java, kind);
+}
+
+/// Records why a Bedrock Edition ping failed.
+#[cfg(feature = "bedrock")]
+pub(crate) fn bedrock_ping_failed(error: &crate::bedrock::BedrockPingError) {
+    use crate::bedrock::BedrockPingError;
+
+    let kind = match error {
+        BedrockPingError::AddressParse { .. } | BedrockPingError::InvalidAddress { .. } => {
+            "invalid_address"
+        }
+        BedrockPingError::NoResponse { .. } => "no_response",
+        BedrockPingError::ServerInfoParse { .. } => "invalid_motd",
+        BedrockPingError::Io { .. } => "io",
+        BedrockPingError::DNSLookupFailed { .. } => "resolve",
+        BedrockPingError::Timeout { .. } => "timeout",
+        BedrockPingError::InvalidPong { .. } => "invalid_pong",
+        BedrockPingError::ConnectFailed { .. } => "connect",
+    };
+    ping_failed(Edition::Bedrock, kind);
+}
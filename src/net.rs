@@ -0,0 +1,80 @@
+//! Networking primitives, which are replaced with simulated ones when built with `--cfg turmoil`.
+//!
+//! With `turmoil`, every connection Elytra Ping makes goes through the simulated network, so connection and
+//! retry logic can be tested deterministically under packet loss and latency. Elytra Ping can then only be
+//! used from inside a turmoil simulation, so only the simulation tests should be run:
+//!
+//! ```sh
+//! RUSTFLAGS="--cfg turmoil" cargo test --lib --features test_util net::
+//! ```
+
+#[cfg(not(turmoil))]
+pub(crate) use tokio::net::lookup_host;
+#[cfg(all(any(feature = "test_util", feature = "server"), not(turmoil)))]
+pub(crate) use tokio::net::TcpListener;
+#[cfg(all(feature = "java_connect", not(turmoil)))]
+pub(crate) use tokio::net::TcpStream;
+#[cfg(all(any(feature = "bedrock", feature = "server"), not(turmoil)))]
+pub(crate) use tokio::net::UdpSocket;
+
+#[cfg(turmoil)]
+pub(crate) use turmoil::net::lookup_host;
+#[cfg(all(any(feature = "test_util", feature = "server"), turmoil))]
+pub(crate) use turmoil::net::TcpListener;
+#[cfg(all(feature = "java_connect", turmoil))]
+pub(crate) use turmoil::net::TcpStream;
+#[cfg(all(any(feature = "bedrock", feature = "server"), turmoil))]
+pub(crate) use turmoil::net::UdpSocket;
+
+#[cfg(all(test, turmoil, feature = "test_util"))]
+mod tests {
+    use super::*;
+    use crate::bedrock::test_support::pong_packet;
+    use crate::test_util::{MockServer, MockServerConfig};
+    use std::time::Duration;
+
+    #[test]
+    fn java_ping_through_latency() {
+        let mut sim = turmoil::Builder::new()
+            .min_message_latency(Duration::from_millis(50))
+            .max_message_latency(Duration::from_millis(50))
+            .build();
+        sim.host("server", || async {
+            let _server = MockServer::start_on(25565, MockServerConfig::default()).await?;
+            std::future::pending::<()>().await;
+            Ok(())
+        });
+        sim.client("client", async {
+            let (info, _) = crate::ping(("server".to_owned(), 25565)).await?;
+            assert_eq!(info.players.unwrap().max, 20);
+            Ok(())
+        });
+        sim.run().unwrap();
+    }
+
+    #[test]
+    fn bedrock_retries_through_packet_loss() {
+        let mut sim = turmoil::Builder::new()
+            .rng_seed(3)
+            .fail_rate(0.5)
+            .repair_rate(0.5)
+            .build();
+        sim.host("server", || async {
+            let socket = UdpSocket::bind("0.0.0.0:19132").await?;
+            let mut request = [0; 1024];
+            loop {
+                let (_, client) = socket.recv_from(&mut request).await?;
+                let pong = pong_packet(&request[1..9], "MCPE;Simulated;390;1.14.60;0;10");
+                socket.send_to(&pong, client).await?;
+            }
+        });
+        sim.client("client", async {
+            let (info, _) =
+                crate::bedrock::ping(("server".to_owned(), 19132), Duration::from_millis(200), 20)
+                    .await?;
+            assert_eq!(info.name, "Simulated");
+            Ok(())
+        });
+        sim.run().unwrap();
+    }
+}
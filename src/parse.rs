@@ -1,59 +1,267 @@
+pub use self::diff::StatusChange;
+#[cfg(feature = "image")]
+pub use self::favicon::decode_favicon_image;
+pub use self::favicon::{
+    decode_favicon, encode_favicon, FaviconError, FaviconHash, FaviconReport, PngColorType,
+    FAVICON_PREFIX, FAVICON_SIZE,
+};
+pub use self::fingerprint::ServerFingerprint;
+pub use self::font::{center_motd, text_width, MOTD_WIDTH};
+pub use self::markdown::{
+    MarkdownColors, MarkdownFlavor, MarkdownOptions, ObfuscatedText, ToAnsi, ToMarkdown,
+};
+pub use self::mods::{ModEntry, ModList, ModPlatform};
+pub use self::version::VersionName;
+use alloc::{string::String, vec::Vec};
+use core::fmt;
 use serde::{Deserialize, Serialize};
 
+mod diff;
+mod favicon;
+mod fingerprint;
+mod font;
+mod forge;
+mod lenient;
+mod markdown;
+mod mods;
+mod version;
+
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 #[serde(rename_all = "camelCase")]
 pub struct JavaServerInfo {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub version: Option<ServerVersion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub players: Option<ServerPlayers>,
     pub description: TextComponent,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub favicon: Option<String>,
-    #[serde(rename = "modinfo")]
+    #[serde(rename = "modinfo", skip_serializing_if = "Option::is_none")]
     pub mod_info: Option<ServerModInfo>,
+    /// Mod information sent by Forge servers running Minecraft 1.13 or later.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub forge_data: Option<ServerForgeData>,
     /// Servers with the No Chat Reports mod installed will set this field to `true` to indicate
     /// to players that all chat messages sent on this server are not reportable to Mojang.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub prevents_chat_reports: Option<bool>,
     /// If the server supports Chat Preview (added in 1.19 and removed in 1.19.3), this field is set to `true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub previews_chat: Option<bool>,
     /// Servers will set this field to `true` if they block chat messages that cannot be reported to Mojang.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub enforces_secure_chat: Option<bool>,
+    /// The modpack the server runs, sent by modded servers with a mod like Better Compatibility Checker.
+    ///
+    /// This is boxed since few servers send it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub modpack_data: Option<alloc::boxed::Box<ServerModpackData>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub struct ServerVersion {
     pub name: String,
-    pub protocol: u32,
+    /// The protocol number of the server's version. Proxies and plugins that hide the version may send a number
+    /// that isn't a real version, such as -1.
+    pub protocol: i64,
+}
+
+impl ServerVersion {
+    pub fn new(name: impl Into<String>, protocol: i64) -> Self {
+        Self {
+            name: name.into(),
+            protocol,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub struct ServerPlayers {
+    /// The maximum number of players. Counts that aren't valid numbers of players, such as negative numbers or
+    /// numbers sent as strings, are clamped or parsed rather than failing the whole status.
+    #[serde(deserialize_with = "lenient::player_count")]
     pub max: u32,
+    /// The number of online players, which is deserialized like `max`.
+    #[serde(deserialize_with = "lenient::player_count")]
     pub online: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sample: Option<Vec<ServerPlayersSample>>,
 }
 
+impl ServerPlayers {
+    pub fn new(max: u32, online: u32) -> Self {
+        Self {
+            max,
+            online,
+            sample: None,
+        }
+    }
+
+    pub fn with_sample(mut self, sample: Vec<ServerPlayersSample>) -> Self {
+        self.sample = Some(sample);
+        self
+    }
+
+    /// Returns the sample entries that identify real players, along with how many online players were not identified.
+    pub fn real_players(&self) -> RealPlayers<'_> {
+        let sample = self.sample.as_deref().unwrap_or_default();
+        let players: Vec<_> = sample
+            .iter()
+            .filter(|player| !player.is_anonymous())
+            .collect();
+        RealPlayers {
+            anonymous: sample.len() - players.len(),
+            hidden: self
+                .online
+                .saturating_sub(u32::try_from(players.len()).unwrap_or(u32::MAX)),
+            players,
+        }
+    }
+
+    /// Returns whether the UUIDs in the sample suggest the server runs in offline mode, or `None` if the sample
+    /// has no players with an online or offline mode UUID.
+    ///
+    /// This is only a hint: a proxy in online mode can forward offline mode UUIDs, and an offline mode server may
+    /// have no players in its sample.
+    pub fn sample_suggests_offline_mode(&self) -> Option<bool> {
+        let (mut online, mut offline) = (0, 0);
+        for player in self.sample.as_deref().unwrap_or_default() {
+            match player.uuid_kind() {
+                Some(PlayerUuidKind::Online) => online += 1,
+                Some(PlayerUuidKind::Offline) => offline += 1,
+                _ => {}
+            }
+        }
+        (online + offline > 0).then_some(offline > online)
+    }
+
+    /// Returns whether the sample is being used to show text, such as an advertisement, instead of players.
+    ///
+    /// Servers do this with entries whose names contain formatting codes or spaces, or whose UUIDs are missing,
+    /// nil or repeated. Placeholders for [anonymous](ServerPlayersSample::is_anonymous) players are ignored.
+    pub fn sample_is_advertisement(&self) -> bool {
+        let mut ids = Vec::new();
+        for player in self.sample.as_deref().unwrap_or_default() {
+            if player.is_anonymous() && player.name.as_deref() == Some(ANONYMOUS_PLAYER_NAME) {
+                continue;
+            }
+            if player.is_anonymous() || !player.name.as_deref().map_or(false, is_username) {
+                return true;
+            }
+            ids.extend(player.id.as_deref());
+        }
+        let len = ids.len();
+        ids.sort_unstable();
+        ids.dedup();
+        ids.len() != len
+    }
+}
+
+/// The kind of UUID a player in the sample has, returned by [`ServerPlayersSample::uuid_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PlayerUuidKind {
+    /// A version 4 UUID, which Mojang gives to accounts. Servers in online mode report these.
+    Online,
+    /// A version 3 UUID derived from the player's name, which servers in offline mode report.
+    Offline,
+    /// The nil UUID, which is sent for anonymous players.
+    Nil,
+    /// Any other UUID, such as the ones Floodgate gives to Bedrock Edition players.
+    Other,
+}
+
+/// The name vanilla servers send for players who have chosen not to be shown in the sample.
+const ANONYMOUS_PLAYER_NAME: &str = "Anonymous Player";
+
+/// Returns whether `name` could be a player's username, allowing the `.` and `*` prefixes that Floodgate gives
+/// Bedrock Edition players.
+fn is_username(name: &str) -> bool {
+    let name = name.strip_prefix(['.', '*']).unwrap_or(name);
+    (1..=16).contains(&name.len()) && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// The non-anonymous players in a server's player sample.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RealPlayers<'a> {
+    /// The sample entries that identify real players.
+    pub players: Vec<&'a ServerPlayersSample>,
+    /// The number of sample entries that were anonymous.
+    pub anonymous: usize,
+    /// The number of online players that are not in `players`, either because they were anonymous or not sampled.
+    pub hidden: u32,
+}
+
 /// Contains basic information about one of the players in a server.
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub struct ServerPlayersSample {
     /// The player's username
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
     /// The player's UUID
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
 }
 
 impl ServerPlayersSample {
+    pub fn new(name: impl Into<String>, id: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            id: Some(id.into()),
+        }
+    }
+
     /// Returns whether the server has chosen to hide this player's identity and is reporting placeholder information. This is generally caused by a player having the [Allow Server Listings](https://wiki.vg/Protocol#Client_Information_.28configuration.29) option set to `false`.
     pub fn is_anonymous(&self) -> bool {
         self.id
             .as_deref()
             .map_or(true, |id| id == "00000000-0000-0000-0000-000000000000")
     }
+
+    /// Classifies the player's UUID by its version, or returns `None` if the server didn't send a valid one.
+    pub fn uuid_kind(&self) -> Option<PlayerUuidKind> {
+        let id = self.id.as_deref()?;
+        let digits = match id.len() {
+            32 => id,
+            36 if id
+                .char_indices()
+                .all(|(i, c)| (c == '-') == matches!(i, 8 | 13 | 18 | 23)) =>
+            {
+                id
+            }
+            _ => return None,
+        };
+        let mut digits = digits.chars().filter(|&c| c != '-');
+        if !digits.clone().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        if digits.clone().all(|c| c == '0') {
+            return Some(PlayerUuidKind::Nil);
+        }
+        Some(match digits.nth(12) {
+            Some('4') => PlayerUuidKind::Online,
+            Some('3') => PlayerUuidKind::Offline,
+            _ => PlayerUuidKind::Other,
+        })
+    }
+
+    /// Parses the player's UUID, or returns `None` if the server did not send one.
+    #[cfg(feature = "uuid")]
+    pub fn uuid(&self) -> Result<Option<uuid::Uuid>, uuid::Error> {
+        self.id.as_deref().map(uuid::Uuid::parse_str).transpose()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub struct ServerModInfo {
     #[serde(rename = "type")]
@@ -63,6 +271,7 @@ pub struct ServerModInfo {
 }
 
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub struct ServerMod {
     #[serde(rename = "modid")]
@@ -70,7 +279,204 @@ pub struct ServerMod {
     pub version: String,
 }
 
-impl std::str::FromStr for JavaServerInfo {
+/// The modpack a modded server runs, so launchers can match the server to the pack on CurseForge or FTB.
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase")]
+pub struct ServerModpackData {
+    /// The id of the modpack's project on its platform.
+    #[serde(rename = "projectID", skip_serializing_if = "Option::is_none")]
+    pub project_id: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// The id of the modpack version's file on its platform.
+    #[serde(rename = "versionID", skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<u64>,
+    /// The release channel of the version, like `Release` or `Beta`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_type: Option<String>,
+    /// Whether this data was set by the server's owner rather than read from the modpack's files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_metadata: Option<bool>,
+}
+
+/// Mod information sent by modern Forge servers.
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[non_exhaustive]
+#[serde(rename_all = "camelCase", from = "forge::RawServerForgeData")]
+pub struct ServerForgeData {
+    #[serde(default)]
+    pub channels: Vec<ServerForgeChannel>,
+    #[serde(default)]
+    pub mods: Vec<ServerForgeMod>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fml_network_version: Option<u32>,
+    /// Whether the server omitted some of its mods from the response.
+    #[serde(default)]
+    pub truncated: bool,
+    /// The mods and channels compressed into a string, which Forge sends instead of listing them since
+    /// Minecraft 1.18.1.
+    ///
+    /// They're decoded into `mods` and `channels` when the status is parsed. If this is set but `mods` is empty,
+    /// the string couldn't be decoded.
+    #[serde(rename = "d", skip_serializing_if = "Option::is_none")]
+    pub compressed: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[non_exhaustive]
+pub struct ServerForgeChannel {
+    pub res: String,
+    pub version: String,
+    pub required: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+#[non_exhaustive]
+pub struct ServerForgeMod {
+    #[serde(rename = "modId")]
+    pub mod_id: String,
+    /// The mod's version, or a marker like `"ANY"` or `"OHNOES"` if the mod has no version.
+    #[serde(rename = "modmarker")]
+    pub mod_marker: String,
+}
+
+/// A mod reported by a server, regardless of which field it was listed in.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct ModRef<'a> {
+    pub id: &'a str,
+    pub version: &'a str,
+}
+
+impl JavaServerInfo {
+    /// Creates a status with only a description, such as for a server to send.
+    pub fn new(description: impl Into<TextComponent>) -> Self {
+        Self {
+            version: None,
+            players: None,
+            description: description.into(),
+            favicon: None,
+            mod_info: None,
+            forge_data: None,
+            prevents_chat_reports: None,
+            previews_chat: None,
+            enforces_secure_chat: None,
+            modpack_data: None,
+        }
+    }
+
+    pub fn with_version(mut self, version: ServerVersion) -> Self {
+        self.version = Some(version);
+        self
+    }
+
+    pub fn with_players(mut self, players: ServerPlayers) -> Self {
+        self.players = Some(players);
+        self
+    }
+
+    /// Sets the favicon, which should be a data URL starting with [`FAVICON_PREFIX`].
+    pub fn with_favicon(mut self, favicon: impl Into<String>) -> Self {
+        self.favicon = Some(favicon.into());
+        self
+    }
+
+    /// Decodes the server's favicon into raw PNG bytes, or returns `None` if the server did not send one.
+    pub fn favicon_png(&self) -> Result<Option<Vec<u8>>, FaviconError> {
+        self.favicon.as_deref().map(decode_favicon).transpose()
+    }
+
+    /// Decodes the server's favicon and describes the image it contains, or returns `None` if the server did not send one.
+    ///
+    /// Use [`FaviconReport::is_valid_for_client`] to check whether the vanilla client will display it.
+    pub fn favicon_report(&self) -> Result<Option<FaviconReport>, FaviconError> {
+        self.favicon_png()?
+            .map(|png| FaviconReport::from_png(&png))
+            .transpose()
+    }
+
+    /// Returns a hash of the server's decoded favicon, or `None` if the server did not send one.
+    ///
+    /// This can be stored instead of the favicon itself to detect when a server changes its icon.
+    pub fn favicon_hash(&self) -> Result<Option<FaviconHash>, FaviconError> {
+        Ok(self.favicon_png()?.map(|png| FaviconHash::from_png(&png)))
+    }
+
+    /// Decodes the server's favicon into an image, or returns `None` if the server did not send one.
+    #[cfg(feature = "image")]
+    pub fn favicon_image(&self) -> Result<Option<image::DynamicImage>, FaviconError> {
+        self.favicon_png()?
+            .map(|png| decode_favicon_image(&png))
+            .transpose()
+    }
+
+    /// Returns whether the server reported any mod loader information.
+    pub fn is_modded(&self) -> bool {
+        self.mod_info.is_some() || self.forge_data.is_some()
+    }
+
+    /// Returns every mod the server reported, from both the legacy `modinfo` and modern `forgeData` fields.
+    ///
+    /// Versions are returned as sent, including markers like `ANY`. [`JavaServerInfo::mod_list`] returns the same
+    /// mods along with the loader, without the markers.
+    pub fn mods(&self) -> impl Iterator<Item = ModRef<'_>> {
+        let legacy = self.mod_info.iter().flat_map(|info| {
+            info.mod_list.iter().map(|m| ModRef {
+                id: &m.mod_id,
+                version: &m.version,
+            })
+        });
+        let forge = self.forge_data.iter().flat_map(|data| {
+            data.mods.iter().map(|m| ModRef {
+                id: &m.mod_id,
+                version: &m.mod_marker,
+            })
+        });
+        legacy.chain(forge)
+    }
+
+    /// Returns the number of mods the server reported.
+    pub fn mod_count(&self) -> usize {
+        self.mods().count()
+    }
+
+    /// Finds a mod reported by the server by its id.
+    pub fn find_mod(&self, id: &str) -> Option<ModRef<'_>> {
+        self.mods().find(|m| m.id == id)
+    }
+}
+
+/// Formats a short summary of the server, like `Paper 1.21.1 — 153/500 — A Minecraft Server`.
+///
+/// Formatting codes are removed from the description, which may span two lines.
+impl fmt::Display for JavaServerInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(version) = &self.version {
+            parts.push(String::from(version.name.trim()));
+        }
+        if let Some(players) = &self.players {
+            parts.push(alloc::format!("{}/{}", players.online, players.max));
+        }
+        let description = self.description.to_plain_text();
+        let lines: Vec<_> = description
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect();
+        parts.push(lines.join("\n"));
+        parts.retain(|part| !part.is_empty());
+        write!(f, "{}", parts.join(" — "))
+    }
+}
+
+impl core::str::FromStr for JavaServerInfo {
     type Err = serde_json::Error;
     fn from_str(json: &str) -> Result<Self, Self::Err> {
         serde_json::from_str(json)
@@ -78,6 +484,7 @@ impl std::str::FromStr for JavaServerInfo {
 }
 
 #[derive(Serialize, Deserialize, Debug, Hash, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[serde(untagged)]
 pub enum TextComponent {
     Plain(String),
@@ -86,26 +493,209 @@ pub enum TextComponent {
 }
 
 #[derive(Debug, Serialize, Deserialize, Hash, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
 #[non_exhaustive]
 pub struct FancyText {
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub text: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub color: Option<String>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub bold: Option<bool>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub italic: Option<bool>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub underlined: Option<bool>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub strikethrough: Option<bool>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub obfuscated: Option<bool>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub extra: Option<Vec<TextComponent>>,
 }
 
+impl FancyText {
+    /// Creates unformatted text. Formatting that isn't set is inherited from the parent component.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: Some(text.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Sets the color, which is either a name like `gold` or a hex color like `#ffaa00`.
+    pub fn with_color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    pub fn with_bold(mut self, bold: bool) -> Self {
+        self.bold = Some(bold);
+        self
+    }
+
+    pub fn with_italic(mut self, italic: bool) -> Self {
+        self.italic = Some(italic);
+        self
+    }
+
+    pub fn with_underlined(mut self, underlined: bool) -> Self {
+        self.underlined = Some(underlined);
+        self
+    }
+
+    pub fn with_strikethrough(mut self, strikethrough: bool) -> Self {
+        self.strikethrough = Some(strikethrough);
+        self
+    }
+
+    pub fn with_obfuscated(mut self, obfuscated: bool) -> Self {
+        self.obfuscated = Some(obfuscated);
+        self
+    }
+
+    /// Appends a child component, which inherits this component's formatting.
+    pub fn with_extra(mut self, extra: impl Into<TextComponent>) -> Self {
+        self.extra.get_or_insert_with(Vec::new).push(extra.into());
+        self
+    }
+}
+
+impl From<String> for TextComponent {
+    fn from(text: String) -> Self {
+        TextComponent::Plain(text)
+    }
+}
+
+impl From<&str> for TextComponent {
+    fn from(text: &str) -> Self {
+        TextComponent::Plain(String::from(text))
+    }
+}
+
+impl From<FancyText> for TextComponent {
+    fn from(text: FancyText) -> Self {
+        TextComponent::Fancy(text)
+    }
+}
+
+impl TextComponent {
+    /// Returns the text of this component and its children, without any formatting or legacy `§` codes.
+    pub fn to_plain_text(&self) -> String {
+        let mut text = String::new();
+        self.push_plain_text(&mut text);
+        strip_legacy_codes(&text)
+    }
+
+    fn push_plain_text(&self, out: &mut String) {
+        match self {
+            TextComponent::Plain(text) => out.push_str(text),
+            TextComponent::Fancy(fancy) => {
+                if let Some(text) = &fancy.text {
+                    out.push_str(text);
+                }
+                for child in fancy.extra.iter().flatten() {
+                    child.push_plain_text(out);
+                }
+            }
+            TextComponent::Extra(components) => {
+                for component in components {
+                    component.push_plain_text(out);
+                }
+            }
+        }
+    }
+}
+
+/// Removes legacy formatting codes (like `§a` or `§l`) from a string.
+pub fn strip_legacy_codes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Converts a string containing legacy formatting codes (like `§a` or `§l`) into a text component.
+///
+/// Unknown codes are removed. As in the game, a color code resets any formatting before it.
+pub fn parse_legacy_codes(text: &str) -> TextComponent {
+    if !text.contains('§') {
+        return TextComponent::Plain(String::from(text));
+    }
+
+    let mut components = Vec::new();
+    let mut style = FancyText::default();
+    let mut current = String::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '§' {
+            current.push(c);
+            continue;
+        }
+        let Some(code) = chars.next() else {
+            break;
+        };
+        if !current.is_empty() {
+            components.push(TextComponent::Fancy(FancyText {
+                text: Some(core::mem::take(&mut current)),
+                ..style.clone()
+            }));
+        }
+        match code.to_ascii_lowercase() {
+            'k' => style.obfuscated = Some(true),
+            'l' => style.bold = Some(true),
+            'm' => style.strikethrough = Some(true),
+            'n' => style.underlined = Some(true),
+            'o' => style.italic = Some(true),
+            'r' => style = FancyText::default(),
+            code => {
+                if let Some(color) = legacy_color(code) {
+                    style = FancyText {
+                        color: Some(String::from(color)),
+                        ..Default::default()
+                    };
+                }
+            }
+        }
+    }
+    if !current.is_empty() {
+        components.push(TextComponent::Fancy(FancyText {
+            text: Some(current),
+            ..style
+        }));
+    }
+
+    TextComponent::Extra(components)
+}
+
+fn legacy_color(code: char) -> Option<&'static str> {
+    Some(match code {
+        '0' => "black",
+        '1' => "dark_blue",
+        '2' => "dark_green",
+        '3' => "dark_aqua",
+        '4' => "dark_red",
+        '5' => "dark_purple",
+        '6' => "gold",
+        '7' => "gray",
+        '8' => "dark_gray",
+        '9' => "blue",
+        'a' => "green",
+        'b' => "aqua",
+        'c' => "red",
+        'd' => "light_purple",
+        'e' => "yellow",
+        'f' => "white",
+        _ => return None,
+    })
+}
+
 impl From<TextComponent> for FancyText {
     fn from(value: TextComponent) -> Self {
         match value {
@@ -123,3 +713,265 @@ impl From<TextComponent> for FancyText {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::borrow::ToOwned;
+
+    #[test]
+    fn real_players() {
+        let players: ServerPlayers = serde_json::from_str(
+            r#"{
+                "max": 100,
+                "online": 5,
+                "sample": [
+                    { "name": "doinkythederp", "id": "069a79f4-44e9-4726-a5be-fca90e38aaf5" },
+                    { "name": "Anonymous Player", "id": "00000000-0000-0000-0000-000000000000" }
+                ]
+            }"#,
+        )
+        .unwrap();
+        let real = players.real_players();
+        assert_eq!(real.players.len(), 1);
+        assert_eq!(real.players[0].name.as_deref(), Some("doinkythederp"));
+        assert_eq!(real.anonymous, 1);
+        assert_eq!(real.hidden, 4);
+    }
+
+    #[test]
+    fn sample_advertisement() {
+        let sample = |sample: &str| -> ServerPlayers {
+            serde_json::from_str(&alloc::format!(
+                r#"{{ "max": 100, "online": 5, "sample": {sample} }}"#
+            ))
+            .unwrap()
+        };
+        assert!(!sample(
+            r#"[
+                { "name": "doinkythederp", "id": "069a79f4-44e9-4726-a5be-fca90e38aaf5" },
+                { "name": ".BedrockPlayer", "id": "00000000-0000-0000-0009-01f2a3b4c5d6" },
+                { "name": "Anonymous Player", "id": "00000000-0000-0000-0000-000000000000" },
+                { "name": "Anonymous Player", "id": "00000000-0000-0000-0000-000000000000" }
+            ]"#
+        )
+        .sample_is_advertisement());
+        assert!(sample(
+            r#"[{ "name": "§6Join our Discord!", "id": "069a79f4-44e9-4726-a5be-fca90e38aaf5" }]"#
+        )
+        .sample_is_advertisement());
+        assert!(
+            sample(r#"[{ "name": "Welcome", "id": "00000000-0000-0000-0000-000000000000" }]"#)
+                .sample_is_advertisement()
+        );
+        assert!(sample(
+            r#"[
+                { "name": "line_one", "id": "069a79f4-44e9-4726-a5be-fca90e38aaf5" },
+                { "name": "line_two", "id": "069a79f4-44e9-4726-a5be-fca90e38aaf5" }
+            ]"#
+        )
+        .sample_is_advertisement());
+    }
+
+    #[test]
+    fn uuid_kinds() {
+        let kind = |id: &str| {
+            ServerPlayersSample {
+                name: None,
+                id: Some(id.to_owned()),
+            }
+            .uuid_kind()
+        };
+        assert_eq!(
+            kind("069a79f4-44e9-4726-a5be-fca90e38aaf5"),
+            Some(PlayerUuidKind::Online)
+        );
+        // offline mode UUID of "Notch"
+        assert_eq!(
+            kind("b50ad385829d3141a2167e7d7539ba7f"),
+            Some(PlayerUuidKind::Offline)
+        );
+        assert_eq!(
+            kind("00000000-0000-0000-0000-000000000000"),
+            Some(PlayerUuidKind::Nil)
+        );
+        assert_eq!(
+            kind("00000000-0000-0000-0009-01f2a3b4c5d6"),
+            Some(PlayerUuidKind::Other)
+        );
+        assert_eq!(kind("069a79f4-44e9-4726-a5be-fca90e38aaf"), None);
+        assert_eq!(kind("069a79f444e9-4726-a5be-fca90e38aaf5-"), None);
+
+        let players: ServerPlayers = serde_json::from_str(
+            r#"{ "max": 20, "online": 1, "sample": [{ "name": "Notch", "id": "b50ad385-829d-3141-a216-7e7d7539ba7f" }] }"#,
+        )
+        .unwrap();
+        assert_eq!(players.sample_suggests_offline_mode(), Some(true));
+    }
+
+    #[test]
+    fn display_summary() {
+        let info: JavaServerInfo = r#"{
+            "version": { "name": "Paper 1.21.1", "protocol": 767 },
+            "players": { "max": 500, "online": 153 },
+            "description": { "text": "§aA Minecraft Server\n", "extra": [{ "text": "Second line" }] }
+        }"#
+        .parse()
+        .unwrap();
+        assert_eq!(
+            info.to_string(),
+            "Paper 1.21.1 — 153/500 — A Minecraft Server\nSecond line"
+        );
+
+        let info: JavaServerInfo = r#"{
+            "version": { "name": "Paper 1.21.1", "protocol": 767 },
+            "players": { "max": 500, "online": 153 },
+            "description": " §r "
+        }"#
+        .parse()
+        .unwrap();
+        assert_eq!(info.to_string(), "Paper 1.21.1 — 153/500");
+    }
+
+    #[test]
+    fn lenient_player_counts() {
+        let players: ServerPlayers =
+            serde_json::from_str(r#"{ "max": -1, "online": " 42 " }"#).unwrap();
+        assert_eq!((players.max, players.online), (0, 42));
+        let players: ServerPlayers =
+            serde_json::from_str(r#"{ "max": 1e12, "online": 3.5 }"#).unwrap();
+        assert_eq!((players.max, players.online), (u32::MAX, 3));
+        assert!(
+            serde_json::from_str::<ServerPlayers>(r#"{ "max": "lots", "online": 0 }"#).is_err()
+        );
+    }
+
+    #[test]
+    fn unusual_protocol() {
+        for protocol in [-1, 4_294_967_296] {
+            let info: JavaServerInfo = alloc::format!(
+                r#"{{ "version": {{ "name": "Velocity", "protocol": {protocol} }}, "description": "" }}"#
+            )
+            .parse()
+            .unwrap();
+            assert_eq!(info.version.unwrap().protocol, protocol);
+        }
+    }
+
+    #[test]
+    fn constructors() {
+        let info = JavaServerInfo::new(
+            FancyText::new("Hello ")
+                .with_color("gold")
+                .with_extra(FancyText::new("world").with_bold(true)),
+        )
+        .with_version(ServerVersion::new("1.21", 767))
+        .with_players(
+            ServerPlayers::new(20, 1).with_sample(vec![ServerPlayersSample::new(
+                "Notch",
+                "069a79f4-44e9-4726-a5be-fca90e38aaf5",
+            )]),
+        );
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(json.parse::<JavaServerInfo>().unwrap(), info);
+        assert_eq!(info.description.to_plain_text(), "Hello world");
+        assert_eq!(info.to_string(), "1.21 — 1/20 — Hello world");
+    }
+
+    #[test]
+    fn modpack_data() {
+        let info: JavaServerInfo = r#"{
+            "description": "",
+            "modpackData": {
+                "projectID": 715572,
+                "name": "All the Mods 9",
+                "version": "0.2.44",
+                "versionID": 5102389,
+                "releaseType": "Release",
+                "isMetadata": true
+            }
+        }"#
+        .parse()
+        .unwrap();
+        let modpack = info.modpack_data.unwrap();
+        assert_eq!(modpack.project_id, Some(715572));
+        assert_eq!(modpack.name.as_deref(), Some("All the Mods 9"));
+        assert_eq!(modpack.release_type.as_deref(), Some("Release"));
+    }
+
+    #[test]
+    fn mod_helpers() {
+        let info: JavaServerInfo = r#"{
+            "description": "A Forge server",
+            "forgeData": {
+                "channels": [],
+                "mods": [
+                    { "modId": "forge", "modmarker": "47.2.0" },
+                    { "modId": "jei", "modmarker": "15.2.0.27" }
+                ],
+                "fmlNetworkVersion": 3
+            }
+        }"#
+        .parse()
+        .unwrap();
+        assert!(info.is_modded());
+        assert_eq!(info.mod_count(), 2);
+        assert_eq!(info.find_mod("jei").unwrap().version, "15.2.0.27");
+        assert!(info.find_mod("create").is_none());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_info_reparses() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        for seed in 0..64u8 {
+            let data: Vec<u8> = (0..512u32)
+                .map(|i| (i as u8).wrapping_mul(31).wrapping_add(seed))
+                .collect();
+            let info = JavaServerInfo::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            // an empty component has several equivalent representations, so normalize it first
+            let parsed: JavaServerInfo = serde_json::to_string(&info).unwrap().parse().unwrap();
+            let json = serde_json::to_string(&parsed).unwrap();
+            assert_eq!(json.parse::<JavaServerInfo>().unwrap(), parsed);
+        }
+    }
+
+    #[test]
+    fn legacy_codes() {
+        assert_eq!(
+            parse_legacy_codes("Plain"),
+            TextComponent::Plain("Plain".into())
+        );
+
+        let component = parse_legacy_codes("§e§lThe Hive§r - §aGames");
+        assert_eq!(component.to_plain_text(), "The Hive - Games");
+        let TextComponent::Extra(parts) = component else {
+            panic!("expected multiple parts");
+        };
+        assert_eq!(
+            parts[0],
+            TextComponent::Fancy(FancyText {
+                text: Some("The Hive".into()),
+                color: Some("yellow".into()),
+                bold: Some(true),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            parts[1],
+            TextComponent::Fancy(FancyText {
+                text: Some(" - ".into()),
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            parts[2],
+            TextComponent::Fancy(FancyText {
+                text: Some("Games".into()),
+                color: Some("green".into()),
+                ..Default::default()
+            })
+        );
+    }
+}
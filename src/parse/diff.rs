@@ -0,0 +1,143 @@
+use super::{FaviconHash, JavaServerInfo, ServerVersion};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+/// A meaningful difference between two statuses of a server, returned by [`JavaServerInfo::diff`].
+///
+/// The [`Display`](fmt::Display) implementation describes the change in a short sentence, suitable for a log.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StatusChange {
+    /// The text of the description changed. Changes that only affect formatting aren't reported.
+    Description { old: String, new: String },
+    /// The version name or protocol number changed.
+    Version {
+        old: Option<ServerVersion>,
+        new: Option<ServerVersion>,
+    },
+    /// The favicon changed. A favicon that couldn't be decoded has no hash.
+    Favicon {
+        old: Option<FaviconHash>,
+        new: Option<FaviconHash>,
+    },
+    /// The maximum number of players changed.
+    MaxPlayers { old: Option<u32>, new: Option<u32> },
+}
+
+impl JavaServerInfo {
+    /// Lists the differences between this status and a newer one from the same server, such as to log what
+    /// changed between two pings.
+    ///
+    /// Only changes that the server's owner would have made are reported, so the online players and player
+    /// sample are ignored.
+    pub fn diff(&self, other: &JavaServerInfo) -> Vec<StatusChange> {
+        let mut changes = Vec::new();
+
+        let (old, new) = (
+            self.description.to_plain_text(),
+            other.description.to_plain_text(),
+        );
+        if old != new {
+            changes.push(StatusChange::Description { old, new });
+        }
+
+        if self.version != other.version {
+            changes.push(StatusChange::Version {
+                old: self.version.clone(),
+                new: other.version.clone(),
+            });
+        }
+
+        // the same image can be encoded differently, so compare the decoded images
+        if self.favicon != other.favicon {
+            let old = self.favicon_hash().ok().flatten();
+            let new = other.favicon_hash().ok().flatten();
+            if old.is_none() || old != new {
+                changes.push(StatusChange::Favicon { old, new });
+            }
+        }
+
+        let (old, new) = (
+            self.players.as_ref().map(|players| players.max),
+            other.players.as_ref().map(|players| players.max),
+        );
+        if old != new {
+            changes.push(StatusChange::MaxPlayers { old, new });
+        }
+
+        changes
+    }
+}
+
+impl fmt::Display for StatusChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusChange::Description { old, new } => {
+                write!(f, "description changed from {old:?} to {new:?}")
+            }
+            StatusChange::Version { old, new } => {
+                f.write_str("version changed from ")?;
+                write_optional(f, old.as_ref().map(|version| &version.name))?;
+                f.write_str(" to ")?;
+                write_optional(f, new.as_ref().map(|version| &version.name))
+            }
+            StatusChange::Favicon { old, new } => {
+                f.write_str("favicon changed from ")?;
+                write_optional(f, old.as_ref())?;
+                f.write_str(" to ")?;
+                write_optional(f, new.as_ref())
+            }
+            StatusChange::MaxPlayers { old, new } => {
+                f.write_str("max players changed from ")?;
+                write_optional(f, old.as_ref())?;
+                f.write_str(" to ")?;
+                write_optional(f, new.as_ref())
+            }
+        }
+    }
+}
+
+fn write_optional(f: &mut fmt::Formatter<'_>, value: Option<impl fmt::Display>) -> fmt::Result {
+    match value {
+        Some(value) => write!(f, "{value}"),
+        None => f.write_str("nothing"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn diff() {
+        let old: JavaServerInfo = r#"{
+            "version": { "name": "1.20.4", "protocol": 765 },
+            "players": { "max": 20, "online": 3 },
+            "description": "§aWelcome!"
+        }"#
+        .parse()
+        .unwrap();
+        let new: JavaServerInfo = r#"{
+            "version": { "name": "1.21", "protocol": 767 },
+            "players": { "max": 50, "online": 7 },
+            "description": { "text": "Welcome!", "color": "red" },
+            "favicon": "data:image/png;base64,iVBORw0KGgo="
+        }"#
+        .parse()
+        .unwrap();
+
+        assert!(old.diff(&old).is_empty());
+        let changes = old.diff(&new);
+        assert_eq!(changes.len(), 3);
+        assert!(matches!(changes[0], StatusChange::Version { .. }));
+        assert_eq!(
+            changes[1],
+            StatusChange::Favicon {
+                old: None,
+                new: Some(FaviconHash::from_png(b"\x89PNG\r\n\x1a\n"))
+            }
+        );
+        assert_eq!(changes[2].to_string(), "max players changed from 20 to 50");
+    }
+}
@@ -0,0 +1,216 @@
+use alloc::{string::String, vec::Vec};
+use base64::Engine;
+use core::fmt;
+use sha2::{Digest, Sha256};
+#[cfg(feature = "image")]
+use snafu::ResultExt;
+use snafu::{Backtrace, Snafu};
+
+/// The prefix every favicon sent in a status response must start with.
+pub const FAVICON_PREFIX: &str = "data:image/png;base64,";
+/// The width and height, in pixels, the vanilla client requires favicons to have.
+pub const FAVICON_SIZE: u32 = 64;
+
+const PNG_SIGNATURE: [u8; 8] = *b"\x89PNG\r\n\x1a\n";
+
+#[derive(Snafu, Debug)]
+pub enum FaviconError {
+    /// The favicon is not a PNG data URI.
+    #[snafu(display("Favicon is not a PNG data URI"))]
+    InvalidPrefix { backtrace: Backtrace },
+    /// The favicon's base64 data is malformed.
+    #[snafu(display("Failed to decode favicon data: {error}"))]
+    Base64Decode {
+        // not a source because `DecodeError` only implements `Error` with std
+        error: base64::DecodeError,
+        backtrace: Backtrace,
+    },
+    /// The favicon data is not a valid PNG image.
+    #[snafu(display("Favicon data is not a valid PNG image"))]
+    InvalidPng { backtrace: Backtrace },
+    /// Failed to decode the favicon image.
+    #[cfg(feature = "image")]
+    #[snafu(display("Failed to decode favicon image: {source}"))]
+    ImageDecode {
+        source: image::ImageError,
+        backtrace: Backtrace,
+    },
+}
+
+/// The pixel format of a PNG image, as declared in its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PngColorType {
+    Grayscale,
+    Rgb,
+    Indexed,
+    GrayscaleAlpha,
+    Rgba,
+}
+
+impl PngColorType {
+    fn from_byte(byte: u8) -> Option<Self> {
+        match byte {
+            0 => Some(Self::Grayscale),
+            2 => Some(Self::Rgb),
+            3 => Some(Self::Indexed),
+            4 => Some(Self::GrayscaleAlpha),
+            6 => Some(Self::Rgba),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes PNG bytes into an image.
+#[cfg(feature = "image")]
+pub fn decode_favicon_image(png: &[u8]) -> Result<image::DynamicImage, FaviconError> {
+    image::load_from_memory_with_format(png, image::ImageFormat::Png).context(ImageDecodeSnafu)
+}
+
+/// Describes the image stored in a favicon.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct FaviconReport {
+    pub width: u32,
+    pub height: u32,
+    pub bit_depth: u8,
+    pub color_type: PngColorType,
+}
+
+impl FaviconReport {
+    /// Reads the header of a PNG image.
+    pub fn from_png(png: &[u8]) -> Result<Self, FaviconError> {
+        // signature, then the IHDR chunk's length and type, then its data
+        let header = png.get(..29).ok_or_else(|| InvalidPngSnafu.build())?;
+        if header[..8] != PNG_SIGNATURE || &header[12..16] != b"IHDR" {
+            return InvalidPngSnafu.fail();
+        }
+        let width = u32::from_be_bytes(header[16..20].try_into().unwrap());
+        let height = u32::from_be_bytes(header[20..24].try_into().unwrap());
+        let bit_depth = header[24];
+        let color_type =
+            PngColorType::from_byte(header[25]).ok_or_else(|| InvalidPngSnafu.build())?;
+        if width == 0 || height == 0 {
+            return InvalidPngSnafu.fail();
+        }
+        Ok(Self {
+            width,
+            height,
+            bit_depth,
+            color_type,
+        })
+    }
+
+    /// Returns whether the vanilla client will accept this image as a server icon.
+    pub fn is_valid_for_client(&self) -> bool {
+        self.width == FAVICON_SIZE && self.height == FAVICON_SIZE
+    }
+}
+
+/// Decodes a `data:image/png;base64,...` favicon string into raw PNG bytes.
+///
+/// Line breaks inside the base64 data are ignored, since some older servers wrap it.
+pub fn decode_favicon(favicon: &str) -> Result<Vec<u8>, FaviconError> {
+    let data = favicon
+        .strip_prefix(FAVICON_PREFIX)
+        .ok_or_else(|| InvalidPrefixSnafu.build())?;
+    let data: String = data.chars().filter(|c| !matches!(c, '\n' | '\r')).collect();
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|error| Base64DecodeSnafu { error }.build())
+}
+
+/// Encodes PNG bytes as a `data:image/png;base64,...` favicon string, such as for a server to send.
+///
+/// The vanilla client only shows icons that are [`FAVICON_SIZE`] pixels square.
+pub fn encode_favicon(png: &[u8]) -> String {
+    let mut favicon = String::from(FAVICON_PREFIX);
+    base64::engine::general_purpose::STANDARD.encode_string(png, &mut favicon);
+    favicon
+}
+
+/// A SHA-256 hash of a favicon's decoded PNG bytes.
+///
+/// Two favicons have the same hash only if they contain the same image data, regardless of how the base64 was wrapped.
+/// The [`Display`](fmt::Display) implementation formats the hash as lowercase hex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FaviconHash(pub [u8; 32]);
+
+impl FaviconHash {
+    /// Hashes decoded PNG bytes.
+    pub fn from_png(png: &[u8]) -> Self {
+        Self(Sha256::digest(png).into())
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for FaviconHash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn png_header(width: u32, height: u32) -> Vec<u8> {
+        let mut png = PNG_SIGNATURE.to_vec();
+        png.extend_from_slice(&13u32.to_be_bytes());
+        png.extend_from_slice(b"IHDR");
+        png.extend_from_slice(&width.to_be_bytes());
+        png.extend_from_slice(&height.to_be_bytes());
+        png.extend_from_slice(&[8, 6, 0, 0, 0]);
+        png
+    }
+
+    #[test]
+    fn decode_valid_favicon() {
+        let favicon = encode_favicon(b"\x89PNG");
+        assert_eq!(favicon, format!("{FAVICON_PREFIX}iVBORw=="));
+        assert_eq!(decode_favicon(&favicon).unwrap(), b"\x89PNG");
+    }
+
+    #[test]
+    fn decode_wrapped_favicon() {
+        let favicon = format!("{FAVICON_PREFIX}iVBO\nRw==");
+        assert_eq!(decode_favicon(&favicon).unwrap(), b"\x89PNG");
+    }
+
+    #[test]
+    fn reject_malformed_favicon() {
+        assert!(matches!(
+            decode_favicon("data:image/jpeg;base64,iVBORw=="),
+            Err(FaviconError::InvalidPrefix { .. })
+        ));
+        assert!(matches!(
+            decode_favicon(&format!("{FAVICON_PREFIX}not base64!")),
+            Err(FaviconError::Base64Decode { .. })
+        ));
+    }
+
+    #[test]
+    fn inspect_favicon() {
+        let report = FaviconReport::from_png(&png_header(64, 64)).unwrap();
+        assert_eq!(report.color_type, PngColorType::Rgba);
+        assert!(report.is_valid_for_client());
+
+        let report = FaviconReport::from_png(&png_header(128, 64)).unwrap();
+        assert!(!report.is_valid_for_client());
+
+        assert!(FaviconReport::from_png(b"\x89PNG").is_err());
+    }
+
+    #[test]
+    fn hash_favicon() {
+        let hash = FaviconHash::from_png(b"");
+        assert_eq!(
+            hash.to_string(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+}
@@ -0,0 +1,104 @@
+use super::JavaServerInfo;
+use alloc::string::String;
+use core::fmt;
+use sha2::{Digest, Sha256};
+
+/// A hash identifying a server by the parts of its status that stay the same between pings, returned by
+/// [`JavaServerInfo::fingerprint`].
+///
+/// The [`Display`](fmt::Display) implementation formats the hash as lowercase hex.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ServerFingerprint(pub [u8; 32]);
+
+impl ServerFingerprint {
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl fmt::Display for ServerFingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl JavaServerInfo {
+    /// Hashes the server's version, favicon and description, such as to find the same server behind many
+    /// addresses in a scan.
+    ///
+    /// The description is reduced to a skeleton first: formatting is removed, letters are lowercased,
+    /// whitespace is collapsed and every run of digits is treated the same, so counters and dates in the
+    /// description don't change the fingerprint. The player counts are ignored.
+    pub fn fingerprint(&self) -> ServerFingerprint {
+        let mut hasher = Sha256::new();
+        let mut field = |bytes: &[u8]| {
+            // prefix each field with its length so different fields can't run together
+            hasher.update((bytes.len() as u64).to_be_bytes());
+            hasher.update(bytes);
+        };
+
+        match &self.version {
+            Some(version) => {
+                field(version.name.trim().as_bytes());
+                field(&version.protocol.to_be_bytes());
+            }
+            None => field(b""),
+        }
+        match (&self.favicon, self.favicon_hash()) {
+            (_, Ok(Some(hash))) => field(hash.as_bytes()),
+            (Some(favicon), Err(_)) => field(favicon.as_bytes()),
+            _ => field(b""),
+        }
+        field(motd_skeleton(&self.description.to_plain_text()).as_bytes());
+
+        ServerFingerprint(hasher.finalize().into())
+    }
+}
+
+fn motd_skeleton(text: &str) -> String {
+    let mut skeleton = String::new();
+    for word in text.split_whitespace() {
+        if !skeleton.is_empty() {
+            skeleton.push(' ');
+        }
+        let mut digits = false;
+        for c in word.chars().flat_map(char::to_lowercase) {
+            if c.is_ascii_digit() {
+                if !digits {
+                    skeleton.push('#');
+                }
+                digits = true;
+            } else {
+                skeleton.push(c);
+                digits = false;
+            }
+        }
+    }
+    skeleton
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fingerprint() {
+        let info = |description: &str, online: u32| -> JavaServerInfo {
+            serde_json::from_str(&alloc::format!(
+                r#"{{
+                    "version": {{ "name": "Paper 1.21", "protocol": 767 }},
+                    "players": {{ "max": 100, "online": {online} }},
+                    "description": {description:?}
+                }}"#
+            ))
+            .unwrap()
+        };
+        let fingerprint = info("§aSeason 4 — day 12", 5).fingerprint();
+        assert_eq!(fingerprint, info("Season  4 —\nDAY 130", 40).fingerprint());
+        assert_ne!(fingerprint, info("Season 4 — night 12", 5).fingerprint());
+        assert_eq!(motd_skeleton("  Top 10 \n servers"), "top # servers");
+    }
+}
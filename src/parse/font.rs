@@ -0,0 +1,77 @@
+use alloc::string::String;
+
+/// The width of a line of the description in the vanilla server list, in pixels of the default font.
+pub const MOTD_WIDTH: u32 = 271;
+
+/// Measures the width of text in the default font, in pixels, as the game would render it.
+///
+/// Legacy formatting codes aren't counted, except that bold text is a pixel wider per character. Characters
+/// outside of ASCII are assumed to be as wide as most letters.
+pub fn text_width(text: &str) -> u32 {
+    let mut width = 0;
+    let mut bold = false;
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            match chars.next().map(|code| code.to_ascii_lowercase()) {
+                Some('l') => bold = true,
+                Some('r' | '0'..='9' | 'a'..='f') => bold = false,
+                _ => {}
+            }
+            continue;
+        }
+        width += char_width(c) + u32::from(bold);
+    }
+    width
+}
+
+/// Pads each line of a description with spaces so it's centered in the vanilla server list.
+///
+/// Lines that are already too wide to be centered are left as they are. Text can only be moved by whole spaces,
+/// so it may be up to two pixels off center.
+pub fn center_motd(text: &str) -> String {
+    let mut centered = String::with_capacity(text.len());
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            centered.push('\n');
+        }
+        let padding = MOTD_WIDTH.saturating_sub(text_width(line)) / 2;
+        let space = char_width(' ');
+        centered.extend(core::iter::repeat(' ').take(((padding + space / 2) / space) as usize));
+        centered.push_str(line);
+    }
+    centered
+}
+
+/// The width of a character including the pixel of space after it.
+fn char_width(c: char) -> u32 {
+    match c {
+        '!' | '\'' | ',' | '.' | ':' | ';' | 'i' | '|' => 2,
+        '`' | 'l' => 3,
+        ' ' | 'I' | '[' | ']' | 't' => 4,
+        '"' | '(' | ')' | '*' | '<' | '>' | 'f' | 'k' | '{' | '}' => 5,
+        '@' | '~' => 7,
+        _ => 6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width() {
+        assert_eq!(text_width("Hi!"), 6 + 2 + 2);
+        assert_eq!(text_width("§a§lHi§r!"), 7 + 3 + 2);
+    }
+
+    #[test]
+    fn center() {
+        // the line is 60 pixels wide, leaving 105 pixels on each side, which is closest to 26 spaces
+        let line = "AAAAAAAAAA";
+        let centered = center_motd(&alloc::format!("§a{line}\n{}", "A".repeat(50)));
+        let (first, second) = centered.split_once('\n').unwrap();
+        assert_eq!(first, alloc::format!("{}§a{line}", " ".repeat(26)));
+        assert_eq!(second, "A".repeat(50));
+    }
+}
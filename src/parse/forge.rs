@@ -0,0 +1,230 @@
+use super::{ServerForgeChannel, ServerForgeData, ServerForgeMod};
+use crate::varint::VarIntBuf;
+use alloc::{format, string::String, vec::Vec};
+use bytes::Buf;
+use serde::Deserialize;
+
+/// The marker Forge sends as the version of a mod that doesn't need to be installed on the client.
+const IGNORE_SERVER_ONLY: &str = "IGNORESERVERONLY";
+
+/// `forgeData` as it's sent, before the compressed `d` field is decoded.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(super) struct RawServerForgeData {
+    #[serde(default)]
+    channels: Vec<ServerForgeChannel>,
+    #[serde(default)]
+    mods: Vec<ServerForgeMod>,
+    fml_network_version: Option<u32>,
+    #[serde(default)]
+    truncated: bool,
+    d: Option<String>,
+}
+
+impl From<RawServerForgeData> for ServerForgeData {
+    fn from(raw: RawServerForgeData) -> Self {
+        let mut data = ServerForgeData {
+            channels: raw.channels,
+            mods: raw.mods,
+            fml_network_version: raw.fml_network_version,
+            truncated: raw.truncated,
+            compressed: raw.d,
+        };
+        // the lists are empty when they're compressed, unless this status was serialized after being decoded
+        if let Some(decoded) = data.compressed.as_deref().and_then(decode) {
+            data.truncated |= decoded.truncated;
+            data.mods = decoded.mods;
+            data.channels = decoded.channels;
+        }
+        data
+    }
+}
+
+struct DecodedForgeData {
+    truncated: bool,
+    mods: Vec<ServerForgeMod>,
+    channels: Vec<ServerForgeChannel>,
+}
+
+/// Decodes the mods and channels that Forge compresses into the `d` field, or returns `None` if it's malformed.
+fn decode(d: &str) -> Option<DecodedForgeData> {
+    let bytes = decode_optimized(d)?;
+    let mut buf = &bytes[..];
+
+    let truncated = get_bool(&mut buf)?;
+    let mod_count = get_u16(&mut buf)?;
+    let mut mods = Vec::new();
+    let mut channels = Vec::new();
+    for _ in 0..mod_count {
+        // the channel count, shifted left to make room for a flag set for mods that are only on the server
+        let flags = get_var_int(&mut buf)?;
+        let mod_id = get_string(&mut buf)?;
+        let mod_marker = if flags & 1 != 0 {
+            String::from(IGNORE_SERVER_ONLY)
+        } else {
+            get_string(&mut buf)?
+        };
+        for _ in 0..flags >> 1 {
+            let path = get_string(&mut buf)?;
+            channels.push(ServerForgeChannel {
+                res: format!("{mod_id}:{path}"),
+                version: get_string(&mut buf)?,
+                required: get_bool(&mut buf)?,
+            });
+        }
+        mods.push(ServerForgeMod { mod_id, mod_marker });
+    }
+
+    // channels that don't belong to a mod are listed with their full names
+    for _ in 0..get_var_int(&mut buf)? {
+        channels.push(ServerForgeChannel {
+            res: get_string(&mut buf)?,
+            version: get_string(&mut buf)?,
+            required: get_bool(&mut buf)?,
+        });
+    }
+
+    Some(DecodedForgeData {
+        truncated,
+        mods,
+        channels,
+    })
+}
+
+/// Unpacks bytes from a string where each character holds 15 bits, after two characters holding the length.
+fn decode_optimized(d: &str) -> Option<Vec<u8>> {
+    let mut chars = d.chars().map(|c| c as u32 & 0x7fff);
+    let len = chars.next()? as usize | (chars.next()? as usize) << 15;
+    // every character holds less than two bytes, so a longer length is malformed
+    let mut bytes = Vec::with_capacity(len.min(d.len() * 2));
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    while bytes.len() < len {
+        if bits >= 8 {
+            bytes.push(buffer as u8);
+            buffer >>= 8;
+            bits -= 8;
+        } else if let Some(c) = chars.next() {
+            buffer |= c << bits;
+            bits += 15;
+        } else if bits > 0 && bytes.len() + 1 == len {
+            // Forge leaves out the top bits of the last byte if they don't fit in the last character
+            bytes.push(buffer as u8);
+        } else {
+            return None;
+        }
+    }
+    Some(bytes)
+}
+
+fn get_bool(buf: &mut &[u8]) -> Option<bool> {
+    buf.has_remaining().then(|| buf.get_u8() != 0)
+}
+
+fn get_u16(buf: &mut &[u8]) -> Option<u16> {
+    (buf.remaining() >= 2).then(|| buf.get_u16())
+}
+
+fn get_var_int(buf: &mut &[u8]) -> Option<i32> {
+    buf.get_var_int().ok()
+}
+
+fn get_string(buf: &mut &[u8]) -> Option<String> {
+    let len = usize::try_from(get_var_int(buf)?).ok()?;
+    let bytes = buf.get(..len)?;
+    let string = core::str::from_utf8(bytes).ok()?.into();
+    buf.advance(len);
+    Some(string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::varint::VarIntBufMut;
+    use crate::JavaServerInfo;
+    use bytes::BufMut;
+
+    /// Packs bytes the way Forge does, 15 bits to a character.
+    fn encode_optimized(bytes: &[u8]) -> String {
+        let mut out = String::new();
+        out.push(char::from_u32(bytes.len() as u32 & 0x7fff).unwrap());
+        out.push(char::from_u32((bytes.len() as u32 >> 15) & 0x7fff).unwrap());
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        for &byte in bytes {
+            if bits >= 15 {
+                out.push(char::from_u32(buffer & 0x7fff).unwrap());
+                buffer >>= 15;
+                bits -= 15;
+            }
+            buffer |= u32::from(byte) << bits;
+            bits += 8;
+        }
+        if bits > 0 {
+            out.push(char::from_u32(buffer & 0x7fff).unwrap());
+        }
+        out
+    }
+
+    fn put_string(buf: &mut Vec<u8>, string: &str) {
+        buf.put_var_int(string.len() as i32);
+        buf.put_slice(string.as_bytes());
+    }
+
+    #[test]
+    fn compressed_mods() {
+        let mut bytes = Vec::new();
+        bytes.put_u8(1); // truncated
+        bytes.put_u16(2);
+        bytes.put_var_int(1 << 1); // one channel
+        put_string(&mut bytes, "create");
+        put_string(&mut bytes, "0.5.1");
+        put_string(&mut bytes, "main");
+        put_string(&mut bytes, "1");
+        bytes.put_u8(1);
+        bytes.put_var_int(1); // only on the server
+        put_string(&mut bytes, "spark");
+        bytes.put_var_int(1);
+        put_string(&mut bytes, "forge:tier_sorting");
+        put_string(&mut bytes, "1.0");
+        bytes.put_u8(0);
+
+        let json = serde_json::json!({
+            "description": "",
+            "forgeData": {
+                "channels": [],
+                "mods": [],
+                "fmlNetworkVersion": 3,
+                "d": encode_optimized(&bytes),
+            }
+        });
+        let info: JavaServerInfo = json.to_string().parse().unwrap();
+        let data = info.forge_data.as_ref().unwrap();
+        assert!(data.compressed.is_some());
+        assert!(data.truncated);
+        assert_eq!(info.mod_count(), 2);
+        assert_eq!(data.mods[0].mod_id, "create");
+        assert_eq!(data.mods[0].mod_marker, "0.5.1");
+        assert_eq!(data.mods[1].mod_marker, IGNORE_SERVER_ONLY);
+        assert_eq!(data.channels.len(), 2);
+        assert_eq!(data.channels[0].res, "create:main");
+        assert!(data.channels[0].required);
+        assert_eq!(data.channels[1].res, "forge:tier_sorting");
+        assert_eq!(info.mod_list().unwrap().mods[1].version, None);
+
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(json.parse::<JavaServerInfo>().unwrap(), info);
+    }
+
+    #[test]
+    fn malformed() {
+        // the length says there's more data than the string holds
+        assert!(decode_optimized("\u{10}\u{0}a").is_none());
+        let info: JavaServerInfo = r#"{"description":"","forgeData":{"d":"\u0010\u0000a"}}"#
+            .parse()
+            .unwrap();
+        let data = info.forge_data.unwrap();
+        assert!(data.compressed.is_some());
+        assert!(data.mods.is_empty());
+    }
+}
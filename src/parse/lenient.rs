@@ -0,0 +1,50 @@
+use core::fmt;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+
+/// Deserializes a player count, tolerating the ways some servers get it wrong.
+///
+/// Numbers outside the range of a `u32` are clamped to it, fractions are rounded down, and numbers sent as strings
+/// are parsed. Compact binary formats can't be inspected like this, so they get a plain `u32`.
+pub(super) fn player_count<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    if deserializer.is_human_readable() {
+        deserializer.deserialize_any(PlayerCountVisitor)
+    } else {
+        u32::deserialize(deserializer)
+    }
+}
+
+struct PlayerCountVisitor;
+
+impl<'de> Visitor<'de> for PlayerCountVisitor {
+    type Value = u32;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a player count")
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<u32, E> {
+        Ok(value.clamp(0, u32::MAX.into()) as u32)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<u32, E> {
+        Ok(value.min(u32::MAX.into()) as u32)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<u32, E> {
+        // casts from floats saturate, and NaN becomes 0
+        Ok(value as u32)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<u32, E> {
+        let value = value.trim();
+        if let Ok(count) = value.parse::<i64>() {
+            self.visit_i64(count)
+        } else if let Ok(count) = value.parse::<u64>() {
+            self.visit_u64(count)
+        } else if let Ok(count) = value.parse::<f64>() {
+            self.visit_f64(count)
+        } else {
+            Err(E::invalid_value(de::Unexpected::Str(value), &self))
+        }
+    }
+}
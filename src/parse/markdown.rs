@@ -0,0 +1,446 @@
+use super::{parse_legacy_codes, TextComponent};
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+/// Renders formatted text as Markdown, such as to post a server's description in a chat message.
+pub trait ToMarkdown {
+    fn to_markdown(&self, options: &MarkdownOptions) -> String;
+}
+
+/// Controls how [`ToMarkdown`] renders text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct MarkdownOptions {
+    /// Whether to escape characters that Markdown would interpret, since the text is controlled by the
+    /// server. Defaults to `true`.
+    pub escape: bool,
+    /// Which Markdown syntax to use for formatting. Defaults to [`MarkdownFlavor::CommonMark`].
+    pub flavor: MarkdownFlavor,
+    /// What to do with colors, which Markdown can't represent. Defaults to [`MarkdownColors::Drop`].
+    pub colors: MarkdownColors,
+    /// What to do with obfuscated text. Defaults to [`ObfuscatedText::Keep`].
+    pub obfuscated: ObfuscatedText,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            escape: true,
+            flavor: MarkdownFlavor::CommonMark,
+            colors: MarkdownColors::Drop,
+            obfuscated: ObfuscatedText::Keep,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    pub fn with_escape(mut self, escape: bool) -> Self {
+        self.escape = escape;
+        self
+    }
+
+    pub fn with_flavor(mut self, flavor: MarkdownFlavor) -> Self {
+        self.flavor = flavor;
+        self
+    }
+
+    pub fn with_colors(mut self, colors: MarkdownColors) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    pub fn with_obfuscated(mut self, obfuscated: ObfuscatedText) -> Self {
+        self.obfuscated = obfuscated;
+        self
+    }
+}
+
+/// The Markdown syntax to render formatting with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MarkdownFlavor {
+    /// Bold, italic and (as in GitHub Flavored Markdown) strikethrough. Underlined and obfuscated text is left
+    /// unformatted.
+    CommonMark,
+    /// Discord's Markdown, where `__` underlines text and obfuscated text is hidden behind a spoiler.
+    Discord,
+}
+
+/// How to represent colors in Markdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum MarkdownColors {
+    /// Leave colors out.
+    Drop,
+    /// Put a colored circle emoji before text whenever its color changes.
+    Emoji,
+    /// Render the text with ANSI colors in an `ansi` code block, which Discord displays in color. Other
+    /// formatting and escaping is skipped, since code blocks are shown literally.
+    AnsiCodeBlock,
+}
+
+/// How to render text with the obfuscated (`§k`) style, which the game shows as constantly changing random
+/// characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ObfuscatedText {
+    /// Render the text as it was sent, which is usually meaningless filler.
+    Keep,
+    /// Leave the text out, except for line breaks.
+    Remove,
+    /// Replace every character except whitespace with the given placeholder, such as `▒`.
+    Replace(char),
+}
+
+impl ObfuscatedText {
+    /// Applies this option to flattened text, leaving no runs with the obfuscated style unless it's `Keep`.
+    pub(super) fn apply(self, runs: &mut Vec<(String, Style)>) {
+        let placeholder = match self {
+            ObfuscatedText::Keep => return,
+            ObfuscatedText::Remove => None,
+            ObfuscatedText::Replace(placeholder) => Some(placeholder),
+        };
+        for (text, style) in runs.iter_mut().filter(|(_, style)| style.obfuscated) {
+            *text = text
+                .chars()
+                .filter_map(|c| match placeholder {
+                    _ if c == '\n' => Some(c),
+                    Some(_) if c.is_whitespace() => Some(c),
+                    placeholder => placeholder,
+                })
+                .collect();
+            style.obfuscated = false;
+        }
+        // merge the runs that now look the same as their neighbors
+        let mut merged: Vec<(String, Style)> = Vec::with_capacity(runs.len());
+        for (text, style) in runs.drain(..).filter(|(text, _)| !text.is_empty()) {
+            match merged.last_mut() {
+                Some((last, last_style)) if *last_style == style => last.push_str(&text),
+                _ => merged.push((text, style)),
+            }
+        }
+        *runs = merged;
+    }
+}
+
+impl TextComponent {
+    /// Like [`TextComponent::to_plain_text`], but with obfuscated text rendered as given.
+    pub fn to_plain_text_with(&self, obfuscated: ObfuscatedText) -> String {
+        let mut runs = Vec::new();
+        push_runs(self, &Style::default(), &mut runs);
+        obfuscated.apply(&mut runs);
+        runs.into_iter().map(|(text, _)| text).collect()
+    }
+}
+
+impl ToMarkdown for TextComponent {
+    fn to_markdown(&self, options: &MarkdownOptions) -> String {
+        let mut runs = Vec::new();
+        push_runs(self, &Style::default(), &mut runs);
+        options.obfuscated.apply(&mut runs);
+        render_markdown(&runs, options)
+    }
+}
+
+/// Renders formatted text with ANSI escape codes, such as to print it in a terminal.
+pub trait ToAnsi {
+    fn to_ansi(&self) -> String {
+        self.to_ansi_with(ObfuscatedText::Keep)
+    }
+
+    /// Like [`ToAnsi::to_ansi`], but with obfuscated text rendered as given.
+    fn to_ansi_with(&self, obfuscated: ObfuscatedText) -> String;
+}
+
+impl ToAnsi for TextComponent {
+    fn to_ansi_with(&self, obfuscated: ObfuscatedText) -> String {
+        let mut runs = Vec::new();
+        push_runs(self, &Style::default(), &mut runs);
+        obfuscated.apply(&mut runs);
+        let mut out = String::new();
+        push_ansi(&mut out, &runs, false);
+        out
+    }
+}
+
+/// The formatting of a piece of text, after inheriting from its parents.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(super) struct Style {
+    pub color: Option<String>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underlined: bool,
+    pub strikethrough: bool,
+    pub obfuscated: bool,
+}
+
+/// Flattens a component into pieces of text with the same style, merging neighbors that look the same.
+pub(super) fn push_runs(
+    component: &TextComponent,
+    parent: &Style,
+    runs: &mut Vec<(String, Style)>,
+) {
+    match component {
+        TextComponent::Plain(text) if text.contains('§') => {
+            push_runs(&parse_legacy_codes(text), parent, runs)
+        }
+        TextComponent::Plain(text) if text.is_empty() => {}
+        TextComponent::Plain(text) => match runs.last_mut() {
+            Some((last, style)) if style == parent => last.push_str(text),
+            _ => runs.push((text.clone(), parent.clone())),
+        },
+        TextComponent::Fancy(fancy) => {
+            let style = Style {
+                color: fancy.color.clone().or_else(|| parent.color.clone()),
+                bold: fancy.bold.unwrap_or(parent.bold),
+                italic: fancy.italic.unwrap_or(parent.italic),
+                underlined: fancy.underlined.unwrap_or(parent.underlined),
+                strikethrough: fancy.strikethrough.unwrap_or(parent.strikethrough),
+                obfuscated: fancy.obfuscated.unwrap_or(parent.obfuscated),
+            };
+            if let Some(text) = &fancy.text {
+                push_runs(&TextComponent::Plain(text.clone()), &style, runs);
+            }
+            for child in fancy.extra.iter().flatten() {
+                push_runs(child, &style, runs);
+            }
+        }
+        TextComponent::Extra(components) => {
+            for component in components {
+                push_runs(component, parent, runs);
+            }
+        }
+    }
+}
+
+pub(super) fn render_markdown(runs: &[(String, Style)], options: &MarkdownOptions) -> String {
+    let mut out = String::new();
+    if options.colors == MarkdownColors::AnsiCodeBlock {
+        // Discord only supports the original eight colors, and a code block can't contain its own fence
+        let mut ansi = String::new();
+        push_ansi(&mut ansi, runs, true);
+        out.push_str("```ansi\n");
+        out.push_str(&ansi.replace("```", "`\u{200b}``"));
+        out.push_str("\n```");
+        return out;
+    }
+
+    let mut color = None;
+    for (text, style) in runs {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                out.push('\n');
+            }
+            if line.trim().is_empty() {
+                out.push_str(line);
+                continue;
+            }
+            if options.colors == MarkdownColors::Emoji
+                && style.color.is_some()
+                && style.color != color
+            {
+                color = style.color.clone();
+                if let Some(named) = style.color.as_deref().and_then(NamedColor::find) {
+                    out.push_str(named.emoji);
+                }
+            }
+            push_emphasized(&mut out, line, style, options);
+        }
+    }
+    out
+}
+
+/// Writes a line of text wrapped in the markers for its style, keeping whitespace outside the markers since
+/// Markdown doesn't allow it inside.
+fn push_emphasized(out: &mut String, text: &str, style: &Style, options: &MarkdownOptions) {
+    let discord = options.flavor == MarkdownFlavor::Discord;
+    let markers = [
+        (discord && style.obfuscated, "||"),
+        (style.strikethrough, "~~"),
+        (discord && style.underlined, "__"),
+        (style.bold, "**"),
+        (style.italic, "*"),
+    ];
+    let trimmed = text.trim();
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+
+    out.push_str(leading);
+    for (_, marker) in markers.iter().filter(|(enabled, _)| *enabled) {
+        out.push_str(marker);
+    }
+    if options.escape {
+        for c in trimmed.chars() {
+            if matches!(
+                c,
+                '\\' | '*' | '_' | '~' | '`' | '|' | '<' | '>' | '#' | '[' | ']' | '(' | ')'
+            ) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    } else {
+        out.push_str(trimmed);
+    }
+    for (_, marker) in markers.iter().rev().filter(|(enabled, _)| *enabled) {
+        out.push_str(marker);
+    }
+    out.push_str(trailing);
+}
+
+/// Writes text with ANSI escape codes, using only the eight basic colors if `basic` is set.
+///
+/// The text comes from the server, so control characters other than newlines are left out. Otherwise a server
+/// could send its own escape sequences to move the cursor or change the terminal's title or clipboard.
+pub(super) fn push_ansi(out: &mut String, runs: &[(String, Style)], basic: bool) {
+    for (text, style) in runs {
+        out.push_str("\x1b[0");
+        for (enabled, code) in [(style.bold, 1), (style.underlined, 4)] {
+            if enabled {
+                write!(out, ";{code}").unwrap();
+            }
+        }
+        if !basic {
+            for (enabled, code) in [(style.italic, 3), (style.strikethrough, 9)] {
+                if enabled {
+                    write!(out, ";{code}").unwrap();
+                }
+            }
+        }
+        if let Some(named) = style.color.as_deref().and_then(NamedColor::find) {
+            let code = if basic { named.ansi % 60 } else { named.ansi };
+            write!(out, ";{code}").unwrap();
+        }
+        out.push('m');
+        out.extend(text.chars().filter(|&c| c == '\n' || !c.is_control()));
+    }
+    if !runs.is_empty() {
+        out.push_str("\x1b[0m");
+    }
+}
+
+/// One of the 16 colors with a legacy formatting code.
+struct NamedColor {
+    name: &'static str,
+    rgb: (u8, u8, u8),
+    ansi: u8,
+    emoji: &'static str,
+}
+
+#[rustfmt::skip]
+const NAMED_COLORS: [NamedColor; 16] = [
+    NamedColor { name: "black", rgb: (0, 0, 0), ansi: 30, emoji: "⚫" },
+    NamedColor { name: "dark_blue", rgb: (0, 0, 170), ansi: 34, emoji: "🔵" },
+    NamedColor { name: "dark_green", rgb: (0, 170, 0), ansi: 32, emoji: "🟢" },
+    NamedColor { name: "dark_aqua", rgb: (0, 170, 170), ansi: 36, emoji: "🔵" },
+    NamedColor { name: "dark_red", rgb: (170, 0, 0), ansi: 31, emoji: "🔴" },
+    NamedColor { name: "dark_purple", rgb: (170, 0, 170), ansi: 35, emoji: "🟣" },
+    NamedColor { name: "gold", rgb: (255, 170, 0), ansi: 33, emoji: "🟠" },
+    NamedColor { name: "gray", rgb: (170, 170, 170), ansi: 37, emoji: "⚪" },
+    NamedColor { name: "dark_gray", rgb: (85, 85, 85), ansi: 90, emoji: "⚫" },
+    NamedColor { name: "blue", rgb: (85, 85, 255), ansi: 94, emoji: "🔵" },
+    NamedColor { name: "green", rgb: (85, 255, 85), ansi: 92, emoji: "🟢" },
+    NamedColor { name: "aqua", rgb: (85, 255, 255), ansi: 96, emoji: "🔵" },
+    NamedColor { name: "red", rgb: (255, 85, 85), ansi: 91, emoji: "🔴" },
+    NamedColor { name: "light_purple", rgb: (255, 85, 255), ansi: 95, emoji: "🟣" },
+    NamedColor { name: "yellow", rgb: (255, 255, 85), ansi: 93, emoji: "🟡" },
+    NamedColor { name: "white", rgb: (255, 255, 255), ansi: 97, emoji: "⚪" },
+];
+
+impl NamedColor {
+    /// Finds a named color, or the closest one to a `#rrggbb` color.
+    fn find(color: &str) -> Option<&'static NamedColor> {
+        if let Some(named) = NAMED_COLORS.iter().find(|named| named.name == color) {
+            return Some(named);
+        }
+        let hex = color.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+        let rgb = u32::from_str_radix(hex, 16).ok()?;
+        let (r, g, b) = ((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8);
+        NAMED_COLORS.iter().min_by_key(|named| {
+            let distance = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2);
+            distance(named.rgb.0, r) + distance(named.rgb.1, g) + distance(named.rgb.2, b)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::borrow::ToOwned;
+
+    #[test]
+    fn formatting() {
+        let motd = TextComponent::Plain("§a§lHello§r world\n§o§nsub*title ".to_owned());
+        assert_eq!(
+            motd.to_markdown(&MarkdownOptions::default()),
+            "**Hello** world\n*sub\\*title* "
+        );
+        assert_eq!(
+            motd.to_markdown(
+                &MarkdownOptions::default()
+                    .with_flavor(MarkdownFlavor::Discord)
+                    .with_escape(false)
+            ),
+            "**Hello** world\n__*sub*title*__ "
+        );
+    }
+
+    #[test]
+    fn colors() {
+        let motd: TextComponent = serde_json::from_str(
+            r##"{"text":"","extra":[{"text":"Red ","color":"red"},{"text":"Teal","color":"#00aaa0"}]}"##,
+        )
+        .unwrap();
+        let options = MarkdownOptions::default();
+        assert_eq!(motd.to_markdown(&options), "Red Teal");
+        assert_eq!(
+            motd.to_markdown(&options.with_colors(MarkdownColors::Emoji)),
+            "🔴Red 🔵Teal"
+        );
+        assert_eq!(
+            motd.to_markdown(&options.with_colors(MarkdownColors::AnsiCodeBlock)),
+            "```ansi\n\x1b[0;31mRed \x1b[0;36mTeal\x1b[0m\n```"
+        );
+        assert_eq!(motd.to_ansi(), "\x1b[0;91mRed \x1b[0;36mTeal\x1b[0m");
+    }
+
+    #[test]
+    fn ansi_strips_control_characters() {
+        let motd =
+            TextComponent::Plain("Hi\x1b]52;c;cHduZWQ=\x07\x1b[2J\u{9b}31m\nthere\r".to_owned());
+        assert_eq!(
+            motd.to_ansi(),
+            "\x1b[0mHi]52;c;cHduZWQ=[2J31m\nthere\x1b[0m"
+        );
+        assert_eq!(
+            motd.to_markdown(
+                &MarkdownOptions::default().with_colors(MarkdownColors::AnsiCodeBlock)
+            ),
+            "```ansi\n\x1b[0mHi]52;c;cHduZWQ=[2J31m\nthere\x1b[0m\n```"
+        );
+    }
+
+    #[test]
+    fn obfuscated() {
+        let motd = TextComponent::Plain("§kab c§r Hi §kd\ne".to_owned());
+        assert_eq!(
+            motd.to_plain_text_with(ObfuscatedText::Keep),
+            "ab c Hi d\ne"
+        );
+        assert_eq!(motd.to_plain_text_with(ObfuscatedText::Remove), " Hi \n");
+        assert_eq!(
+            motd.to_plain_text_with(ObfuscatedText::Replace('▒')),
+            "▒▒ ▒ Hi ▒\n▒"
+        );
+        let options = MarkdownOptions::default().with_flavor(MarkdownFlavor::Discord);
+        assert_eq!(motd.to_markdown(&options), "||ab c|| Hi ||d||\n||e||");
+        assert_eq!(
+            motd.to_markdown(&options.with_obfuscated(ObfuscatedText::Replace('?'))),
+            "?? ? Hi ?\n?"
+        );
+        assert_eq!(
+            motd.to_ansi_with(ObfuscatedText::Remove),
+            "\x1b[0m Hi \n\x1b[0m"
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use super::{JavaServerInfo, ModRef};
+use alloc::{string::String, vec::Vec};
+
+/// The mods a server reported and the loader it runs, returned by [`JavaServerInfo::mod_list`].
+///
+/// This is the same whether the server sent the legacy `modinfo` field or the modern `forgeData` field.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ModList {
+    pub platform: ModPlatform,
+    pub mods: Vec<ModEntry>,
+    /// Whether the server left some of its mods out of the status.
+    pub truncated: bool,
+}
+
+/// The mod loader a server runs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ModPlatform {
+    /// Forge, which sends `modinfo` before Minecraft 1.13 and `forgeData` since.
+    Forge,
+    /// NeoForge, which sends `forgeData` like Forge.
+    NeoForge,
+    /// A loader identified by the `type` in `modinfo`, other than `FML`.
+    Other(String),
+}
+
+/// A mod in a [`ModList`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ModEntry {
+    pub id: String,
+    /// The mod's version, or `None` if the server sent a marker like `ANY` instead.
+    pub version: Option<String>,
+}
+
+impl JavaServerInfo {
+    /// Returns the mods the server reported and the loader it runs, or `None` if it didn't report any.
+    ///
+    /// The mods are the same ones returned by [`JavaServerInfo::mods`], with version markers replaced by `None`.
+    pub fn mod_list(&self) -> Option<ModList> {
+        if !self.is_modded() {
+            return None;
+        }
+        let mods: Vec<_> = self.mods().map(ModEntry::from).collect();
+        let platform = match (&self.forge_data, &self.mod_info) {
+            (Some(_), _) if mods.iter().any(|m| m.id == "neoforge") => ModPlatform::NeoForge,
+            (None, Some(info)) if !info.loader_type.eq_ignore_ascii_case("FML") => {
+                ModPlatform::Other(info.loader_type.clone())
+            }
+            _ => ModPlatform::Forge,
+        };
+        Some(ModList {
+            platform,
+            mods,
+            truncated: self
+                .forge_data
+                .as_ref()
+                .map_or(false, |data| data.truncated),
+        })
+    }
+}
+
+impl<'a> ModRef<'a> {
+    /// Returns the mod's version, or `None` if the server sent a marker like `ANY` instead.
+    pub fn known_version(&self) -> Option<&'a str> {
+        let is_marker = matches!(
+            self.version,
+            "" | "ANY" | "NONE" | "OHNOES" | "IGNORESERVERONLY"
+        );
+        (!is_marker).then_some(self.version)
+    }
+}
+
+impl From<ModRef<'_>> for ModEntry {
+    fn from(m: ModRef<'_>) -> Self {
+        ModEntry {
+            id: String::from(m.id),
+            version: m.known_version().map(String::from),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mod_list() {
+        let legacy: JavaServerInfo = r#"{
+            "description": "",
+            "modinfo": {
+                "type": "FML",
+                "modList": [
+                    { "modid": "minecraft", "version": "1.12.2" },
+                    { "modid": "forge", "version": "14.23.5.2860" }
+                ]
+            }
+        }"#
+        .parse()
+        .unwrap();
+        let list = legacy.mod_list().unwrap();
+        assert_eq!(list.platform, ModPlatform::Forge);
+        assert_eq!(list.mods.len(), 2);
+        assert_eq!(list.mods[1].version.as_deref(), Some("14.23.5.2860"));
+
+        let modern: JavaServerInfo = r#"{
+            "description": "",
+            "forgeData": {
+                "mods": [
+                    { "modId": "neoforge", "modmarker": "20.4.80" },
+                    { "modId": "serverside", "modmarker": "OHNOES" }
+                ],
+                "truncated": true
+            }
+        }"#
+        .parse()
+        .unwrap();
+        let list = modern.mod_list().unwrap();
+        assert_eq!(list.platform, ModPlatform::NeoForge);
+        assert!(list.truncated);
+        assert_eq!(list.mods[1].version, None);
+
+        // both fields are listed, like `mods` does
+        let both: JavaServerInfo = r#"{
+            "description": "",
+            "modinfo": { "type": "FML", "modList": [{ "modid": "legacy", "version": "ANY" }] },
+            "forgeData": { "mods": [{ "modId": "modern", "modmarker": "1.0" }] }
+        }"#
+        .parse()
+        .unwrap();
+        let list = both.mod_list().unwrap();
+        assert_eq!(list.platform, ModPlatform::Forge);
+        assert_eq!(list.mods.len(), both.mod_count());
+        assert_eq!(list.mods[0].version, None);
+        assert_eq!(both.find_mod("legacy").unwrap().known_version(), None);
+        assert_eq!(list.mods[1].version.as_deref(), Some("1.0"));
+
+        let vanilla: JavaServerInfo = r#"{ "description": "" }"#.parse().unwrap();
+        assert_eq!(vanilla.mod_list(), None);
+    }
+}
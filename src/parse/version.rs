@@ -0,0 +1,121 @@
+use super::{strip_legacy_codes, ServerVersion};
+use alloc::{string::String, vec::Vec};
+
+/// The parts of a version name like `Paper 1.21.1` or `BungeeCord 1.8.x-1.21.x`, returned by
+/// [`ServerVersion::parse_name`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct VersionName {
+    /// The server software, like `Paper`, or `None` if the name doesn't start with one, like a vanilla server's.
+    pub software: Option<String>,
+    /// The Minecraft versions in the name. A range like `1.8.x-1.21.x` results in its first and last versions.
+    pub mc_versions: Vec<String>,
+    /// The rest of the name, such as the software's own version, like `3.3.0-SNAPSHOT` in
+    /// `Velocity 3.3.0-SNAPSHOT`.
+    pub build: Option<String>,
+}
+
+impl VersionName {
+    /// Splits a version name into its parts. Formatting codes are removed first.
+    ///
+    /// Versions are recognized by their format: either `1.` or a year like `26.` followed by numbers, where the
+    /// last one may be `x`.
+    pub fn parse(name: &str) -> Self {
+        let mut parsed = VersionName::default();
+        let mut software = Vec::new();
+        let mut build = Vec::new();
+        let name = strip_legacy_codes(name);
+        for token in name.split_whitespace() {
+            let version = token.trim_matches(|c: char| matches!(c, ',' | '(' | ')' | '[' | ']'));
+            if is_mc_version(version) {
+                parsed.mc_versions.push(String::from(version));
+                continue;
+            }
+            // a range like `1.8.x-1.21.x`, or a version with a suffix like `1.20.4-R0.1`
+            if let Some((first, rest)) = version
+                .split_once('-')
+                .filter(|(first, _)| is_mc_version(first))
+            {
+                parsed.mc_versions.push(String::from(first));
+                if is_mc_version(rest) {
+                    parsed.mc_versions.push(String::from(rest));
+                } else {
+                    build.push(rest);
+                }
+                continue;
+            }
+            let starts_with_digit = token.starts_with(|c: char| c.is_ascii_digit());
+            if parsed.mc_versions.is_empty() && build.is_empty() && !starts_with_digit {
+                software.push(token);
+            } else {
+                build.push(token);
+            }
+        }
+        parsed.software = (!software.is_empty()).then(|| software.join(" "));
+        parsed.build = (!build.is_empty()).then(|| build.join(" "));
+        parsed
+    }
+}
+
+impl ServerVersion {
+    /// Splits the version name into the server software, Minecraft versions and build, like
+    /// [`VersionName::parse`].
+    pub fn parse_name(&self) -> VersionName {
+        VersionName::parse(&self.name)
+    }
+}
+
+fn is_mc_version(version: &str) -> bool {
+    let mut parts = version.split('.');
+    let major_ok = match parts.next().and_then(|major| major.parse::<u32>().ok()) {
+        Some(major) => major == 1 || (25..100).contains(&major),
+        None => false,
+    };
+    let rest: Vec<_> = parts.collect();
+    major_ok
+        && (1..=2).contains(&rest.len())
+        && rest.iter().enumerate().all(|(i, part)| {
+            (!part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+                || (*part == "x" && i == rest.len() - 1)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{borrow::ToOwned, vec};
+
+    #[test]
+    fn parse() {
+        let parse = |name: &str| {
+            let parsed = VersionName::parse(name);
+            (parsed.software, parsed.mc_versions, parsed.build)
+        };
+        let owned = |s: &str| Some(s.to_owned());
+        assert_eq!(
+            parse("Paper 1.21.1"),
+            (owned("Paper"), vec!["1.21.1".to_owned()], None)
+        );
+        assert_eq!(
+            parse("BungeeCord 1.8.x-1.21.x"),
+            (
+                owned("BungeeCord"),
+                vec!["1.8.x".to_owned(), "1.21.x".to_owned()],
+                None
+            )
+        );
+        assert_eq!(
+            parse("Velocity 3.3.0-SNAPSHOT"),
+            (owned("Velocity"), vec![], owned("3.3.0-SNAPSHOT"))
+        );
+        assert_eq!(parse("§c1.20.4"), (None, vec!["1.20.4".to_owned()], None));
+        assert_eq!(
+            parse("Spigot 1.20.4-R0.1"),
+            (owned("Spigot"), vec!["1.20.4".to_owned()], owned("R0.1"))
+        );
+        assert_eq!(
+            parse("Requires 26.1"),
+            (owned("Requires"), vec!["26.1".to_owned()], None)
+        );
+    }
+}
@@ -0,0 +1,509 @@
+//! Pinging a server repeatedly, such as to monitor whether it's online.
+
+use crate::log::debug;
+use crate::{ping_outcome_or_timeout, PingError, PingOutcome, ServerAddress};
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::time::Instant;
+
+/// Pings a server over and over, opening a new connection for each status request since servers close the
+/// connection after answering one.
+///
+/// After a failed ping, the next one is delayed by a backoff that doubles with each consecutive failure.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut poller = elytra_ping::StatusPoller::new(("mc.hypixel.net", 25565));
+/// loop {
+///     match poller.next_status().await {
+///         Ok(outcome) => println!("{} players online", outcome.info.players.map_or(0, |p| p.online)),
+///         Err(error) => println!("ping failed: {error}"),
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StatusPoller {
+    target: ServerAddress,
+    interval: Duration,
+    timeout: Duration,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    backoff: Option<Duration>,
+    next_poll: Option<Instant>,
+}
+
+impl StatusPoller {
+    /// Creates a poller that pings every 30 seconds, with a 5 second timeout and backoff of up to 5 minutes.
+    pub fn new(target: impl Into<ServerAddress>) -> Self {
+        Self {
+            target: target.into(),
+            interval: Duration::from_secs(30),
+            timeout: Duration::from_secs(5),
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(300),
+            backoff: None,
+            next_poll: None,
+        }
+    }
+
+    /// Sets how long to wait after a successful ping before sending the next one.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how long each ping can take before it fails.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the delay after the first failed ping, and the most it can grow to after more failures.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    pub fn target(&self) -> &ServerAddress {
+        &self.target
+    }
+
+    /// Waits until the next ping is due, then pings the server.
+    ///
+    /// The first call pings immediately.
+    pub async fn next_status(&mut self) -> Result<PingOutcome, PingError> {
+        if let Some(next_poll) = self.next_poll {
+            tokio::time::sleep_until(next_poll).await;
+        }
+        let result = ping_outcome_or_timeout(self.target.clone(), self.timeout).await;
+        let delay = match &result {
+            Ok(_) => {
+                self.backoff = None;
+                self.interval
+            }
+            Err(error) => {
+                let backoff = self
+                    .backoff
+                    .map_or(self.initial_backoff, |backoff| backoff * 2)
+                    .min(self.max_backoff);
+                debug!(
+                    target = %self.target,
+                    ?backoff,
+                    %error,
+                    "Polling failed"
+                );
+                self.backoff = Some(backoff);
+                backoff
+            }
+        };
+        self.next_poll = Some(Instant::now() + delay);
+        result
+    }
+}
+
+/// A summary of one poll, small enough to keep many of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct StatusSample {
+    /// When the ping was started, or when it failed.
+    pub timestamp: SystemTime,
+    /// Whether the ping succeeded.
+    pub online: bool,
+    /// The number of players online, if the ping succeeded and the server reported it.
+    pub players_online: Option<u32>,
+    /// The round-trip time of the ping packet, if the ping succeeded.
+    pub latency: Option<Duration>,
+}
+
+impl StatusSample {
+    pub fn from_result(result: &Result<PingOutcome, PingError>) -> Self {
+        match result {
+            Ok(outcome) => Self {
+                timestamp: outcome.timestamp,
+                online: true,
+                players_online: outcome.info.players.as_ref().map(|players| players.online),
+                latency: Some(outcome.latency),
+            },
+            Err(_) => Self {
+                timestamp: SystemTime::now(),
+                online: false,
+                players_online: None,
+                latency: None,
+            },
+        }
+    }
+}
+
+/// The most recent samples of each server being monitored, with the oldest dropped once there are too many.
+///
+/// Samples are expected to be recorded in chronological order.
+#[derive(Debug, Clone)]
+pub struct StatusHistory {
+    capacity: usize,
+    targets: HashMap<ServerAddress, VecDeque<StatusSample>>,
+}
+
+impl StatusHistory {
+    /// Creates a history that keeps the last `capacity` samples of each server.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "history capacity must be at least 1");
+        Self {
+            capacity,
+            targets: HashMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, target: &ServerAddress, sample: StatusSample) {
+        let samples = match self.targets.get_mut(target) {
+            Some(samples) => samples,
+            None => self
+                .targets
+                .entry(target.clone())
+                .or_insert_with(|| VecDeque::with_capacity(self.capacity)),
+        };
+        if samples.len() == self.capacity {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    /// Returns the samples of `target`, oldest first.
+    pub fn samples(&self, target: &ServerAddress) -> impl Iterator<Item = &StatusSample> {
+        self.targets.get(target).into_iter().flatten()
+    }
+
+    pub fn latest(&self, target: &ServerAddress) -> Option<&StatusSample> {
+        self.targets.get(target)?.back()
+    }
+
+    /// Returns the samples of `target` taken at or after `timestamp`, oldest first.
+    pub fn since(
+        &self,
+        target: &ServerAddress,
+        timestamp: SystemTime,
+    ) -> impl Iterator<Item = &StatusSample> {
+        self.samples(target)
+            .skip_while(move |sample| sample.timestamp < timestamp)
+    }
+
+    /// Returns the fraction of samples of `target` within the last `window` that were online, or `None` if
+    /// there are no samples in the window.
+    pub fn availability(&self, target: &ServerAddress, window: Duration) -> Option<f64> {
+        let start = SystemTime::now().checked_sub(window).unwrap_or(UNIX_EPOCH);
+        let (online, total) = self
+            .since(target, start)
+            .fold((0, 0), |(online, total), sample| {
+                (online + usize::from(sample.online), total + 1)
+            });
+        (total > 0).then(|| online as f64 / total as f64)
+    }
+
+    /// Returns the servers that have samples.
+    pub fn targets(&self) -> impl Iterator<Item = &ServerAddress> {
+        self.targets.keys()
+    }
+
+    /// Forgets the samples of `target`, returning whether there were any.
+    pub fn remove(&mut self, target: &ServerAddress) -> bool {
+        self.targets.remove(target).is_some()
+    }
+}
+
+/// Whether a server is up, as decided by [`AvailabilityTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Availability {
+    Online,
+    Offline,
+}
+
+/// Decides whether a server is online from consecutive ping results, so a single dropped ping doesn't make
+/// a server look like it went offline.
+///
+/// A server is considered offline after `offline_after` failed pings in a row, and online again after
+/// `online_after` successful pings in a row. Until one of those happens, its availability is unknown.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use elytra_ping::poller::AvailabilityTracker;
+///
+/// let mut poller = elytra_ping::StatusPoller::new(("mc.hypixel.net", 25565));
+/// let mut tracker = AvailabilityTracker::new(3, 2);
+/// loop {
+///     if let Some(availability) = tracker.record(&poller.next_status().await) {
+///         println!("{} is now {availability:?}", poller.target());
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AvailabilityTracker {
+    offline_after: u32,
+    online_after: u32,
+    availability: Option<Availability>,
+    /// The availability that the latest results disagreeing with the current one point to, and how many
+    /// of them there were in a row.
+    pending: Option<Availability>,
+    streak: u32,
+}
+
+impl Default for AvailabilityTracker {
+    /// Creates a tracker that considers a server offline after 3 failures and online after 1 success.
+    fn default() -> Self {
+        Self::new(3, 1)
+    }
+}
+
+impl AvailabilityTracker {
+    /// # Panics
+    ///
+    /// Panics if either threshold is zero.
+    pub fn new(offline_after: u32, online_after: u32) -> Self {
+        assert!(
+            offline_after > 0 && online_after > 0,
+            "availability thresholds must be at least 1"
+        );
+        Self {
+            offline_after,
+            online_after,
+            availability: None,
+            pending: None,
+            streak: 0,
+        }
+    }
+
+    /// Records the result of a ping, returning the server's new availability if it changed.
+    pub fn record(&mut self, result: &Result<PingOutcome, PingError>) -> Option<Availability> {
+        self.observe(result.is_ok())
+    }
+
+    fn observe(&mut self, online: bool) -> Option<Availability> {
+        let (observed, threshold) = if online {
+            (Availability::Online, self.online_after)
+        } else {
+            (Availability::Offline, self.offline_after)
+        };
+        if self.availability == Some(observed) {
+            self.pending = None;
+            return None;
+        }
+        if self.pending != Some(observed) {
+            self.pending = Some(observed);
+            self.streak = 0;
+        }
+        self.streak += 1;
+        if self.streak < threshold {
+            return None;
+        }
+        self.pending = None;
+        self.availability = Some(observed);
+        Some(observed)
+    }
+
+    /// Returns whether the server is online, or `None` if there haven't been enough pings in a row to tell.
+    pub fn availability(&self) -> Option<Availability> {
+        self.availability
+    }
+}
+
+/// Writes samples as CSV rows of `timestamp,online,players,latency_ms`, for graphing in a spreadsheet.
+///
+/// Timestamps are written in UTC in the ISO 8601 format, and values that are unknown are left empty. The
+/// header is written before the first row.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() -> std::io::Result<()> {
+/// use elytra_ping::poller::{CsvExporter, StatusSample};
+///
+/// let mut poller = elytra_ping::StatusPoller::new(("mc.hypixel.net", 25565));
+/// let mut exporter = CsvExporter::new(std::fs::File::create("uptime.csv")?);
+/// loop {
+///     let result = poller.next_status().await;
+///     exporter.write_sample(&StatusSample::from_result(&result))?;
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct CsvExporter<W> {
+    writer: W,
+    wrote_header: bool,
+}
+
+impl<W: Write> CsvExporter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            wrote_header: false,
+        }
+    }
+
+    pub fn write_sample(&mut self, sample: &StatusSample) -> io::Result<()> {
+        if !self.wrote_header {
+            self.writer
+                .write_all(b"timestamp,online,players,latency_ms\n")?;
+            self.wrote_header = true;
+        }
+        write!(
+            self.writer,
+            "{},{}",
+            Iso8601(sample.timestamp),
+            sample.online
+        )?;
+        match sample.players_online {
+            Some(players) => write!(self.writer, ",{players}")?,
+            None => self.writer.write_all(b",")?,
+        }
+        match sample.latency {
+            Some(latency) => writeln!(self.writer, ",{}", latency.as_millis()),
+            None => self.writer.write_all(b",\n"),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Formats a time as `YYYY-MM-DDTHH:MM:SSZ`.
+struct Iso8601(SystemTime);
+
+impl std::fmt::Display for Iso8601 {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let secs = self
+            .0
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let (days, secs) = (secs / 86400, secs % 86400);
+        // convert days since the epoch to a date in the proleptic Gregorian calendar
+        let days = days + 719468;
+        let era = days / 146097;
+        let day_of_era = days % 146097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + u64::from(month <= 2);
+        write!(
+            f,
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            secs / 3600,
+            secs / 60 % 60,
+            secs % 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn history() {
+        let target = ServerAddress::new("localhost", 25565);
+        let now = SystemTime::now();
+        let sample = |age: u64, online: bool| StatusSample {
+            timestamp: now - Duration::from_secs(age),
+            online,
+            players_online: None,
+            latency: None,
+        };
+
+        let mut history = StatusHistory::new(3);
+        assert_eq!(history.availability(&target, Duration::from_secs(60)), None);
+        for (age, online) in [(400, true), (300, false), (200, true), (100, false)] {
+            history.record(&target, sample(age, online));
+        }
+        // the oldest sample was dropped
+        assert_eq!(history.samples(&target).count(), 3);
+        assert_eq!(history.latest(&target), Some(&sample(100, false)));
+        assert_eq!(
+            history
+                .since(&target, now - Duration::from_secs(250))
+                .count(),
+            2
+        );
+        assert_eq!(
+            history.availability(&target, Duration::from_secs(250)),
+            Some(0.5)
+        );
+        assert_eq!(history.availability(&target, Duration::from_secs(50)), None);
+
+        assert!(history.remove(&target));
+        assert_eq!(history.latest(&target), None);
+    }
+
+    #[test]
+    fn availability() {
+        let mut tracker = AvailabilityTracker::new(3, 2);
+        let changes: Vec<_> = [
+            true, false, true, true, false, false, true, false, false, false,
+        ]
+        .into_iter()
+        .map(|online| tracker.observe(online))
+        .collect();
+        assert_eq!(
+            changes,
+            [
+                None,
+                None,
+                None,
+                Some(Availability::Online),
+                None,
+                None,
+                // a success resets the count of failures
+                None,
+                None,
+                None,
+                Some(Availability::Offline),
+            ]
+        );
+        assert_eq!(tracker.availability(), Some(Availability::Offline));
+    }
+
+    #[test]
+    fn csv() {
+        let mut exporter = CsvExporter::new(Vec::new());
+        exporter
+            .write_sample(&StatusSample {
+                timestamp: UNIX_EPOCH + Duration::from_secs(951_827_696),
+                online: true,
+                players_online: Some(3),
+                latency: Some(Duration::from_millis(42)),
+            })
+            .unwrap();
+        exporter
+            .write_sample(&StatusSample {
+                timestamp: UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+                online: false,
+                players_online: None,
+                latency: None,
+            })
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(exporter.into_inner()).unwrap(),
+            "timestamp,online,players,latency_ms\n\
+             2000-02-29T12:34:56Z,true,3,42\n\
+             2023-11-14T22:13:20Z,false,,\n"
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Exposing the results of monitoring servers to Prometheus.
+
+use crate::{PingError, PingOutcome, ServerAddress};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::Duration;
+
+/// Per-server gauges, kept up to date with the latest ping of each server and rendered in the Prometheus
+/// text format for a scrape endpoint.
+///
+/// Each gauge has a `target` label with the server's address. If the latest ping failed, only
+/// `elytra_ping_online` is reported for that server.
+///
+/// ```no_run
+/// # #[tokio::main]
+/// # async fn main() {
+/// use elytra_ping::{prometheus::StatusGauges, StatusPoller};
+/// use std::sync::{Arc, Mutex};
+///
+/// let gauges = Arc::new(Mutex::new(StatusGauges::new()));
+/// let mut poller = StatusPoller::new(("mc.hypixel.net", 25565));
+/// loop {
+///     let result = poller.next_status().await;
+///     gauges.lock().unwrap().record(poller.target(), &result);
+///     // serve `gauges.lock().unwrap().to_string()` from the scrape endpoint
+/// }
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StatusGauges {
+    targets: BTreeMap<String, TargetGauges>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TargetGauges {
+    online: bool,
+    players_online: Option<u32>,
+    latency: Option<Duration>,
+}
+
+impl StatusGauges {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Updates the gauges for `target` with the result of pinging it.
+    pub fn record(&mut self, target: &ServerAddress, result: &Result<PingOutcome, PingError>) {
+        let gauges = match result {
+            Ok(outcome) => TargetGauges {
+                online: true,
+                players_online: outcome.info.players.as_ref().map(|players| players.online),
+                latency: Some(outcome.latency),
+            },
+            Err(_) => TargetGauges {
+                online: false,
+                players_online: None,
+                latency: None,
+            },
+        };
+        self.targets.insert(target.to_string(), gauges);
+    }
+
+    /// Stops reporting gauges for `target`, returning whether it was being reported.
+    pub fn remove(&mut self, target: &ServerAddress) -> bool {
+        self.targets.remove(&target.to_string()).is_some()
+    }
+}
+
+impl fmt::Display for StatusGauges {
+    /// Writes the gauges in the Prometheus text exposition format.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_gauge(
+            f,
+            "elytra_ping_online",
+            "Whether the server responded to the latest ping.",
+            |gauges| Some(u8::from(gauges.online).into()),
+        )?;
+        self.write_gauge(
+            f,
+            "elytra_ping_players_online",
+            "The number of players the server reported being online.",
+            |gauges| gauges.players_online.map(f64::from),
+        )?;
+        self.write_gauge(
+            f,
+            "elytra_ping_latency_ms",
+            "The round-trip time of the latest ping, in milliseconds.",
+            |gauges| gauges.latency.map(|latency| latency.as_secs_f64() * 1000.0),
+        )
+    }
+}
+
+impl StatusGauges {
+    fn write_gauge(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        name: &str,
+        help: &str,
+        value: impl Fn(&TargetGauges) -> Option<f64>,
+    ) -> fmt::Result {
+        writeln!(f, "# HELP {name} {help}")?;
+        writeln!(f, "# TYPE {name} gauge")?;
+        for (target, gauges) in &self.targets {
+            if let Some(value) = value(gauges) {
+                writeln!(f, "{name}{{target=\"{}\"}} {value}", LabelValue(target))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Escapes a label value, which is quoted in the text format.
+struct LabelValue<'a>(&'a str);
+
+impl fmt::Display for LabelValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\\' => f.write_str("\\\\")?,
+                '"' => f.write_str("\\\"")?,
+                '\n' => f.write_str("\\n")?,
+                c => fmt::Write::write_char(f, c)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offline_target() {
+        let mut gauges = StatusGauges::new();
+        let target = ServerAddress::new("say \"hi\"", 25565);
+        let error = crate::TimeoutSnafu {
+            target: target.clone(),
+        }
+        .build();
+        gauges.record(&target, &Err(error));
+        let rendered = gauges.to_string();
+        assert!(rendered.contains("elytra_ping_online{target=\"say \\\"hi\\\":25565\"} 0\n"));
+        assert!(!rendered.contains("elytra_ping_players_online{"));
+        assert!(!rendered.contains("elytra_ping_latency_ms{"));
+
+        assert!(gauges.remove(&target));
+        assert!(!gauges.to_string().contains("target="));
+    }
+}
@@ -1,27 +1,39 @@
+pub use self::capture::{CapturedPacket, PacketCapture, PacketDirection};
+#[cfg(feature = "codec")]
+pub use self::codec::SlpCodec;
 pub use self::frame::{Frame, FrameError, ServerState};
-use crate::mc_string::encode_mc_string;
+pub use self::login::LoginProbe;
+pub use self::observer::FrameObserver;
+use self::observer::Observers;
+use crate::address::AddressParseError;
+#[cfg(feature = "java_connect")]
+use crate::address::{Edition, IntoServerAddress, ServerAddress};
+use crate::log::{debug, error, info, trace};
+use crate::mc_string::BytesStr;
 use crate::mc_string::McStringError;
+#[cfg(feature = "java_connect")]
+use crate::net::TcpStream;
 #[cfg(feature = "java_parse")]
 use crate::parse::JavaServerInfo;
-use bytes::{Buf, BytesMut};
-use mc_varint::{VarInt, VarIntWrite};
+use crate::varint::{var_int_len, VarInt, VarIntBuf, VarIntBufMut, MAX_VAR_INT_LEN};
+use bytes::{Buf, Bytes, BytesMut};
 use snafu::OptionExt;
 use snafu::{Backtrace, GenerateImplicitData, Snafu};
+#[cfg(feature = "java_parse")]
 use std::str::FromStr;
-use std::{
-    fmt::Debug,
-    io::{Cursor, Write},
-    time::Duration,
-};
-use tokio::{
-    io::{AsyncReadExt, AsyncWriteExt, BufWriter},
-    net::TcpStream,
-};
-use tracing::error;
-use tracing::info;
-use tracing::{debug, event, instrument, trace, Level};
+#[cfg(any(feature = "java_connect", feature = "simple"))]
+use std::time::Instant;
+use std::{fmt::Debug, io::Cursor, net::SocketAddr, time::Duration};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
 
+mod capture;
+#[cfg(feature = "codec")]
+mod codec;
 mod frame;
+mod login;
+mod observer;
+#[cfg(feature = "futures")]
+mod stream;
 
 #[derive(Snafu, Debug)]
 pub enum ProtocolError {
@@ -47,7 +59,14 @@ pub enum ProtocolError {
         #[snafu(backtrace)]
         source: FrameError,
     },
+    /// The server address could not be parsed.
+    #[snafu(display("Invalid server address: {source}"), context(false))]
+    InvalidAddress {
+        #[snafu(backtrace)]
+        source: AddressParseError,
+    },
     /// Failed to resolve SRV record.
+    #[cfg(feature = "java_connect")]
     #[snafu(display("Failed to resolve SRV record: {source}"), context(false))]
     SrvResolveError {
         source: trust_dns_resolver::error::ResolveError,
@@ -57,15 +76,17 @@ pub enum ProtocolError {
     FrameOutOfOrder {
         backtrace: Backtrace,
         expected: &'static str,
-        got: Frame,
+        got: Box<Frame>,
     },
     /// Failed to parse JSON response.
+    #[cfg(feature = "java_parse")]
     #[snafu(display("Failed to parse JSON response: {source}"), context(false))]
     JsonParse {
         source: serde_json::Error,
         backtrace: Backtrace,
     },
     /// DNS lookup failed.
+    #[cfg(feature = "java_connect")]
     #[snafu(display("DNS lookup failed for address `{address}`."))]
     DNSLookupFailed {
         address: String,
@@ -73,91 +94,295 @@ pub enum ProtocolError {
     },
 }
 
+/// The transport [`SlpProtocol`] uses when no other is given.
+mod transport {
+    #[cfg(feature = "java_connect")]
+    pub type Default = crate::net::TcpStream;
+
+    /// Without `java_connect` there are no connections to default to, so the transport must be given.
+    #[cfg(not(feature = "java_connect"))]
+    #[derive(Debug)]
+    pub enum Default {}
+}
+
+/// A connection to a Java Edition server that speaks the Server List Ping protocol.
+///
+/// Connections are usually opened with [`connect`], but any transport that implements [`AsyncRead`] and
+/// [`AsyncWrite`] can be used with [`SlpProtocol::new`].
 #[derive(Debug)]
-pub struct SlpProtocol {
+pub struct SlpProtocol<S = transport::Default> {
     hostname: String,
     port: u16,
-    stream: BufWriter<TcpStream>,
+    stream: BufWriter<S>,
     buffer: BytesMut,
+    /// Outgoing packets are encoded here, so the allocation is reused.
+    write_buffer: BytesMut,
+    mimic_vanilla: bool,
+    /// The length prefix and body length of the frame at the start of the buffer, once they've been decoded.
+    pending_len: Option<(usize, usize)>,
+    connection_info: Option<ConnectionInfo>,
+    capture: Option<PacketCapture>,
+    observers: Observers,
+    /// Whether this is the server's end of the connection, whose traffic isn't counted in the metrics.
+    #[cfg(feature = "metrics")]
+    server: bool,
+}
+
+/// The most that is reserved in the read buffer at once while waiting for the rest of a packet.
+const READ_CHUNK_LEN: usize = 8 * 1024;
+
+/// Removes the first packet from `buffer` if it has been received in full, returning it along with the length
+/// of its length prefix.
+///
+/// `pending_len` remembers the length of a partially received packet, so its header isn't decoded again on every
+/// read.
+fn split_packet(
+    buffer: &mut BytesMut,
+    pending_len: &mut Option<(usize, usize)>,
+) -> Result<Option<(Bytes, usize)>, FrameError> {
+    let (header_len, body_len) = match *pending_len {
+        Some(pending_len) => pending_len,
+        None => {
+            let mut cursor = Cursor::new(&buffer[..]);
+            match Frame::read_length(&mut cursor) {
+                Ok(body_len) => {
+                    let len = (cursor.position() as usize, body_len);
+                    *pending_len = Some(len);
+                    len
+                }
+                // Not enough data has been buffered
+                Err(FrameError::Incomplete { .. }) => return Ok(None),
+                // An error was encountered
+                Err(e) => return Err(e),
+            }
+        }
+    };
+
+    let len = header_len + body_len;
+    if buffer.len() < len {
+        trace!(
+            packet_len = len,
+            downloaded = buffer.len(),
+            "Incomplete frame"
+        );
+        // the length came from the peer, so only make room for the next read instead of the whole packet
+        buffer.reserve((len - buffer.len()).min(READ_CHUNK_LEN));
+        return Ok(None);
+    }
+    *pending_len = None;
+
+    trace!("Splitting frame off the buffer");
+    Ok(Some((buffer.split_to(len).freeze(), header_len)))
+}
+
+/// Details about how [`connect`] reached a server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ConnectionInfo {
+    /// The target of the SRV record that was followed, if any.
+    pub srv_record: Option<String>,
+    /// The address that was connected to.
+    pub address: SocketAddr,
+    /// Time spent looking up the SRV record.
+    pub srv_time: Duration,
+    /// Time spent resolving the hostname.
+    pub dns_time: Duration,
+    /// What was looked up to find the address.
+    pub trace: ResolutionTrace,
+    /// Time spent opening the TCP connection.
+    pub connect_time: Duration,
 }
 
+/// Details about a packet received by [`SlpProtocol::read_frame_with_metadata`], for bandwidth accounting and
+/// debugging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct FrameMetadata {
+    /// The packet's id.
+    pub id: i32,
+    /// The length of the whole packet in bytes, including its length prefix.
+    pub len: usize,
+    /// The number of bytes before the packet's fields, which are the length prefix and the packet id.
+    pub header_len: usize,
+}
+
+/// The state a client asks to switch to in the handshake, also called its intent.
 #[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ProtocolState {
     Status = 1,
     Login = 2,
+    /// Logging in after being transferred from another server, added in 1.20.5.
+    Transfer = 3,
 }
-impl SlpProtocol {
-    pub fn new(hostname: String, port: u16, stream: TcpStream) -> Self {
+
+impl ProtocolState {
+    /// Returns the state with the id sent in a handshake, or `None` if the id is unknown.
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            1 => Some(Self::Status),
+            2 => Some(Self::Login),
+            3 => Some(Self::Transfer),
+            _ => None,
+        }
+    }
+}
+impl<S: AsyncRead + AsyncWrite + Unpin> SlpProtocol<S> {
+    /// Wraps a transport that is already connected to a server.
+    ///
+    /// `hostname` and `port` are sent to the server in the handshake.
+    ///
+    /// ```no_run
+    /// # async fn example(relay: tokio::io::DuplexStream) -> Result<(), elytra_ping::protocol::ProtocolError> {
+    /// let mut client = elytra_ping::SlpProtocol::new("mc.hypixel.net".to_owned(), 25565, relay);
+    /// client.handshake().await?;
+    /// let json = client.get_status_raw().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn new(hostname: String, port: u16, stream: S) -> Self {
         Self {
             hostname,
             port,
             stream: BufWriter::new(stream),
             buffer: BytesMut::with_capacity(4096),
+            write_buffer: BytesMut::new(),
+            mimic_vanilla: false,
+            pending_len: None,
+            connection_info: None,
+            capture: None,
+            observers: Observers::default(),
+            #[cfg(feature = "metrics")]
+            server: false,
+        }
+    }
+
+    /// Marks this as the server's end of the connection, so its traffic isn't counted as sent to or received
+    /// from servers.
+    #[cfg(any(feature = "server", feature = "test_util"))]
+    pub(crate) fn set_server(&mut self) {
+        #[cfg(feature = "metrics")]
+        {
+            self.server = true;
         }
     }
 
+    #[cfg(feature = "metrics")]
+    fn record_sent(&self, bytes: usize) {
+        if !self.server {
+            crate::metrics::bytes_sent(crate::address::Edition::Java, bytes);
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn record_received(&self, bytes: usize) {
+        if !self.server {
+            crate::metrics::bytes_received(crate::address::Edition::Java, bytes);
+        }
+    }
+
+    /// Registers an observer that will be called with every frame sent or received over this connection.
+    pub fn add_observer(&mut self, observer: impl FrameObserver + 'static) {
+        self.observers.push(Box::new(observer));
+    }
+
+    /// Starts recording the raw bytes of every packet sent or received, keeping at most `limit` bytes.
+    ///
+    /// This is useful for diagnosing servers that violate the protocol. The captured packets can be
+    /// retrieved with [`SlpProtocol::captured_packets`], even after an error.
+    pub fn enable_capture(&mut self, limit: usize) {
+        self.capture = Some(PacketCapture::new(limit));
+    }
+
+    /// Returns the packets recorded since [`SlpProtocol::enable_capture`] was called.
+    pub fn captured_packets(&self) -> Option<&PacketCapture> {
+        self.capture.as_ref()
+    }
+
+    /// Returns details about how the connection was established, if it was opened with [`connect`].
+    pub fn connection_info(&self) -> Option<&ConnectionInfo> {
+        self.connection_info.as_ref()
+    }
+
+    /// Makes the connection behave like the vanilla client, for anti-bot filters that drop pings that look
+    /// like they came from anything else.
+    ///
+    /// While enabled, the handshake is held back and sent in the same write as the packet after it, the way
+    /// the vanilla client groups them, and ping requests carry the current time as their payload instead of a
+    /// fixed value. Servers behind a proxy usually also expect the handshake to contain the address the player
+    /// typed rather than an SRV target, which can be set with [`SlpProtocol::set_handshake_address`].
+    pub fn set_mimic_vanilla(&mut self, enabled: bool) {
+        self.mimic_vanilla = enabled;
+    }
+
+    /// Changes the hostname and port sent in the handshake.
+    pub fn set_handshake_address(&mut self, hostname: String, port: u16) {
+        self.hostname = hostname;
+        self.port = port;
+    }
+
+    /// Creates the handshake to send before requesting the status, claiming to be a client using
+    /// [`Frame::PROTOCOL_VERSION`]. See [`SlpProtocol::create_handshake_frame_with`].
     pub fn create_handshake_frame(&self) -> Frame {
+        self.create_handshake_frame_with(Frame::PROTOCOL_VERSION, ProtocolState::Status)
+    }
+
+    /// Creates a handshake claiming to be a client using `protocol`, which asks to switch to `state`.
+    ///
+    /// IPv6 literals are sent without brackets or a zone index, in their shortest form, like the vanilla client
+    /// sends them. Other hostnames are sent as they are, so a hostname longer than
+    /// [`MAX_ADDRESS_LEN`](crate::mc_string::MAX_ADDRESS_LEN) makes sending the handshake fail.
+    pub fn create_handshake_frame_with(&self, protocol: i32, state: ProtocolState) -> Frame {
         Frame::Handshake {
-            protocol: VarInt::from(Frame::PROTOCOL_VERSION),
-            address: self.hostname.to_owned(),
+            protocol: VarInt::from(protocol),
+            address: handshake_host(&self.hostname).into(),
             port: self.port,
-            state: VarInt::from(ProtocolState::Status as i32),
+            state: VarInt::from(state as i32),
         }
     }
 
-    /// Sends frame data over the connection as a packet.
+    /// Sends frame data over the connection as a packet, along with any packets queued with
+    /// [`SlpProtocol::queue_frame`].
+    ///
+    /// The packet is encoded into a buffer that is reused for every packet sent over the connection.
     pub async fn write_frame(&mut self, frame: Frame) -> Result<(), ProtocolError> {
-        debug!("Writing frame: {frame:?}");
-
-        let mut packet_data: Vec<u8> = Vec::with_capacity(5);
-
-        match frame {
-            Frame::Handshake {
-                protocol,
-                address,
-                port,
-                state,
-            } => {
-                trace!("writing handshake frame");
-                packet_data.write_var_int(VarInt::from(Frame::HANDSHAKE_ID))?;
-                packet_data.write_var_int(protocol)?;
-                Write::write(&mut packet_data, &encode_mc_string(&address)?)?;
-                Write::write(&mut packet_data, &port.to_be_bytes())?;
-                packet_data.write_var_int(state)?;
-            }
-            Frame::StatusRequest => {
-                trace!("writing status request frame");
-                packet_data.write_var_int(VarInt::from(Frame::STATUS_REQUEST_ID))?;
-            }
-            Frame::StatusResponse { json } => {
-                trace!("writing status response frame");
-                packet_data.write_var_int(VarInt::from(Frame::STATUS_RESPONSE_ID))?;
-                Write::write(&mut packet_data, &encode_mc_string(&json)?)?;
-            }
-            Frame::PingRequest { payload } => {
-                trace!("writing ping request frame");
-                packet_data.write_var_int(VarInt::from(Frame::PING_REQUEST_ID))?;
-                Write::write(&mut packet_data, &payload.to_be_bytes())?;
-            }
-            Frame::PingResponse { payload } => {
-                trace!("writing ping response frame");
-                packet_data.write_var_int(VarInt::from(Frame::PING_RESPONSE_ID))?;
-                Write::write(&mut packet_data, &payload.to_be_bytes())?;
-            }
+        let handshake = matches!(frame, Frame::Handshake { .. });
+        self.queue_frame(frame)?;
+        if self.mimic_vanilla && handshake {
+            trace!("Holding the handshake until the next packet");
+            return Ok(());
         }
+        self.send_write_buffer().await
+    }
 
-        let len = VarInt::from(i32::try_from(packet_data.len()).unwrap());
-        event!(
-            Level::TRACE,
-            "combining packet length (of {}) and data",
-            packet_data.len()
-        );
-        let mut packet: Vec<u8> = Vec::with_capacity(packet_data.len() + 5);
-        packet.write_var_int(len)?;
-        Write::write(&mut packet, &packet_data)?;
+    /// Encodes a frame to be sent in the same write as the next one, instead of on its own.
+    ///
+    /// Sending several packets at once saves a write and usually fits them in one TCP segment, like the vanilla
+    /// client's handshake and status request.
+    pub fn queue_frame(&mut self, frame: Frame) -> Result<(), ProtocolError> {
+        let start = self.write_buffer.len();
+        if let Err(error) = frame.encode_into(&mut self.write_buffer) {
+            self.write_buffer.truncate(start);
+            return Err(error.into());
+        }
+        let len = self.write_buffer.len() - start;
+        debug!(frame_id = frame.id(), bytes = len, "Writing frame");
+        self.observers.notify(PacketDirection::Sent, &frame, len);
+        if let Some(capture) = &mut self.capture {
+            capture.push(PacketDirection::Sent, &self.write_buffer[start..]);
+        }
+        Ok(())
+    }
 
+    /// Sends the packets in the write buffer.
+    async fn send_write_buffer(&mut self) -> Result<(), ProtocolError> {
         trace!("sending the packet!");
-        self.stream.write_all(&packet).await?;
+        #[cfg(feature = "metrics")]
+        self.record_sent(self.write_buffer.len());
+        let result = self.stream.write_all_buf(&mut self.write_buffer).await;
+        // don't send the rest of a packet that failed partway along with the next one
+        self.write_buffer.clear();
+        result?;
         self.stream.flush().await?;
         Ok(())
     }
@@ -171,37 +396,91 @@ impl SlpProtocol {
         &mut self,
         server_state: Option<ServerState>,
     ) -> Result<Option<Frame>, ProtocolError> {
+        Ok(self
+            .read_frame_with_metadata(server_state)
+            .await?
+            .map(|(frame, _)| frame))
+    }
+
+    /// Like [`SlpProtocol::read_frame`], but also returns details about the packet the frame was parsed from.
+    pub async fn read_frame_with_metadata(
+        &mut self,
+        server_state: Option<ServerState>,
+    ) -> Result<Option<(Frame, FrameMetadata)>, ProtocolError> {
         loop {
             // Attempt to parse a frame from the buffered data. If enough data
             // has been buffered, the frame is returned.
-            if let Some(frame) = self.parse_frame(server_state)? {
-                debug!("Received frame: {frame:?}");
+            if let Some(frame) = self.parse_frame_with_metadata(server_state)? {
                 return Ok(Some(frame));
             }
 
-            // There is not enough buffered data to read a frame. Attempt to
-            // read more data from the socket.
-            //
-            // On success, the number of bytes is returned. `0` indicates "end
-            // of stream".
-            let bytes_read = self.stream.read_buf(&mut self.buffer).await?;
-            if bytes_read == 0 {
-                // The remote closed the connection. For this to be a clean
-                // shutdown, there should be no data in the read buffer. If
-                // there is, this means that the peer closed the socket while
-                // sending a frame.
-                if self.buffer.is_empty() {
-                    info!("Connection closed cleanly");
-                    return Ok(None);
-                }
-                error!("Connection closed unexpectedly");
-                return Err(ProtocolError::ConnectionClosed {
-                    backtrace: Backtrace::generate(),
-                });
+            if !self.fill_buffer().await? {
+                return Ok(None);
             }
         }
     }
 
+    /// Reads more data from the connection into the buffer, returning `false` if the connection was closed cleanly.
+    async fn fill_buffer(&mut self) -> Result<bool, ProtocolError> {
+        // On success, the number of bytes is returned. `0` indicates "end
+        // of stream".
+        let bytes_read = self.stream.read_buf(&mut self.buffer).await?;
+        #[cfg(feature = "metrics")]
+        self.record_received(bytes_read);
+        if bytes_read == 0 {
+            // The remote closed the connection. For this to be a clean
+            // shutdown, there should be no data in the read buffer. If
+            // there is, this means that the peer closed the socket while
+            // sending a frame.
+            if self.buffer.is_empty() {
+                info!("Connection closed cleanly");
+                return Ok(false);
+            }
+            error!("Connection closed unexpectedly");
+            if let Some(capture) = &mut self.capture {
+                capture.push(PacketDirection::Received, &self.buffer);
+            }
+            return Err(ProtocolError::ConnectionClosed {
+                backtrace: Backtrace::generate(),
+            });
+        }
+        Ok(true)
+    }
+
+    /// Receives a packet without parsing it into a [`Frame`], returning its id and body.
+    ///
+    /// This is used for packets outside the status state, which have no [`Frame`] variant.
+    pub(crate) async fn read_packet(&mut self) -> Result<Option<(i32, Vec<u8>)>, ProtocolError> {
+        loop {
+            if let Some((packet, header_len)) = self.split_packet()? {
+                let mut body = Cursor::new(&packet[header_len..]);
+                let id = body.get_var_int().map_err(FrameError::from)?;
+                debug!(packet_id = id, bytes = packet.len(), "Received packet");
+                return Ok(Some((id, body.chunk().to_vec())));
+            }
+
+            if !self.fill_buffer().await? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Sends a packet that has no [`Frame`] variant.
+    pub(crate) async fn write_packet(&mut self, id: i32, body: &[u8]) -> Result<(), ProtocolError> {
+        let len = var_int_len(id) + body.len();
+        debug!(packet_id = id, bytes = len, "Writing packet");
+        let start = self.write_buffer.len();
+        self.write_buffer.reserve(len + MAX_VAR_INT_LEN);
+        self.write_buffer
+            .put_var_int(i32::try_from(len).ok().context(PacketTooLongSnafu)?);
+        self.write_buffer.put_var_int(id);
+        self.write_buffer.extend_from_slice(body);
+        if let Some(capture) = &mut self.capture {
+            capture.push(PacketDirection::Sent, &self.write_buffer[start..]);
+        }
+        self.send_write_buffer().await
+    }
+
     /// Parse the most recent frame from the connection, removing it from the buffer.
     ///
     /// # Arguments
@@ -211,25 +490,56 @@ impl SlpProtocol {
         &mut self,
         server_state: Option<ServerState>,
     ) -> Result<Option<Frame>, ProtocolError> {
-        let mut cursor = Cursor::new(&self.buffer[..]);
-
-        // Check whether a full frame is available
-        match Frame::check(&mut cursor) {
-            Ok(()) => {
-                let frame = Frame::parse(&mut cursor, server_state)?;
+        Ok(self
+            .parse_frame_with_metadata(server_state)?
+            .map(|(frame, _)| frame))
+    }
 
-                trace!("Discarding frame from buffer");
-                // current cursor position is the entire frame
-                self.buffer.advance(cursor.position() as usize);
+    /// Like [`SlpProtocol::parse_frame`], but also returns details about the packet the frame was parsed from.
+    pub fn parse_frame_with_metadata(
+        &mut self,
+        server_state: Option<ServerState>,
+    ) -> Result<Option<(Frame, FrameMetadata)>, ProtocolError> {
+        let Some((packet, prefix_len)) = self.split_packet()? else {
+            return Ok(None);
+        };
+        let frame = Frame::parse_body(packet.slice(prefix_len..), server_state)?;
+        let metadata = FrameMetadata {
+            id: frame.id(),
+            len: packet.len(),
+            header_len: prefix_len + var_int_len(frame.id()),
+        };
+        debug!(
+            frame_id = metadata.id,
+            bytes = metadata.len,
+            "Received frame"
+        );
+        self.observers
+            .notify(PacketDirection::Received, &frame, metadata.len);
+        Ok(Some((frame, metadata)))
+    }
 
-                // Return the frame to the caller.
-                Ok(Some(frame))
-            }
-            // Not enough data has been buffered
-            Err(FrameError::Incomplete { .. }) => Ok(None),
-            // An error was encountered
-            Err(e) => Err(e.into()),
+    /// Removes the first packet from the buffer if it has been received in full, returning it along with the
+    /// length of its length prefix.
+    ///
+    /// The packet shares the buffer's memory, so strings parsed from it don't need to be copied.
+    fn split_packet(&mut self) -> Result<Option<(Bytes, usize)>, ProtocolError> {
+        let Some((packet, header_len)) = split_packet(&mut self.buffer, &mut self.pending_len)?
+        else {
+            return Ok(None);
+        };
+        if let Some(capture) = &mut self.capture {
+            capture.push(PacketDirection::Received, &packet);
         }
+        Ok(Some((packet, header_len)))
+    }
+
+    /// Sends bytes over the connection without framing them.
+    #[cfg(feature = "test_util")]
+    pub(crate) async fn write_raw(&mut self, bytes: &[u8]) -> Result<(), ProtocolError> {
+        self.stream.write_all(bytes).await?;
+        self.stream.flush().await?;
+        Ok(())
     }
 
     pub async fn disconnect(mut self) -> Result<(), ProtocolError> {
@@ -237,43 +547,79 @@ impl SlpProtocol {
         Ok(())
     }
 
-    #[cfg(feature = "simple")]
     pub async fn handshake(&mut self) -> Result<(), ProtocolError> {
         self.write_frame(self.create_handshake_frame()).await?;
         Ok(())
     }
 
-    #[cfg(feature = "simple")]
+    /// Sends the handshake and status request in the same write, like the vanilla client. The status response
+    /// can then be read with [`SlpProtocol::read_frame`].
+    pub async fn send_handshake_and_status(&mut self) -> Result<(), ProtocolError> {
+        self.queue_frame(self.create_handshake_frame())?;
+        self.write_frame(Frame::StatusRequest).await
+    }
+
+    /// Like [`SlpProtocol::handshake`], but claims to be a client using `protocol` instead of
+    /// [`Frame::PROTOCOL_VERSION`].
+    pub async fn handshake_with_protocol(&mut self, protocol: i32) -> Result<(), ProtocolError> {
+        self.write_frame(self.create_handshake_frame_with(protocol, ProtocolState::Status))
+            .await
+    }
+
+    #[cfg(feature = "java_parse")]
     pub async fn get_status(&mut self) -> Result<JavaServerInfo, ProtocolError> {
+        let json = self.get_status_bytes().await?;
+        Ok(JavaServerInfo::from_str(&json)?)
+    }
+
+    /// Requests the server's status, returning both the parsed status and the JSON it was parsed from.
+    #[cfg(feature = "java_parse")]
+    pub async fn get_status_with_json(
+        &mut self,
+    ) -> Result<(JavaServerInfo, String), ProtocolError> {
+        let json = self.get_status_bytes().await?;
+        let info = JavaServerInfo::from_str(&json)?;
+        Ok((info, json.into()))
+    }
+
+    /// Requests the server's status without parsing it, returning the JSON sent by the server.
+    pub async fn get_status_raw(&mut self) -> Result<String, ProtocolError> {
+        Ok(self.get_status_bytes().await?.into())
+    }
+
+    /// Like [`SlpProtocol::get_status_raw`], but returns the JSON without copying it out of the buffer it was
+    /// received in.
+    pub async fn get_status_bytes(&mut self) -> Result<BytesStr, ProtocolError> {
         self.write_frame(Frame::StatusRequest).await?;
         let frame = self
             .read_frame(None)
             .await?
             .context(ConnectionClosedSnafu)?;
-        let frame_data = match frame {
-            Frame::StatusResponse { json } => json,
-            frame => {
-                return FrameOutOfOrderSnafu {
-                    expected: "StatusResponse",
-                    got: frame,
-                }
-                .fail()
+        match frame {
+            Frame::StatusResponse { json } => Ok(json),
+            frame => FrameOutOfOrderSnafu {
+                expected: "StatusResponse",
+                got: frame,
             }
-        };
-        Ok(JavaServerInfo::from_str(&frame_data)?)
+            .fail(),
+        }
     }
 
     #[cfg(feature = "simple")]
     pub async fn get_latency(&mut self) -> Result<Duration, ProtocolError> {
-        use std::time::Instant;
         const PING_PAYLOAD: i64 = 54321;
 
+        let payload = if self.mimic_vanilla {
+            // the vanilla client sends a timestamp in milliseconds
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |time| time.as_millis() as i64)
+        } else {
+            PING_PAYLOAD
+        };
         let ping_time = Instant::now();
 
-        self.write_frame(Frame::PingRequest {
-            payload: PING_PAYLOAD,
-        })
-        .await?;
+        self.write_frame(Frame::PingRequest { payload }).await?;
         let frame = self
             .read_frame(None)
             .await?
@@ -290,41 +636,526 @@ impl SlpProtocol {
 }
 
 #[cfg(feature = "java_connect")]
-#[instrument]
-pub async fn connect(mut addrs: (String, u16)) -> Result<SlpProtocol, ProtocolError> {
-    use tokio::net::lookup_host;
-    use tracing::{debug, info};
-    use trust_dns_resolver::TokioAsyncResolver;
+impl SlpProtocol {
+    /// Sets whether small packets are sent immediately, disabling Nagle's algorithm. The vanilla client
+    /// enables this.
+    pub fn set_nodelay(&self, nodelay: bool) -> Result<(), ProtocolError> {
+        self.stream.get_ref().set_nodelay(nodelay)?;
+        Ok(())
+    }
+}
 
-    let resolver = TokioAsyncResolver::tokio_from_system_conf()?;
-    if let Ok(records) = resolver
-        .srv_lookup(format!("_minecraft._tcp.{}", addrs.0))
-        .await
-    {
-        if let Some(record) = records.iter().next() {
-            let record = record.target().to_utf8();
-            debug!("Found SRV record: {} -> {}", addrs.0, record);
-            addrs.0 = record;
+/// Formats a hostname for the handshake, removing the brackets and zone index from IPv6 literals.
+fn handshake_host(hostname: &str) -> String {
+    let host = hostname
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(hostname);
+    let ip = host.split_once('%').map_or(host, |(ip, _zone)| ip);
+    match ip.parse::<std::net::Ipv6Addr>() {
+        Ok(ip) => ip.to_string(),
+        Err(_) => hostname.to_owned(),
+    }
+}
+
+/// A server address that has been resolved by [`resolve`].
+#[cfg(feature = "java_connect")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ResolvedAddress {
+    /// The hostname to send in the handshake, which is the SRV record's target if one was found.
+    pub hostname: String,
+    pub port: u16,
+    /// The address to connect to.
+    pub address: SocketAddr,
+    /// Time spent looking up the SRV record.
+    pub srv_time: Duration,
+    /// Time spent resolving the hostname.
+    pub dns_time: Duration,
+    /// What was looked up to find the address.
+    pub trace: ResolutionTrace,
+}
+
+/// Reuses the address of an earlier connection, to reconnect without resolving it again.
+#[cfg(feature = "java_connect")]
+impl From<ConnectionInfo> for ResolvedAddress {
+    fn from(info: ConnectionInfo) -> Self {
+        Self {
+            hostname: info.trace.resolved_host.0.clone(),
+            port: info.trace.resolved_host.1,
+            address: info.address,
+            srv_time: info.srv_time,
+            dns_time: info.dns_time,
+            trace: info.trace,
         }
     }
+}
+
+/// An SRV record pointing to a Minecraft server.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct SrvRecord {
+    pub target: String,
+    pub port: u16,
+    pub priority: u16,
+    pub weight: u16,
+}
+
+/// A record of what was looked up to find a server's address.
+///
+/// CNAMEs are followed by the system resolver, which doesn't report them, so they aren't included.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ResolutionTrace {
+    /// Every SRV record that was found, in the order they would be tried.
+    pub srv_records: Vec<SrvRecord>,
+    /// The SRV record that was followed, if any.
+    pub srv_record: Option<SrvRecord>,
+    /// The hostname and port that were resolved, after following the SRV record.
+    pub resolved_host: (String, u16),
+    /// Every address the hostname resolved to. The first one is connected to.
+    pub addresses: Vec<SocketAddr>,
+}
+
+/// Looks up a server's SRV record and resolves its hostname, without connecting to it.
+#[cfg(feature = "java_connect")]
+pub async fn resolve(addrs: impl IntoServerAddress) -> Result<ResolvedAddress, ProtocolError> {
+    resolve_address(addrs.into_server_address(Edition::Java)?).await
+}
 
+#[cfg(feature = "java_connect")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(host = %address.host, port = address.port))
+)]
+async fn resolve_address(address: ServerAddress) -> Result<ResolvedAddress, ProtocolError> {
+    use crate::net::lookup_host;
+
+    let mut addrs: (String, u16) = address.into();
+
+    let srv_start = Instant::now();
+    let srv_records = lookup_srv(&addrs.0).await?;
+    let srv_record = srv_records.first().cloned();
+    if let Some(record) = &srv_record {
+        debug!(
+            target = %record.target,
+            target_port = record.port,
+            "Found SRV record"
+        );
+        addrs = (record.target.clone(), record.port);
+    }
+    let srv_time = srv_start.elapsed();
+
+    let dns_start = Instant::now();
+    let addresses: Vec<SocketAddr> = lookup_host(addrs.clone()).await?.collect();
     // lookup_host can return multiple but we just need one so we discard the rest
-    let socket_addrs = match lookup_host(addrs.clone()).await?.next() {
-        Some(socket_addrs) => socket_addrs,
-        None => {
-            info!("DNS lookup failed for address");
-            return DNSLookupFailedSnafu { address: addrs.0 }.fail();
+    let Some(&address) = addresses.first() else {
+        info!("DNS lookup failed for address");
+        return DNSLookupFailedSnafu { address: addrs.0 }.fail();
+    };
+
+    Ok(ResolvedAddress {
+        hostname: addrs.0.clone(),
+        port: addrs.1,
+        address,
+        srv_time,
+        dns_time: dns_start.elapsed(),
+        trace: ResolutionTrace {
+            srv_records,
+            srv_record,
+            resolved_host: addrs,
+            addresses,
+        },
+    })
+}
+
+/// Looks up a server's SRV records, ordered by priority and then weight.
+#[cfg(all(feature = "java_connect", not(turmoil)))]
+async fn lookup_srv(hostname: &str) -> Result<Vec<SrvRecord>, ProtocolError> {
+    use trust_dns_resolver::TokioAsyncResolver;
+
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()?;
+    let Ok(records) = resolver
+        .srv_lookup(format!("_minecraft._tcp.{hostname}"))
+        .await
+    else {
+        return Ok(Vec::new());
+    };
+    let mut records: Vec<SrvRecord> = records
+        .iter()
+        .map(|record| SrvRecord {
+            target: record.target().to_utf8(),
+            port: record.port(),
+            priority: record.priority(),
+            weight: record.weight(),
+        })
+        .collect();
+    records.sort_by_key(|record| (record.priority, std::cmp::Reverse(record.weight)));
+    Ok(records)
+}
+
+/// The simulated network has no DNS server to look up SRV records from.
+#[cfg(all(feature = "java_connect", turmoil))]
+async fn lookup_srv(_hostname: &str) -> Result<Vec<SrvRecord>, ProtocolError> {
+    Ok(Vec::new())
+}
+
+/// Opens a connection to an address returned by [`resolve`].
+///
+/// If the connection fails and the server has several SRV records, the remaining records are tried in order.
+#[cfg(feature = "java_connect")]
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(
+        skip_all,
+        fields(host = %resolved.hostname, port = resolved.port, address = %resolved.address)
+    )
+)]
+pub async fn connect_resolved(mut resolved: ResolvedAddress) -> Result<SlpProtocol, ProtocolError> {
+    let connect_start = Instant::now();
+    let stream = match TcpStream::connect(resolved.address).await {
+        Ok(stream) => stream,
+        Err(error) => {
+            info!(%error, "Failed to connect to SLP server");
+            match connect_srv_fallback(&mut resolved).await {
+                Some(stream) => stream,
+                None => return Err(error.into()),
+            }
         }
     };
+    info!("Connected to SLP server");
+    let mut protocol = SlpProtocol::new(resolved.hostname, resolved.port, stream);
+    protocol.connection_info = Some(ConnectionInfo {
+        srv_record: resolved
+            .trace
+            .srv_record
+            .as_ref()
+            .map(|record| record.target.clone()),
+        address: resolved.address,
+        srv_time: resolved.srv_time,
+        dns_time: resolved.dns_time,
+        trace: resolved.trace,
+        connect_time: connect_start.elapsed(),
+    });
+    Ok(protocol)
+}
 
-    match TcpStream::connect(socket_addrs).await {
-        Ok(stream) => {
-            info!("Connected to SLP server");
-            Ok(SlpProtocol::new(addrs.0, addrs.1, stream))
+/// Tries the SRV records after the one that was followed, updating `resolved` to the first one that connects.
+#[cfg(feature = "java_connect")]
+async fn connect_srv_fallback(resolved: &mut ResolvedAddress) -> Option<TcpStream> {
+    use crate::net::lookup_host;
+
+    let fallbacks: Vec<SrvRecord> = resolved.trace.srv_records.iter().skip(1).cloned().collect();
+    for record in fallbacks {
+        let addresses: Vec<SocketAddr> =
+            match lookup_host((record.target.as_str(), record.port)).await {
+                Ok(addresses) => addresses.collect(),
+                Err(error) => {
+                    debug!(target = %record.target, %error, "Failed to resolve SRV target");
+                    continue;
+                }
+            };
+        let Some(&address) = addresses.first() else {
+            continue;
+        };
+        match TcpStream::connect(address).await {
+            Ok(stream) => {
+                debug!(
+                    target = %record.target,
+                    target_port = record.port,
+                    "Connected to fallback SRV target"
+                );
+                resolved.hostname = record.target.clone();
+                resolved.port = record.port;
+                resolved.address = address;
+                resolved.trace.resolved_host = (record.target.clone(), record.port);
+                resolved.trace.srv_record = Some(record);
+                resolved.trace.addresses = addresses;
+                return Some(stream);
+            }
+            Err(error) => info!(
+                target = %record.target,
+                %error,
+                "Failed to connect to SRV target"
+            ),
         }
-        Err(error) => {
-            info!("Failed to connect to SLP server: {}", error);
-            Err(error.into())
+    }
+    None
+}
+
+#[cfg(feature = "java_connect")]
+pub async fn connect(addrs: impl IntoServerAddress) -> Result<SlpProtocol, ProtocolError> {
+    connect_resolved(resolve(addrs).await?).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mc_string::encode_mc_string;
+    use std::sync::{Arc, Mutex};
+    use tokio::net::TcpListener;
+
+    #[cfg(feature = "java_connect")]
+    #[tokio::test]
+    async fn srv_fallback() {
+        // bind then drop a listener to find a port nothing is listening on
+        let refused = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let record = |port, priority| SrvRecord {
+            target: "127.0.0.1".to_owned(),
+            port,
+            priority,
+            weight: 0,
+        };
+        let resolved = ResolvedAddress {
+            hostname: "127.0.0.1".to_owned(),
+            port: refused.port(),
+            address: refused,
+            srv_time: Duration::ZERO,
+            dns_time: Duration::ZERO,
+            trace: ResolutionTrace {
+                srv_records: vec![record(refused.port(), 0), record(port, 1)],
+                srv_record: Some(record(refused.port(), 0)),
+                resolved_host: ("127.0.0.1".to_owned(), refused.port()),
+                addresses: vec![refused],
+            },
+        };
+
+        let client = connect_resolved(resolved).await.unwrap();
+        let info = client.connection_info().unwrap();
+        assert_eq!(info.address.port(), port);
+        assert_eq!(info.trace.srv_record, Some(record(port, 1)));
+        assert_eq!(info.trace.resolved_host.1, port);
+    }
+
+    #[test]
+    fn transfer_intent() {
+        // handshake for protocol 767 to localhost:25565 with intent 3
+        let bytes = [
+            0x10, 0x00, 0xff, 0x05, 0x09, b'l', b'o', b'c', b'a', b'l', b'h', b'o', b's', b't',
+            0x63, 0xdd, 0x03,
+        ];
+        let Frame::Handshake { state, .. } =
+            Frame::parse_bytes(&bytes, Some(ServerState::Handshake)).unwrap()
+        else {
+            panic!("expected a handshake");
+        };
+        assert_eq!(
+            ProtocolState::from_id(i32::from(state)),
+            Some(ProtocolState::Transfer)
+        );
+        assert_eq!(ProtocolState::from_id(4), None);
+    }
+
+    #[test]
+    fn handshake_state() {
+        let client = SlpProtocol::new("localhost".to_owned(), 25565, tokio::io::empty());
+        for state in [
+            ProtocolState::Status,
+            ProtocolState::Login,
+            ProtocolState::Transfer,
+        ] {
+            let Frame::Handshake {
+                protocol,
+                state: sent,
+                ..
+            } = client.create_handshake_frame_with(765, state)
+            else {
+                panic!("expected a handshake");
+            };
+            assert_eq!(i32::from(protocol), 765);
+            assert_eq!(ProtocolState::from_id(i32::from(sent)), Some(state));
+        }
+    }
+
+    #[test]
+    fn handshake_ip_literals() {
+        for (hostname, expected) in [
+            ("mc.example.com", "mc.example.com"),
+            ("203.0.113.7", "203.0.113.7"),
+            ("::1", "::1"),
+            ("[2001:db8::1]", "2001:db8::1"),
+            ("fe80::1%eth0", "fe80::1"),
+            ("[fe80:0:0:0:0:0:0:1%25]", "fe80::1"),
+            // not an IP literal, so it's left alone
+            ("[mc.example.com]", "[mc.example.com]"),
+        ] {
+            let client = SlpProtocol::new(hostname.to_owned(), 25565, tokio::io::empty());
+            let Frame::Handshake { address, .. } = client.create_handshake_frame() else {
+                panic!("expected a handshake");
+            };
+            assert_eq!(*address, *expected);
         }
+
+        let client = SlpProtocol::new("a".repeat(300), 25565, tokio::io::empty());
+        assert!(client
+            .create_handshake_frame()
+            .encode_into(&mut BytesMut::new())
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn frame_metadata() {
+        let (client, mut server) = tokio::io::duplex(512);
+        let mut client = SlpProtocol::new("localhost".to_owned(), 25565, client);
+        let json = "x".repeat(200);
+        let mut packet = BytesMut::new();
+        Frame::StatusResponse { json: json.into() }
+            .encode_into(&mut packet)
+            .unwrap();
+        server.write_all(&packet).await.unwrap();
+
+        let (_, metadata) = client
+            .read_frame_with_metadata(None)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            metadata,
+            FrameMetadata {
+                id: Frame::STATUS_RESPONSE_ID,
+                // a two byte length prefix, the id, then the string's two byte length prefix
+                len: 2 + 1 + 2 + 200,
+                header_len: 3,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn frame_split_across_reads() {
+        let (client, mut server) = tokio::io::duplex(64);
+        let mut client = SlpProtocol::new("localhost".to_owned(), 25565, client);
+        let json = "x".repeat(200);
+        let mut packet = Vec::new();
+        packet.put_var_int(json.len() as i32 + 3);
+        packet.put_var_int(Frame::STATUS_RESPONSE_ID);
+        packet.extend_from_slice(&encode_mc_string(&json).unwrap());
+
+        let writer = tokio::spawn(async move {
+            // the length prefix arrives in two parts, then the body in several
+            for chunk in [&packet[..1], &packet[1..2], &packet[2..100], &packet[100..]] {
+                server.write_all(chunk).await.unwrap();
+                tokio::task::yield_now().await;
+            }
+        });
+        let frame = client.read_frame(None).await.unwrap().unwrap();
+        assert!(matches!(frame, Frame::StatusResponse { json: received } if received == *json));
+        assert_eq!(client.pending_len, None);
+        writer.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn oversized_frame() {
+        let (client, mut server) = tokio::io::duplex(64);
+        let mut client = SlpProtocol::new("localhost".to_owned(), 25565, client);
+        // a length prefix of i32::MAX, far more than any packet is allowed to be
+        server
+            .write_all(&[0xff, 0xff, 0xff, 0xff, 0x07])
+            .await
+            .unwrap();
+        let result = client.read_frame(None).await;
+        assert!(matches!(
+            result,
+            Err(ProtocolError::ParseFailed {
+                source: FrameError::InvalidLength { .. }
+            })
+        ));
+
+        // a valid but large length only reserves part of the packet until more arrives
+        let mut buffer = BytesMut::new();
+        buffer.put_var_int(Frame::MAX_PACKET_LEN as i32);
+        let mut pending_len = None;
+        assert!(split_packet(&mut buffer, &mut pending_len)
+            .unwrap()
+            .is_none());
+        assert!(buffer.capacity() <= READ_CHUNK_LEN + MAX_VAR_INT_LEN);
+    }
+
+    #[tokio::test]
+    async fn mimic_vanilla() {
+        let (client, mut server) = tokio::io::duplex(256);
+        let mut client = SlpProtocol::new("localhost".to_owned(), 25565, client);
+        client.set_mimic_vanilla(true);
+        client.enable_capture(256);
+        client.handshake().await.unwrap();
+
+        // nothing is sent until the status request
+        let mut received = vec![0; 256];
+        let early =
+            tokio::time::timeout(Duration::from_millis(20), server.read(&mut received)).await;
+        assert!(early.is_err());
+        client.write_frame(Frame::StatusRequest).await.unwrap();
+        let n = server.read(&mut received).await.unwrap();
+
+        let packets: Vec<_> = client.captured_packets().unwrap().packets().collect();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[1].bytes, [0x01, Frame::STATUS_REQUEST_ID as u8]);
+        assert_eq!(
+            received[..n],
+            [&packets[0].bytes[..], &packets[1].bytes[..]].concat()
+        );
+    }
+
+    #[tokio::test]
+    async fn handshake_and_status_in_one_write() {
+        let (client, mut server) = tokio::io::duplex(256);
+        let mut client = SlpProtocol::new("localhost".to_owned(), 25565, client);
+        client.send_handshake_and_status().await.unwrap();
+
+        let mut expected = BytesMut::new();
+        client
+            .create_handshake_frame()
+            .encode_into(&mut expected)
+            .unwrap();
+        Frame::StatusRequest.encode_into(&mut expected).unwrap();
+        let mut received = vec![0; 256];
+        let n = server.read(&mut received).await.unwrap();
+        assert_eq!(received[..n], expected[..]);
+    }
+
+    #[tokio::test]
+    async fn observe_frames() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = [0; 2];
+            stream.read_exact(&mut request).await.unwrap();
+            stream
+                .write_all(&[0x04, 0x00, 0x02, b'{', b'}'])
+                .await
+                .unwrap();
+        });
+
+        let stream = tokio::net::TcpStream::connect(address).await.unwrap();
+        let mut client = SlpProtocol::new("localhost".to_owned(), address.port(), stream);
+        let observed = Arc::new(Mutex::new(Vec::new()));
+        let observer = Arc::clone(&observed);
+        client.add_observer(move |direction, frame: &Frame, len| {
+            observer
+                .lock()
+                .unwrap()
+                .push((direction, format!("{frame:?}"), len));
+        });
+
+        assert_eq!(client.get_status_raw().await.unwrap(), "{}");
+        server.await.unwrap();
+
+        let observed = observed.lock().unwrap();
+        assert_eq!(
+            *observed,
+            [
+                (PacketDirection::Sent, "StatusRequest".to_owned(), 2),
+                (
+                    PacketDirection::Received,
+                    r#"StatusResponse { json: "{}" }"#.to_owned(),
+                    5
+                ),
+            ]
+        );
     }
 }
@@ -0,0 +1,93 @@
+use std::collections::VecDeque;
+
+/// Which way a captured packet was travelling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PacketDirection {
+    Sent,
+    Received,
+}
+
+/// The raw bytes of a packet sent or received by an [`SlpProtocol`](super::SlpProtocol).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CapturedPacket {
+    pub direction: PacketDirection,
+    /// The packet's bytes, including its length prefix.
+    pub bytes: Vec<u8>,
+}
+
+/// A buffer of recently captured packets, which discards the oldest packets once it holds more than a set number of bytes.
+#[derive(Clone, Debug, Default)]
+pub struct PacketCapture {
+    packets: VecDeque<CapturedPacket>,
+    size: usize,
+    limit: usize,
+}
+
+impl PacketCapture {
+    /// Creates a buffer that holds at most `limit` bytes of packet data.
+    pub fn new(limit: usize) -> Self {
+        Self {
+            packets: VecDeque::new(),
+            size: 0,
+            limit,
+        }
+    }
+
+    pub fn push(&mut self, direction: PacketDirection, bytes: &[u8]) {
+        // keep the most recent part of packets that don't fit on their own
+        let bytes = &bytes[bytes.len().saturating_sub(self.limit)..];
+        if bytes.is_empty() {
+            return;
+        }
+        self.size += bytes.len();
+        self.packets.push_back(CapturedPacket {
+            direction,
+            bytes: bytes.to_vec(),
+        });
+        while self.size > self.limit {
+            let Some(packet) = self.packets.pop_front() else {
+                break;
+            };
+            self.size -= packet.bytes.len();
+        }
+    }
+
+    /// Returns the captured packets, oldest first.
+    pub fn packets(&self) -> impl Iterator<Item = &CapturedPacket> {
+        self.packets.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.packets.clear();
+        self.size = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capture_is_bounded() {
+        let mut capture = PacketCapture::new(8);
+        capture.push(PacketDirection::Sent, &[1, 2, 3, 4]);
+        capture.push(PacketDirection::Received, &[5, 6, 7, 8]);
+        capture.push(PacketDirection::Sent, &[9, 10]);
+
+        let packets: Vec<_> = capture.packets().collect();
+        assert_eq!(packets.len(), 2);
+        assert_eq!(packets[0].bytes, [5, 6, 7, 8]);
+        assert_eq!(packets[1].bytes, [9, 10]);
+
+        capture.push(PacketDirection::Received, &[0; 16]);
+        assert_eq!(capture.packets().next().unwrap().bytes.len(), 8);
+    }
+
+    #[test]
+    fn zero_limit_stores_nothing() {
+        let mut capture = PacketCapture::new(0);
+        capture.push(PacketDirection::Sent, &[1, 2, 3]);
+        capture.push(PacketDirection::Received, &[]);
+        assert_eq!(capture.packets().count(), 0);
+    }
+}
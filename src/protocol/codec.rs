@@ -0,0 +1,94 @@
+use super::{split_packet, Frame, ProtocolError, ServerState};
+use crate::log::debug;
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// Frames a byte stream as Server List Ping packets, for use with [`Framed`](tokio_util::codec::Framed).
+///
+/// This does the same framing as [`SlpProtocol`](super::SlpProtocol), for connections that Elytra Ping didn't
+/// open, such as in a proxy. By default, frames sent by a server are decoded.
+#[derive(Debug, Clone, Default)]
+pub struct SlpCodec {
+    server_state: Option<ServerState>,
+    /// The length prefix and body length of the packet at the start of the buffer, once they've been decoded.
+    pending_len: Option<(usize, usize)>,
+}
+
+impl SlpCodec {
+    /// Creates a codec that decodes frames sent by a server.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a codec that decodes frames sent by a client, starting in the given state.
+    pub fn server(state: ServerState) -> Self {
+        Self {
+            server_state: Some(state),
+            ..Self::default()
+        }
+    }
+
+    /// Switches between which type of frame to decode. Set to None to decode frames sent by a server.
+    pub fn set_server_state(&mut self, server_state: Option<ServerState>) {
+        self.server_state = server_state;
+    }
+}
+
+impl Decoder for SlpCodec {
+    type Item = Frame;
+    type Error = ProtocolError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, ProtocolError> {
+        let Some((packet, header_len)) = split_packet(src, &mut self.pending_len)? else {
+            return Ok(None);
+        };
+        let frame = Frame::parse_body(packet.slice(header_len..), self.server_state)?;
+        debug!(frame_id = frame.id(), bytes = packet.len(), "Decoded frame");
+        Ok(Some(frame))
+    }
+}
+
+impl Encoder<Frame> for SlpCodec {
+    type Error = ProtocolError;
+
+    fn encode(&mut self, frame: Frame, dst: &mut BytesMut) -> Result<(), ProtocolError> {
+        frame.encode_into(dst)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut client = SlpCodec::new();
+        let mut server = SlpCodec::server(ServerState::Status);
+
+        let mut buf = BytesMut::new();
+        client
+            .encode(Frame::PingRequest { payload: 7 }, &mut buf)
+            .unwrap();
+        client.encode(Frame::StatusRequest, &mut buf).unwrap();
+
+        // a partial packet isn't decoded
+        let mut partial = buf.split_to(3);
+        assert!(server.decode(&mut partial).unwrap().is_none());
+        partial.unsplit(buf);
+        assert_eq!(
+            server.decode(&mut partial).unwrap(),
+            Some(Frame::PingRequest { payload: 7 })
+        );
+        assert_eq!(
+            server.decode(&mut partial).unwrap(),
+            Some(Frame::StatusRequest)
+        );
+        assert!(server.decode(&mut partial).unwrap().is_none());
+
+        let mut buf = BytesMut::new();
+        let response = Frame::StatusResponse { json: "{}".into() };
+        server.encode(response.clone(), &mut buf).unwrap();
+        assert_eq!(client.decode(&mut buf).unwrap(), Some(response));
+    }
+}
@@ -1,11 +1,14 @@
 use std::io::Cursor;
 
-use bytes::Buf;
-use mc_varint::{VarInt, VarIntRead};
+use crate::log::trace;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
 use snafu::{Backtrace, OptionExt, Snafu};
-use tracing::trace;
 
-use crate::mc_string::{decode_mc_string, McStringError};
+use crate::mc_string::{
+    check_length, decode_mc_str, BytesStr, McStringError, MAX_ADDRESS_LEN, MAX_STATUS_RESPONSE_LEN,
+    MAX_STRING_LEN,
+};
+use crate::varint::{var_int_len, VarInt, VarIntBuf, VarIntBufMut, VarIntError};
 
 #[derive(Snafu, Debug)]
 pub enum FrameError {
@@ -21,6 +24,12 @@ pub enum FrameError {
     InvalidLength { backtrace: Backtrace },
     /// Received a frame with an invalid id.
     InvalidFrameId { id: i32, backtrace: Backtrace },
+    /// Received a malformed VarInt.
+    #[snafu(display("Failed to decode VarInt: {source}"), context(false))]
+    VarIntDecodeFailed {
+        #[snafu(backtrace)]
+        source: VarIntError,
+    },
     /// Failed to decode string.
     #[snafu(display("Failed to decode string: {source}"), context(false))]
     StringDecodeFailed {
@@ -29,19 +38,19 @@ pub enum FrameError {
     },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[non_exhaustive]
 pub enum Frame {
     Handshake {
         protocol: VarInt,
-        address: String,
+        address: BytesStr,
         port: u16,
         // should be 1 for status
         state: VarInt,
     },
     StatusRequest,
     StatusResponse {
-        json: String,
+        json: BytesStr,
     },
     PingRequest {
         payload: i64,
@@ -53,10 +62,6 @@ pub enum Frame {
 
 /// Controls what packets a server can receive
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-#[deprecated(
-    since = "5.1.0",
-    note = "Elytra Ping as a SLP server is untested and not supported"
-)]
 pub enum ServerState {
     /// Waiting for the Handshake packet
     Handshake,
@@ -71,17 +76,36 @@ impl Frame {
     pub const STATUS_RESPONSE_ID: i32 = 0x00;
     pub const PING_REQUEST_ID: i32 = 0x01;
     pub const PING_RESPONSE_ID: i32 = 0x01;
+    /// The longest packet body that will be received, the same limit as the vanilla client and server.
+    ///
+    /// The length prefix is sent by the peer, so larger lengths are rejected before anything is buffered.
+    pub const MAX_PACKET_LEN: usize = (1 << 21) - 1;
+
+    /// Creates a status response containing `info`, such as for a server to send.
+    ///
+    /// Fields that aren't set are left out of the JSON, and the favicon is sent as it is.
+    #[cfg(feature = "java_parse")]
+    pub fn status_response_from(info: &crate::JavaServerInfo) -> Frame {
+        let json = serde_json::to_string(info).expect("a status can always be serialized");
+        Frame::StatusResponse { json: json.into() }
+    }
+
+    /// Returns the packet id the frame is sent with.
+    pub fn id(&self) -> i32 {
+        match self {
+            Frame::Handshake { .. } => Self::HANDSHAKE_ID,
+            Frame::StatusRequest => Self::STATUS_REQUEST_ID,
+            Frame::StatusResponse { .. } => Self::STATUS_RESPONSE_ID,
+            Frame::PingRequest { .. } => Self::PING_REQUEST_ID,
+            Frame::PingResponse { .. } => Self::PING_RESPONSE_ID,
+        }
+    }
 
     /// Checks if an entire message can be decoded from `buf`, advancing the cursor past the header
     pub fn check(buf: &mut Cursor<&[u8]>) -> Result<(), FrameError> {
         let available_data = buf.get_ref().len();
 
-        // the varint at the beginning contains the size of the rest of the frame
-        let remaining_data_len: usize =
-            i32::from(buf.read_var_int().ok().context(IncompleteSnafu)?)
-                .try_into()
-                .ok()
-                .context(InvalidLengthSnafu)?;
+        let remaining_data_len = Self::read_length(buf)?;
         let header_len = buf.position() as usize;
         let total_len = header_len + remaining_data_len;
 
@@ -89,14 +113,134 @@ impl Frame {
         let is_valid = available_data >= total_len;
 
         if is_valid {
-            trace!("Valid frame, packet size: {total_len}, header size: {header_len}, body size: {remaining_data_len}, downloaded: {available_data}");
+            trace!(
+                packet_len = total_len,
+                header_len,
+                body_len = remaining_data_len,
+                downloaded = available_data,
+                "Valid frame"
+            );
             Ok(())
         } else {
-            trace!("Invalid frame, packet size: {total_len}, downloaded: {available_data}");
+            trace!(
+                packet_len = total_len,
+                downloaded = available_data,
+                "Incomplete frame"
+            );
             IncompleteSnafu.fail()
         }
     }
 
+    /// Reads the varint at the beginning of a frame, which contains the size of the rest of the frame.
+    ///
+    /// Lengths longer than [`Frame::MAX_PACKET_LEN`] are invalid.
+    pub(super) fn read_length(buf: &mut Cursor<&[u8]>) -> Result<usize, FrameError> {
+        let len = match buf.get_var_int() {
+            Ok(len) => len,
+            Err(VarIntError::Incomplete { .. }) => return IncompleteSnafu.fail(),
+            Err(VarIntError::TooLong { .. }) => return InvalidLengthSnafu.fail(),
+        };
+        usize::try_from(len)
+            .ok()
+            .filter(|&len| len <= Self::MAX_PACKET_LEN)
+            .context(InvalidLengthSnafu)
+    }
+
+    /// Parses a single length-prefixed frame from the start of `bytes`, without needing a connection.
+    ///
+    /// Unlike [`Frame::parse`], the body is never read past the frame's length. Malformed input results in an
+    /// error rather than a panic, so this is suitable as a fuzzing target.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The frame, including its length prefix
+    /// * `server_state` - Switches between which type of frame to accept. Set to None to accept frames for the client.
+    pub fn parse_bytes(
+        bytes: &[u8],
+        server_state: Option<ServerState>,
+    ) -> Result<Frame, FrameError> {
+        let mut cursor = Cursor::new(bytes);
+        let len = Self::read_length(&mut cursor)?;
+        let body = bytes[cursor.position() as usize..]
+            .get(..len)
+            .context(IncompleteSnafu)?;
+        Self::parse_body(Bytes::copy_from_slice(body), server_state)
+    }
+
+    /// Parses the body of a frame that has been split off the buffer it was received in.
+    ///
+    /// Strings in the frame share `body` instead of being copied out of it.
+    pub fn parse_body(body: Bytes, server_state: Option<ServerState>) -> Result<Frame, FrameError> {
+        Self::parse_shared(Some(&body), &mut Cursor::new(&body[..]), server_state)
+    }
+
+    /// Encodes the frame as a length-prefixed packet, appending it to `buf`.
+    ///
+    /// Nothing is written if a string in the frame is too long. Reusing `buf` for many frames avoids allocating
+    /// for each one.
+    pub fn encode_into(&self, buf: &mut BytesMut) -> Result<(), McStringError> {
+        let body_len = self.body_len()?;
+        // strings are limited, so the body is far shorter than i32::MAX
+        let body_len_prefix = body_len as i32;
+        buf.reserve(var_int_len(body_len_prefix) + body_len);
+        buf.put_var_int(body_len_prefix);
+
+        match self {
+            Frame::Handshake {
+                protocol,
+                address,
+                port,
+                state,
+            } => {
+                buf.put_var_int(Self::HANDSHAKE_ID);
+                buf.put_var_int(protocol.0);
+                put_string(buf, address);
+                buf.put_u16(*port);
+                buf.put_var_int(state.0);
+            }
+            Frame::StatusRequest => buf.put_var_int(Self::STATUS_REQUEST_ID),
+            Frame::StatusResponse { json } => {
+                buf.put_var_int(Self::STATUS_RESPONSE_ID);
+                put_string(buf, json);
+            }
+            Frame::PingRequest { payload } => {
+                buf.put_var_int(Self::PING_REQUEST_ID);
+                buf.put_i64(*payload);
+            }
+            Frame::PingResponse { payload } => {
+                buf.put_var_int(Self::PING_RESPONSE_ID);
+                buf.put_i64(*payload);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the length of the frame's body once encoded, checking that its strings aren't too long.
+    fn body_len(&self) -> Result<usize, McStringError> {
+        Ok(match self {
+            Frame::Handshake {
+                protocol,
+                address,
+                state,
+                ..
+            } => {
+                check_length(address, MAX_ADDRESS_LEN)?;
+                var_int_len(Self::HANDSHAKE_ID)
+                    + var_int_len(protocol.0)
+                    + string_len(address)
+                    + 2
+                    + var_int_len(state.0)
+            }
+            Frame::StatusRequest => var_int_len(Self::STATUS_REQUEST_ID),
+            Frame::StatusResponse { json } => {
+                check_length(json, MAX_STRING_LEN)?;
+                var_int_len(Self::STATUS_RESPONSE_ID) + string_len(json)
+            }
+            Frame::PingRequest { .. } => var_int_len(Self::PING_REQUEST_ID) + 8,
+            Frame::PingResponse { .. } => var_int_len(Self::PING_RESPONSE_ID) + 8,
+        })
+    }
+
     /// Parse the body of a frame, after the message has already been validated with `check`.
     ///
     /// # Arguments
@@ -107,15 +251,26 @@ impl Frame {
         cursor: &mut Cursor<&[u8]>,
         server_state: Option<ServerState>,
     ) -> Result<Frame, FrameError> {
-        let id = i32::from(cursor.read_var_int()?);
+        // the cursor may hold more than this frame, so only its strings are copied
+        Self::parse_shared(None, cursor, server_state)
+    }
+
+    /// Parses a frame from `cursor`, which reads from `body` if strings can share it instead of being copied.
+    fn parse_shared(
+        body: Option<&Bytes>,
+        cursor: &mut Cursor<&[u8]>,
+        server_state: Option<ServerState>,
+    ) -> Result<Frame, FrameError> {
+        let id = var_int(cursor)?;
 
         match server_state {
             Some(ServerState::Handshake) => {
                 if id == Self::HANDSHAKE_ID {
-                    let protocol = cursor.read_var_int()?;
-                    let address = decode_mc_string(cursor)?;
+                    let protocol = VarInt(var_int(cursor)?);
+                    let address = string(body, cursor, MAX_ADDRESS_LEN)?;
+                    ensure_remaining(cursor, 2)?;
                     let port = cursor.get_u16();
-                    let state = cursor.read_var_int()?;
+                    let state = VarInt(var_int(cursor)?);
                     return Ok(Frame::Handshake {
                         protocol,
                         address,
@@ -131,6 +286,7 @@ impl Frame {
                     }
                     Self::PING_REQUEST_ID => {
                         // ping request a contains (usually) meaningless Java long
+                        ensure_remaining(cursor, 8)?;
                         let payload = cursor.get_i64();
                         return Ok(Frame::PingRequest { payload });
                     }
@@ -140,11 +296,12 @@ impl Frame {
             None => {
                 match id {
                     Self::STATUS_RESPONSE_ID => {
-                        let json = decode_mc_string(cursor)?;
+                        let json = string(body, cursor, MAX_STATUS_RESPONSE_LEN)?;
                         return Ok(Frame::StatusResponse { json });
                     }
                     Self::PING_RESPONSE_ID => {
                         // ping response contains the same Java long as the request
+                        ensure_remaining(cursor, 8)?;
                         let payload = cursor.get_i64();
                         return Ok(Frame::PingResponse { payload });
                     }
@@ -156,3 +313,177 @@ impl Frame {
         InvalidFrameIdSnafu { id }.fail()
     }
 }
+
+/// Frames are only parsed once they've been received in full, so a VarInt that doesn't fit is malformed.
+fn var_int(cursor: &mut Cursor<&[u8]>) -> Result<i32, FrameError> {
+    match cursor.get_var_int() {
+        Err(VarIntError::Incomplete { .. }) => InvalidLengthSnafu.fail(),
+        result => Ok(result?),
+    }
+}
+
+/// Decodes a string from `cursor` as a slice of `body`, or a copy if there's no `body` to share.
+///
+/// Frames are only parsed once they've been received in full, so a string that doesn't fit is malformed.
+fn string(
+    body: Option<&Bytes>,
+    cursor: &mut Cursor<&[u8]>,
+    max_len: usize,
+) -> Result<BytesStr, FrameError> {
+    match decode_mc_str(cursor, max_len) {
+        Ok(string) => Ok(match body {
+            Some(body) => BytesStr::slice_ref(body, string),
+            None => BytesStr::from(String::from(string)),
+        }),
+        Err(McStringError::Incomplete { .. }) => InvalidLengthSnafu.fail(),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Returns how many bytes a string takes up, including its length prefix.
+fn string_len(string: &str) -> usize {
+    var_int_len(string.len() as i32) + string.len()
+}
+
+/// Writes a string whose length has already been checked.
+fn put_string(buf: &mut BytesMut, string: &str) {
+    buf.put_var_int(string.len() as i32);
+    buf.put_slice(string.as_bytes());
+}
+
+fn ensure_remaining(cursor: &Cursor<&[u8]>, len: usize) -> Result<(), FrameError> {
+    if cursor.remaining() < len {
+        return InvalidLengthSnafu.fail();
+    }
+    Ok(())
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Frame {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.int_in_range(0..=4)? {
+            0 => Frame::Handshake {
+                protocol: VarInt::from(i32::arbitrary(u)?),
+                address: String::arbitrary(u)?.into(),
+                port: u.arbitrary()?,
+                state: VarInt::from(i32::arbitrary(u)?),
+            },
+            1 => Frame::StatusRequest,
+            2 => Frame::StatusResponse {
+                json: String::arbitrary(u)?.into(),
+            },
+            3 => Frame::PingRequest {
+                payload: u.arbitrary()?,
+            },
+            _ => Frame::PingResponse {
+                payload: u.arbitrary()?,
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "java_parse")]
+    #[test]
+    fn status_response_from() {
+        use crate::parse::{ServerPlayers, ServerVersion};
+        use crate::JavaServerInfo;
+
+        let info = JavaServerInfo::new("A Minecraft Server")
+            .with_version(ServerVersion::new("1.21", 767))
+            .with_players(ServerPlayers::new(20, 0))
+            .with_favicon("data:image/png;base64,iVBORw0KGgo=");
+        let Frame::StatusResponse { json } = Frame::status_response_from(&info) else {
+            panic!("expected a status response");
+        };
+        assert_eq!(
+            &*json,
+            r#"{"version":{"name":"1.21","protocol":767},"players":{"max":20,"online":0},"description":"A Minecraft Server","favicon":"data:image/png;base64,iVBORw0KGgo="}"#
+        );
+        assert_eq!(json.parse::<JavaServerInfo>().unwrap(), info);
+    }
+
+    #[test]
+    fn parse_body_shares_buffer() {
+        let body = Bytes::from_static(&[0x00, 0x02, b'{', b'}']);
+        let Frame::StatusResponse { json } = Frame::parse_body(body.clone(), None).unwrap() else {
+            panic!("expected a status response");
+        };
+        assert_eq!(json, "{}");
+        assert_eq!(json.as_ptr(), body[2..].as_ptr());
+    }
+
+    #[test]
+    fn parse_stops_at_frame_end() {
+        let bytes = [0x00, 0x02, b'{', b'}', 0x01, 0, 0, 0, 0, 0, 0, 0, 42];
+        let mut cursor = Cursor::new(&bytes[..]);
+        let frame = Frame::parse(&mut cursor, None).unwrap();
+        assert!(matches!(frame, Frame::StatusResponse { json } if json == "{}"));
+        assert_eq!(cursor.position(), 4);
+        let frame = Frame::parse(&mut cursor, None).unwrap();
+        assert!(matches!(frame, Frame::PingResponse { payload: 42 }));
+    }
+
+    #[test]
+    fn parse_bytes() {
+        let frame = Frame::parse_bytes(&[0x09, 0x01, 0, 0, 0, 0, 0, 0, 0, 42], None).unwrap();
+        assert!(matches!(frame, Frame::PingResponse { payload: 42 }));
+
+        // the frame is too short to contain its payload, and must not be read past its length
+        let result = Frame::parse_bytes(&[0x02, 0x01, 0, 0, 0, 0, 0, 0, 0, 42], None);
+        assert!(matches!(result, Err(FrameError::InvalidLength { .. })));
+        let result = Frame::parse_bytes(&[0x09, 0x01, 0], None);
+        assert!(matches!(result, Err(FrameError::Incomplete { .. })));
+        // the status response's string is longer than the frame
+        let result = Frame::parse_bytes(&[0x03, 0x00, 0x05, b'{', b'}'], None);
+        assert!(matches!(result, Err(FrameError::InvalidLength { .. })));
+        // the length prefix is longer than any packet is allowed to be
+        let result = Frame::parse_bytes(&[0xff, 0xff, 0xff, 0xff, 0x07], None);
+        assert!(matches!(result, Err(FrameError::InvalidLength { .. })));
+        let result = Frame::parse_bytes(&[0x80, 0x80, 0x80, 0x01], None);
+        assert!(matches!(result, Err(FrameError::InvalidLength { .. })));
+    }
+
+    #[test]
+    fn parse_long_status_response() {
+        // longer than a status response can be encoded with, such as from a server with many mods
+        let json = format!(r#"{{"description":"{}"}}"#, "a".repeat(MAX_STRING_LEN));
+        let mut body = vec![0x00];
+        body.put_var_int(json.len() as i32);
+        body.extend_from_slice(json.as_bytes());
+        let frame = Frame::parse_body(body.into(), None).unwrap();
+        assert!(matches!(frame, Frame::StatusResponse { json: received } if *received == json));
+
+        let response = Frame::StatusResponse { json: json.into() };
+        assert!(response.encode_into(&mut BytesMut::new()).is_err());
+    }
+
+    #[test]
+    fn encode_into() {
+        let mut buf = BytesMut::new();
+        Frame::StatusResponse { json: "{}".into() }
+            .encode_into(&mut buf)
+            .unwrap();
+        Frame::PingResponse { payload: 42 }
+            .encode_into(&mut buf)
+            .unwrap();
+        assert_eq!(
+            &buf[..],
+            [0x04, 0x00, 0x02, b'{', b'}', 0x09, 0x01, 0, 0, 0, 0, 0, 0, 0, 42]
+        );
+
+        // a failed frame doesn't leave a partial packet behind
+        let address = "a".repeat(MAX_ADDRESS_LEN + 1);
+        let handshake = Frame::Handshake {
+            protocol: VarInt(767),
+            address: address.into(),
+            port: 25565,
+            state: VarInt(1),
+        };
+        assert!(handshake.encode_into(&mut buf).is_err());
+        assert_eq!(buf.len(), 15);
+    }
+}
@@ -0,0 +1,89 @@
+use super::{FrameError, ProtocolError, ProtocolState, SlpProtocol};
+use crate::mc_string::{
+    decode_mc_string_with_limit, encode_mc_string_with_limit, MAX_LOGIN_DISCONNECT_LEN,
+    MAX_USERNAME_LEN,
+};
+use snafu::OptionExt;
+use std::io::Cursor;
+use tokio::io::{AsyncRead, AsyncWrite};
+
+const LOGIN_START_ID: i32 = 0x00;
+const DISCONNECT_ID: i32 = 0x00;
+const ENCRYPTION_REQUEST_ID: i32 = 0x01;
+const LOGIN_SUCCESS_ID: i32 = 0x02;
+const SET_COMPRESSION_ID: i32 = 0x03;
+
+/// How a server responded to the start of a login, which reveals whether it authenticates players.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum LoginProbe {
+    /// The server asked to enable encryption, so players must be authenticated with Mojang (online mode).
+    OnlineMode,
+    /// The server accepted the player without authenticating them (offline mode).
+    OfflineMode,
+    /// The server disconnected the player before authenticating them, such as because the protocol version
+    /// isn't supported. The reason is a JSON text component.
+    Kicked { reason: String },
+    /// The server sent a packet that doesn't reveal whether it authenticates players, such as a plugin request
+    /// sent by a proxy.
+    Unknown { packet_id: i32 },
+}
+
+impl<S: AsyncRead + AsyncWrite + Unpin> SlpProtocol<S> {
+    /// Starts logging in as `username`, and reports whether the server authenticates players.
+    ///
+    /// This replaces [`SlpProtocol::handshake`], and the login is abandoned after the server's first response,
+    /// so the connection should be closed afterwards. `protocol` should be the protocol version reported in the
+    /// server's status, since servers kick clients with other versions.
+    pub async fn probe_login(
+        &mut self,
+        protocol: i32,
+        username: &str,
+    ) -> Result<LoginProbe, ProtocolError> {
+        self.write_frame(self.create_handshake_frame_with(protocol, ProtocolState::Login))
+            .await?;
+        self.write_packet(LOGIN_START_ID, &login_start_body(protocol, username)?)
+            .await?;
+
+        let (id, body) = self
+            .read_packet()
+            .await?
+            .context(super::ConnectionClosedSnafu)?;
+        Ok(match id {
+            DISCONNECT_ID => LoginProbe::Kicked {
+                reason: decode_mc_string_with_limit(
+                    &mut Cursor::new(&body),
+                    MAX_LOGIN_DISCONNECT_LEN,
+                )
+                .map_err(FrameError::from)?,
+            },
+            ENCRYPTION_REQUEST_ID => LoginProbe::OnlineMode,
+            // online mode servers enable encryption before compression
+            LOGIN_SUCCESS_ID | SET_COMPRESSION_ID => LoginProbe::OfflineMode,
+            packet_id => LoginProbe::Unknown { packet_id },
+        })
+    }
+}
+
+/// Encodes the Login Start packet, whose fields have changed across versions.
+fn login_start_body(protocol: i32, username: &str) -> Result<Vec<u8>, ProtocolError> {
+    // 1.19 added signature data, 1.19.1 added the UUID, 1.19.3 removed signature data, 1.20.2 made the UUID required
+    const V1_19: i32 = 759;
+    const V1_19_1: i32 = 760;
+    const V1_20_2: i32 = 764;
+
+    let mut body = encode_mc_string_with_limit(username, MAX_USERNAME_LEN)?;
+    if protocol >= V1_20_2 {
+        body.extend_from_slice(&[0; 16]);
+    } else if protocol > V1_19_1 {
+        // no UUID
+        body.push(0);
+    } else if protocol == V1_19_1 {
+        // no signature data or UUID
+        body.extend_from_slice(&[0, 0]);
+    } else if protocol == V1_19 {
+        // no signature data
+        body.push(0);
+    }
+    Ok(body)
+}
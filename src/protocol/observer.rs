@@ -0,0 +1,41 @@
+use super::{Frame, PacketDirection};
+use std::fmt;
+
+/// Receives every frame sent or received by an [`SlpProtocol`](super::SlpProtocol).
+///
+/// Observers are useful for logging, collecting metrics, or asserting that a server conforms to the protocol.
+/// Any closure taking the same arguments as [`FrameObserver::observe`] is an observer.
+pub trait FrameObserver: Send {
+    /// Called with each frame and the length of its packet in bytes, including the length prefix.
+    fn observe(&mut self, direction: PacketDirection, frame: &Frame, len: usize);
+}
+
+impl<F> FrameObserver for F
+where
+    F: FnMut(PacketDirection, &Frame, usize) + Send,
+{
+    fn observe(&mut self, direction: PacketDirection, frame: &Frame, len: usize) {
+        self(direction, frame, len)
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct Observers(Vec<Box<dyn FrameObserver>>);
+
+impl Observers {
+    pub fn push(&mut self, observer: Box<dyn FrameObserver>) {
+        self.0.push(observer);
+    }
+
+    pub fn notify(&mut self, direction: PacketDirection, frame: &Frame, len: usize) {
+        for observer in &mut self.0 {
+            observer.observe(direction, frame, len);
+        }
+    }
+}
+
+impl fmt::Debug for Observers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observers({})", self.0.len())
+    }
+}
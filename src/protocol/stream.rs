@@ -0,0 +1,178 @@
+use super::{Frame, PacketDirection, ProtocolError, SlpProtocol};
+use crate::log::{error, info};
+use core::pin::Pin;
+use core::task::{ready, Context, Poll};
+use futures_core::Stream;
+use futures_sink::Sink;
+use snafu::{Backtrace, GenerateImplicitData};
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_util::io::{poll_read_buf, poll_write_buf};
+
+/// Once this many bytes are waiting to be sent, [`Sink::poll_ready`] sends them before accepting more frames.
+const BACKPRESSURE_BOUNDARY: usize = 8 * 1024;
+
+/// Receives frames sent by the server, like [`SlpProtocol::read_frame`]. The stream ends when the server
+/// closes the connection cleanly.
+impl<S: AsyncRead + AsyncWrite + Unpin> Stream for SlpProtocol<S> {
+    type Item = Result<Frame, ProtocolError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        loop {
+            match this.parse_frame(None) {
+                Ok(Some(frame)) => return Poll::Ready(Some(Ok(frame))),
+                Ok(None) => {}
+                Err(error) => return Poll::Ready(Some(Err(error))),
+            }
+
+            let bytes_read = match ready!(poll_read_buf(
+                Pin::new(&mut this.stream),
+                cx,
+                &mut this.buffer
+            )) {
+                Ok(bytes_read) => bytes_read,
+                Err(error) => return Poll::Ready(Some(Err(error.into()))),
+            };
+            #[cfg(feature = "metrics")]
+            this.record_received(bytes_read);
+            if bytes_read == 0 {
+                if this.buffer.is_empty() {
+                    info!("Connection closed cleanly");
+                    return Poll::Ready(None);
+                }
+                error!("Connection closed unexpectedly");
+                if let Some(capture) = &mut this.capture {
+                    capture.push(PacketDirection::Received, &this.buffer);
+                }
+                return Poll::Ready(Some(Err(ProtocolError::ConnectionClosed {
+                    backtrace: Backtrace::generate(),
+                })));
+            }
+        }
+    }
+}
+
+/// Sends frames to the server. Frames are queued like [`SlpProtocol::queue_frame`] and only written once the
+/// sink is flushed, or once enough of them are queued.
+impl<S: AsyncRead + AsyncWrite + Unpin> Sink<Frame> for SlpProtocol<S> {
+    type Error = ProtocolError;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.write_buffer.len() >= BACKPRESSURE_BOUNDARY {
+            self.poll_flush(cx)
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, frame: Frame) -> Result<(), Self::Error> {
+        self.get_mut().queue_frame(frame)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        while !this.write_buffer.is_empty() {
+            match ready!(poll_write_buf(
+                Pin::new(&mut this.stream),
+                cx,
+                &mut this.write_buffer
+            )) {
+                Ok(0) => {
+                    this.write_buffer.clear();
+                    return Poll::Ready(Err(io::Error::from(io::ErrorKind::WriteZero).into()));
+                }
+                #[allow(unused_variables)]
+                Ok(bytes_written) => {
+                    #[cfg(feature = "metrics")]
+                    this.record_sent(bytes_written);
+                }
+                Err(error) => {
+                    // don't send the rest of a packet that failed partway along with the next one
+                    this.write_buffer.clear();
+                    return Poll::Ready(Err(error.into()));
+                }
+            }
+        }
+        Poll::Ready(ready!(Pin::new(&mut this.stream).poll_flush(cx)).map_err(Into::into))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        Poll::Ready(ready!(Pin::new(&mut self.stream).poll_shutdown(cx)).map_err(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::future::poll_fn;
+    use tokio::io::AsyncWriteExt;
+
+    #[tokio::test]
+    async fn sink() {
+        let (client, server) = tokio::io::duplex(64);
+        let mut client = SlpProtocol::new("localhost".to_owned(), 25565, client);
+        let mut server = SlpProtocol::new("localhost".to_owned(), 25565, server);
+
+        let mut sink = Pin::new(&mut client);
+        for payload in [1, 2] {
+            poll_fn(|cx| sink.as_mut().poll_ready(cx)).await.unwrap();
+            sink.as_mut()
+                .start_send(Frame::PingRequest { payload })
+                .unwrap();
+        }
+        poll_fn(|cx| sink.as_mut().poll_close(cx)).await.unwrap();
+
+        for expected in [1, 2] {
+            let frame = server
+                .read_frame(Some(crate::protocol::ServerState::Status))
+                .await
+                .unwrap();
+            assert_eq!(frame, Some(Frame::PingRequest { payload: expected }));
+        }
+        assert!(server.read_frame(None).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn stream() {
+        let (client, server) = tokio::io::duplex(64);
+        let mut client = SlpProtocol::new("localhost".to_owned(), 25565, client);
+        let mut server = SlpProtocol::new("localhost".to_owned(), 25565, server);
+        server
+            .write_frame(Frame::StatusResponse { json: "{}".into() })
+            .await
+            .unwrap();
+        server
+            .write_frame(Frame::PingResponse { payload: 3 })
+            .await
+            .unwrap();
+        drop(server);
+
+        let mut received = Vec::new();
+        while let Some(frame) = poll_fn(|cx| Pin::new(&mut client).poll_next(cx)).await {
+            received.push(frame.unwrap());
+        }
+        assert!(matches!(
+            received[..],
+            [
+                Frame::StatusResponse { .. },
+                Frame::PingResponse { payload: 3 }
+            ]
+        ));
+    }
+
+    #[tokio::test]
+    async fn truncated_stream() {
+        let (client, mut server) = tokio::io::duplex(64);
+        let mut client = SlpProtocol::new("localhost".to_owned(), 25565, client);
+        server.write_all(&[10, 1]).await.unwrap();
+        drop(server);
+
+        let error = poll_fn(|cx| Pin::new(&mut client).poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap_err();
+        assert!(matches!(error, ProtocolError::ConnectionClosed { .. }));
+    }
+}
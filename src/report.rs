@@ -0,0 +1,121 @@
+#[cfg(feature = "bedrock")]
+use crate::bedrock::BedrockServerInfo;
+use crate::JavaServerInfo;
+use core::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// The result of a successful ping, tagged with the edition of the server that was pinged.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "edition", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum PingReport {
+    Java {
+        latency_ms: u64,
+        /// How long opening the TCP connection took, if it was measured.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        connect_ms: Option<u64>,
+        info: JavaServerInfo,
+    },
+    #[cfg(feature = "bedrock")]
+    Bedrock {
+        latency_ms: u64,
+        info: BedrockServerInfo,
+    },
+}
+
+impl PingReport {
+    /// Returns the latency of the ping.
+    pub fn latency(&self) -> Duration {
+        match self {
+            PingReport::Java { latency_ms, .. } => Duration::from_millis(*latency_ms),
+            #[cfg(feature = "bedrock")]
+            PingReport::Bedrock { latency_ms, .. } => Duration::from_millis(*latency_ms),
+        }
+    }
+
+    /// Returns how long opening the TCP connection took, if it was measured.
+    pub fn connect_latency(&self) -> Option<Duration> {
+        match self {
+            PingReport::Java { connect_ms, .. } => connect_ms.map(Duration::from_millis),
+            #[cfg(feature = "bedrock")]
+            PingReport::Bedrock { .. } => None,
+        }
+    }
+}
+
+fn duration_millis(duration: Duration) -> u64 {
+    duration.as_millis().try_into().unwrap_or(u64::MAX)
+}
+
+impl From<(JavaServerInfo, Duration)> for PingReport {
+    fn from((info, latency): (JavaServerInfo, Duration)) -> Self {
+        PingReport::Java {
+            latency_ms: duration_millis(latency),
+            connect_ms: None,
+            info,
+        }
+    }
+}
+
+#[cfg(feature = "simple")]
+impl From<crate::PingOutcome> for PingReport {
+    fn from(outcome: crate::PingOutcome) -> Self {
+        PingReport::Java {
+            latency_ms: duration_millis(outcome.latency),
+            connect_ms: Some(duration_millis(outcome.connect_latency())),
+            info: outcome.info,
+        }
+    }
+}
+
+#[cfg(feature = "bedrock")]
+impl From<(BedrockServerInfo, Duration)> for PingReport {
+    fn from((info, latency): (BedrockServerInfo, Duration)) -> Self {
+        PingReport::Bedrock {
+            latency_ms: duration_millis(latency),
+            info,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize_java_report() {
+        let info: JavaServerInfo = r#"{ "description": "A Minecraft Server" }"#.parse().unwrap();
+        let report = PingReport::from((info, Duration::from_millis(42)));
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["edition"], "java");
+        assert_eq!(json["latency_ms"], 42);
+        assert_eq!(json["info"]["description"], "A Minecraft Server");
+        assert!(json.get("connect_ms").is_none());
+        assert_eq!(serde_json::from_value::<PingReport>(json).unwrap(), report);
+    }
+
+    #[test]
+    fn serialize_connect_latency() {
+        let info: JavaServerInfo = r#"{ "description": "A Minecraft Server" }"#.parse().unwrap();
+        let report = PingReport::Java {
+            latency_ms: 42,
+            connect_ms: Some(12),
+            info,
+        };
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["connect_ms"], 12);
+        assert_eq!(report.connect_latency(), Some(Duration::from_millis(12)));
+        assert_eq!(serde_json::from_value::<PingReport>(json).unwrap(), report);
+    }
+
+    #[cfg(feature = "bedrock")]
+    #[test]
+    fn serialize_bedrock_report() {
+        let info: BedrockServerInfo = "MCPE;Dedicated Server;390;1.14.60;0;10;13253860892328930865;Bedrock level;Survival;1;19132;19133;"
+            .parse()
+            .unwrap();
+        let report = PingReport::from((info, Duration::from_millis(83)));
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<PingReport>(&json).unwrap(), report);
+    }
+}
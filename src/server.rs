@@ -0,0 +1,387 @@
+//! A Java Edition server that answers Server List Pings with a fixed status, such as to keep a listing up while
+//! the real server is down, or to find out who is scanning an address.
+//!
+//! ```no_run
+//! # async fn example() -> std::io::Result<()> {
+//! use elytra_ping::server::{StatusServer, StatusServerConfig};
+//!
+//! let config = StatusServerConfig::maintenance("§6Down for maintenance, back soon!")
+//!     .with_handshake_observer(|handshake: &_| println!("{handshake}"));
+//! let server = StatusServer::start("0.0.0.0:25565".parse().unwrap(), config).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! With the `bedrock` feature, [`BedrockStatusServer`] does the same for Bedrock Edition pings. A
+//! [`LanAnnouncer`] makes a server show up in Java Edition clients' LAN lists.
+
+use crate::log::{debug, error, info};
+use crate::mc_string::encode_mc_string;
+use crate::net::{TcpListener, TcpStream};
+use crate::parse::{ServerPlayers, ServerVersion, TextComponent};
+use crate::protocol::{Frame, ProtocolError, ProtocolState, ServerState, SlpProtocol};
+use crate::JavaServerInfo;
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+#[cfg(feature = "bedrock")]
+pub use self::bedrock::{
+    BedrockPingObserver, BedrockStatusServer, BedrockStatusServerConfig, IncomingBedrockPing,
+};
+pub use self::lan::{
+    lan_announcement, LanAnnouncer, LanAnnouncerConfig, LAN_MULTICAST_GROUP, LAN_PORT,
+};
+
+#[cfg(feature = "bedrock")]
+mod bedrock;
+mod lan;
+
+/// A handshake received by a [`StatusServer`], as reported to a [`HandshakeObserver`].
+///
+/// Everything but `peer` is claimed by the client, so it may not be true.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct IncomingHandshake {
+    /// The address the connection came from.
+    pub peer: SocketAddr,
+    /// The hostname the client says it connected to, which shows the DNS records pointing at the server.
+    pub hostname: String,
+    /// The port the client says it connected to.
+    pub port: u16,
+    /// The client's protocol version.
+    pub protocol: i32,
+    /// The state the client asked to switch to, or `None` if it sent an unknown one.
+    pub intent: Option<ProtocolState>,
+}
+
+impl fmt::Display for IncomingHandshake {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} connected to {}:{} with protocol {}",
+            self.peer, self.hostname, self.port, self.protocol
+        )?;
+        match self.intent {
+            Some(ProtocolState::Status) => f.write_str(" to get the status"),
+            Some(ProtocolState::Login | ProtocolState::Transfer) => f.write_str(" to log in"),
+            None => f.write_str(" with an unknown intent"),
+        }
+    }
+}
+
+/// Receives every handshake a [`StatusServer`] accepts, before it responds.
+///
+/// Any closure taking an [`&IncomingHandshake`](IncomingHandshake) is an observer.
+pub trait HandshakeObserver: Send + Sync {
+    fn observe(&self, handshake: &IncomingHandshake);
+}
+
+impl<F> HandshakeObserver for F
+where
+    F: Fn(&IncomingHandshake) + Send + Sync,
+{
+    fn observe(&self, handshake: &IncomingHandshake) {
+        self(handshake)
+    }
+}
+
+/// Controls how a [`StatusServer`] responds to clients.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct StatusServerConfig {
+    /// The status sent to clients.
+    pub status: JavaServerInfo,
+    /// Whether to respond to ping requests, so clients can show the latency. Defaults to `true`.
+    pub respond_to_pings: bool,
+    /// How long a client has to finish pinging before the connection is closed. Defaults to 10 seconds.
+    pub timeout: Duration,
+    /// The message shown to players who try to join. If `None`, the connection is closed without a message.
+    pub disconnect_message: Option<TextComponent>,
+    /// The most clients served at once. Further connections wait to be accepted until a client disconnects.
+    /// Defaults to 256.
+    pub max_connections: usize,
+    handshake_observer: Option<Observer>,
+}
+
+impl Default for StatusServerConfig {
+    fn default() -> Self {
+        Self {
+            status: JavaServerInfo::new("A Minecraft Server"),
+            respond_to_pings: true,
+            timeout: Duration::from_secs(10),
+            disconnect_message: None,
+            max_connections: 256,
+            handshake_observer: None,
+        }
+    }
+}
+
+impl StatusServerConfig {
+    /// Creates a config for showing that the server is down for maintenance.
+    ///
+    /// The message is shown as the description and to players who try to join. Instead of the player count, the
+    /// server list shows "Maintenance" in red, since the status has a version no client supports.
+    pub fn maintenance(message: impl Into<TextComponent>) -> Self {
+        let message = message.into();
+        Self {
+            status: JavaServerInfo::new(message.clone())
+                .with_version(ServerVersion::new("Maintenance", -1))
+                .with_players(ServerPlayers::new(0, 0)),
+            disconnect_message: Some(message),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_status(mut self, status: JavaServerInfo) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn with_respond_to_pings(mut self, respond_to_pings: bool) -> Self {
+        self.respond_to_pings = respond_to_pings;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_disconnect_message(mut self, message: impl Into<TextComponent>) -> Self {
+        self.disconnect_message = Some(message.into());
+        self
+    }
+
+    pub fn with_max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Sets the server icon from a PNG image, which should be 64 pixels square.
+    pub fn with_icon(mut self, png: &[u8]) -> Self {
+        self.status.favicon = Some(crate::parse::encode_favicon(png));
+        self
+    }
+
+    /// Registers an observer that will be called with every handshake the server receives.
+    pub fn with_handshake_observer(mut self, observer: impl HandshakeObserver + 'static) -> Self {
+        self.handshake_observer = Some(Observer(Arc::new(observer)));
+        self
+    }
+}
+
+#[derive(Clone)]
+struct Observer(Arc<dyn HandshakeObserver>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("HandshakeObserver")
+    }
+}
+
+/// A server answering Server List Pings in the background, which stops when dropped.
+#[derive(Debug)]
+pub struct StatusServer {
+    address: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl StatusServer {
+    /// Starts a server listening on `address` that responds to every client according to `config`.
+    pub async fn start(address: SocketAddr, config: StatusServerConfig) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(address).await?;
+        let address = listener.local_addr()?;
+        let config = Arc::new(config);
+        info!(%address, "Status server started");
+        let task = tokio::spawn(accept_loop(listener, config));
+        Ok(Self { address, task })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+impl Drop for StatusServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// How long to wait before accepting again after accepting a connection failed, such as when the process is out
+/// of file descriptors.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+async fn accept_loop(listener: TcpListener, config: Arc<StatusServerConfig>) {
+    let connections = Arc::new(Semaphore::new(config.max_connections));
+    loop {
+        let permit = Arc::clone(&connections)
+            .acquire_owned()
+            .await
+            .expect("the semaphore is never closed");
+        let (stream, peer) = match listener.accept().await {
+            Ok(connection) => connection,
+            Err(error) => {
+                error!(%error, "Status server failed to accept a connection");
+                tokio::time::sleep(ACCEPT_BACKOFF).await;
+                continue;
+            }
+        };
+        let config = Arc::clone(&config);
+        tokio::spawn(async move {
+            match tokio::time::timeout(config.timeout, serve(stream, peer, &config)).await {
+                Ok(Err(error)) => debug!(%peer, %error, "Status server connection failed"),
+                Err(_) => debug!(%peer, "Status server connection timed out"),
+                Ok(Ok(())) => {}
+            }
+            drop(permit);
+        });
+    }
+}
+
+/// The id of the packet that kicks a player while logging in.
+const LOGIN_DISCONNECT_ID: i32 = 0x00;
+
+async fn serve(
+    stream: TcpStream,
+    peer: SocketAddr,
+    config: &StatusServerConfig,
+) -> Result<(), ProtocolError> {
+    let mut connection = SlpProtocol::new(String::new(), 0, stream);
+    connection.set_server();
+    let Some(Frame::Handshake {
+        protocol,
+        address,
+        port,
+        state,
+    }) = connection.read_frame(Some(ServerState::Handshake)).await?
+    else {
+        return Ok(());
+    };
+    let handshake = IncomingHandshake {
+        peer,
+        hostname: address.as_str().to_owned(),
+        port,
+        protocol: protocol.into(),
+        intent: ProtocolState::from_id(state.into()),
+    };
+    debug!(%handshake, "Received handshake");
+    if let Some(Observer(observer)) = &config.handshake_observer {
+        observer.observe(&handshake);
+    }
+    match handshake.intent {
+        Some(ProtocolState::Status) => {}
+        Some(ProtocolState::Login | ProtocolState::Transfer) => {
+            let Some(message) = &config.disconnect_message else {
+                return Ok(());
+            };
+            // the client waits for a response to its Login Start packet
+            connection.read_packet().await?;
+            let json = serde_json::to_string(message).expect("a message can always be serialized");
+            return connection
+                .write_packet(LOGIN_DISCONNECT_ID, &encode_mc_string(&json)?)
+                .await;
+        }
+        None => return Ok(()),
+    }
+
+    while let Some(frame) = connection.read_frame(Some(ServerState::Status)).await? {
+        match frame {
+            Frame::StatusRequest => {
+                connection
+                    .write_frame(Frame::status_response_from(&config.status))
+                    .await?
+            }
+            Frame::PingRequest { payload } if config.respond_to_pings => {
+                connection
+                    .write_frame(Frame::PingResponse { payload })
+                    .await?;
+                // the ping is the last packet a client sends
+                return Ok(());
+            }
+            _ => return Ok(()),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[tokio::test]
+    async fn status_server() {
+        let handshakes = Arc::new(Mutex::new(Vec::new()));
+        let config = StatusServerConfig::default()
+            .with_status(JavaServerInfo::new("Hello"))
+            .with_handshake_observer({
+                let handshakes = Arc::clone(&handshakes);
+                move |handshake: &IncomingHandshake| {
+                    handshakes.lock().unwrap().push(handshake.clone())
+                }
+            });
+        let server = StatusServer::start("127.0.0.1:0".parse().unwrap(), config)
+            .await
+            .unwrap();
+        let address = server.address();
+
+        let (info, _) = crate::ping(("127.0.0.1", address.port())).await.unwrap();
+        assert_eq!(info.description.to_plain_text(), "Hello");
+
+        let handshakes = handshakes.lock().unwrap();
+        assert_eq!(handshakes.len(), 1);
+        assert_eq!(handshakes[0].hostname, "127.0.0.1");
+        assert_eq!(handshakes[0].port, address.port());
+        assert_eq!(handshakes[0].protocol, Frame::PROTOCOL_VERSION);
+        assert_eq!(handshakes[0].intent, Some(ProtocolState::Status));
+        assert!(handshakes[0].peer.ip().is_loopback());
+    }
+
+    #[tokio::test]
+    async fn max_connections() {
+        let config = StatusServerConfig::default()
+            .with_max_connections(1)
+            .with_timeout(Duration::from_secs(30));
+        let server = StatusServer::start("127.0.0.1:0".parse().unwrap(), config)
+            .await
+            .unwrap();
+        let address = ("127.0.0.1", server.address().port());
+
+        // an idle client holds the only connection, so the ping isn't answered until it leaves
+        let idle = tokio::net::TcpStream::connect(server.address())
+            .await
+            .unwrap();
+        tokio::task::yield_now().await;
+        let ping = crate::ping_or_timeout(address, Duration::from_millis(200)).await;
+        assert!(ping.is_err());
+        drop(idle);
+        crate::ping(address).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn maintenance() {
+        let config = StatusServerConfig::maintenance("Back soon").with_icon(b"\x89PNG");
+        let server = StatusServer::start("127.0.0.1:0".parse().unwrap(), config)
+            .await
+            .unwrap();
+        let address = ("127.0.0.1", server.address().port());
+
+        let (info, _) = crate::ping(address).await.unwrap();
+        assert_eq!(info.description.to_plain_text(), "Back soon");
+        assert_eq!(info.version.as_ref().unwrap().protocol, -1);
+        assert_eq!(info.favicon_png().unwrap().unwrap(), b"\x89PNG");
+
+        let probe = crate::probe_online_mode(address, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert!(
+            matches!(probe, crate::protocol::LoginProbe::Kicked { reason } if reason.contains("Back soon"))
+        );
+    }
+}
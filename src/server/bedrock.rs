@@ -0,0 +1,230 @@
+use crate::bedrock::{BedrockPong, BedrockServerInfo, PingRequestFrame};
+use crate::log::{debug, error, info, trace};
+use crate::net::UdpSocket;
+use crate::JavaServerInfo;
+use std::fmt;
+use std::io::ErrorKind;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+
+/// An unconnected ping received by a [`BedrockStatusServer`], as reported to a [`BedrockPingObserver`].
+///
+/// Since pings are sent over UDP, `peer` may be spoofed.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct IncomingBedrockPing {
+    /// The address the ping came from.
+    pub peer: SocketAddr,
+    /// The time the client sent, which is echoed back. Most clients send their uptime in milliseconds.
+    pub time: i64,
+    /// The client's RakNet GUID, which stays the same for a game session.
+    pub client_guid: i64,
+    /// When the ping was received.
+    pub timestamp: SystemTime,
+}
+
+impl fmt::Display for IncomingBedrockPing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} pinged with client GUID {} and time {}",
+            self.peer, self.client_guid, self.time
+        )
+    }
+}
+
+/// Receives every ping a [`BedrockStatusServer`] accepts, before it responds.
+///
+/// Any closure taking an [`&IncomingBedrockPing`](IncomingBedrockPing) is an observer.
+pub trait BedrockPingObserver: Send + Sync {
+    fn observe(&self, ping: &IncomingBedrockPing);
+}
+
+impl<F> BedrockPingObserver for F
+where
+    F: Fn(&IncomingBedrockPing) + Send + Sync,
+{
+    fn observe(&self, ping: &IncomingBedrockPing) {
+        self(ping)
+    }
+}
+
+/// Controls how a [`BedrockStatusServer`] responds to clients.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct BedrockStatusServerConfig {
+    /// The status sent to clients.
+    pub status: BedrockServerInfo,
+    /// The server's RakNet GUID. Defaults to a random one.
+    pub server_guid: i64,
+    /// Whether to respond to pings. Defaults to `true`.
+    ///
+    /// Pongs are larger than pings and go to an address that can be spoofed, so a server that only watches for
+    /// pings can turn this off to avoid being used to amplify traffic.
+    pub respond_to_pings: bool,
+    ping_observer: Option<Observer>,
+}
+
+impl Default for BedrockStatusServerConfig {
+    fn default() -> Self {
+        Self {
+            status: JavaServerInfo::new("A Minecraft Server").into(),
+            server_guid: rand::random(),
+            respond_to_pings: true,
+            ping_observer: None,
+        }
+    }
+}
+
+impl BedrockStatusServerConfig {
+    pub fn with_status(mut self, status: BedrockServerInfo) -> Self {
+        self.status = status;
+        self
+    }
+
+    pub fn with_server_guid(mut self, guid: i64) -> Self {
+        self.server_guid = guid;
+        self
+    }
+
+    pub fn with_respond_to_pings(mut self, respond_to_pings: bool) -> Self {
+        self.respond_to_pings = respond_to_pings;
+        self
+    }
+
+    /// Registers an observer that will be called with every ping the server receives.
+    pub fn with_ping_observer(mut self, observer: impl BedrockPingObserver + 'static) -> Self {
+        self.ping_observer = Some(Observer(Arc::new(observer)));
+        self
+    }
+}
+
+#[derive(Clone)]
+struct Observer(Arc<dyn BedrockPingObserver>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BedrockPingObserver")
+    }
+}
+
+/// How long to wait before receiving again after receiving failed for a reason other than an undelivered pong.
+const RECEIVE_BACKOFF: Duration = Duration::from_millis(100);
+
+/// A server answering Bedrock Edition unconnected pings in the background, which stops when dropped.
+///
+/// Like [`StatusServer`](super::StatusServer), but for RakNet. Other packets, such as connection requests,
+/// are ignored.
+#[derive(Debug)]
+pub struct BedrockStatusServer {
+    address: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl BedrockStatusServer {
+    /// Starts a server listening on `address` that responds to every ping according to `config`.
+    pub async fn start(
+        address: SocketAddr,
+        config: BedrockStatusServerConfig,
+    ) -> std::io::Result<Self> {
+        let socket = UdpSocket::bind(address).await?;
+        let address = socket.local_addr()?;
+        info!(%address, "Bedrock status server started");
+        let task = tokio::spawn(async move {
+            let motd = config.status.to_motd_string();
+            // pings are 33 bytes, so anything that doesn't fit is not a ping
+            let mut buffer = [0; 64];
+            loop {
+                let (len, peer) = match socket.recv_from(&mut buffer).await {
+                    Ok(received) => received,
+                    // on Windows, an earlier pong that couldn't be delivered fails the next receive
+                    Err(error) if error.kind() == ErrorKind::ConnectionReset => {
+                        trace!(%error, "Ignoring undelivered pong");
+                        continue;
+                    }
+                    Err(error) => {
+                        error!(%error, "Bedrock status server failed to receive a datagram");
+                        tokio::time::sleep(RECEIVE_BACKOFF).await;
+                        continue;
+                    }
+                };
+                let Some(request) = PingRequestFrame::from_bytes(&buffer[..len]) else {
+                    trace!(%peer, "Ignoring datagram that isn't a ping");
+                    continue;
+                };
+                let ping = IncomingBedrockPing {
+                    peer,
+                    time: request.time,
+                    client_guid: request.guid,
+                    timestamp: SystemTime::now(),
+                };
+                debug!(%ping, "Received ping");
+                if let Some(Observer(observer)) = &config.ping_observer {
+                    observer.observe(&ping);
+                }
+                if !config.respond_to_pings {
+                    continue;
+                }
+                let pong = BedrockPong {
+                    time: request.time,
+                    server_guid: config.server_guid,
+                    motd: motd.clone(),
+                };
+                if let Err(error) = socket.send_to(&pong.to_vec(), peer).await {
+                    debug!(%peer, %error, "Failed to send pong");
+                }
+            }
+        });
+        Ok(Self { address, task })
+    }
+
+    /// Returns the address the server is listening on.
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+}
+
+impl Drop for BedrockStatusServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bedrock::BedrockPingOptions;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn bedrock_status_server() {
+        let pings = Arc::new(Mutex::new(Vec::new()));
+        let status: BedrockServerInfo = "MCPE;Honeypot;390;1.14.60;0;10".parse().unwrap();
+        let config = BedrockStatusServerConfig::default()
+            .with_status(status.clone())
+            .with_server_guid(7)
+            .with_ping_observer({
+                let pings = Arc::clone(&pings);
+                move |ping: &IncomingBedrockPing| pings.lock().unwrap().push(ping.clone())
+            });
+        let server = BedrockStatusServer::start("127.0.0.1:0".parse().unwrap(), config)
+            .await
+            .unwrap();
+
+        let options = BedrockPingOptions::default().with_guid(42).with_time(1234);
+        let (pong, _) =
+            crate::bedrock::ping_addr_raw(server.address(), Duration::from_secs(2), 1, options)
+                .await
+                .unwrap();
+        assert_eq!(pong.server_guid, 7);
+        assert_eq!(pong.info().unwrap(), status);
+
+        let pings = pings.lock().unwrap();
+        assert_eq!(pings.len(), 1);
+        assert_eq!((pings[0].client_guid, pings[0].time), (42, 1234));
+        assert!(pings[0].peer.ip().is_loopback());
+    }
+}
@@ -0,0 +1,142 @@
+use crate::log::{debug, info};
+use crate::net::UdpSocket;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// The multicast group Java Edition clients listen on for LAN games.
+pub const LAN_MULTICAST_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 2, 60);
+/// The port Java Edition clients listen on for LAN games.
+pub const LAN_PORT: u16 = 4445;
+
+/// Formats the announcement a Java Edition game sends when it's opened to LAN.
+///
+/// Clients show `motd` in their server list, and join on `port` at the address the announcement came from.
+pub fn lan_announcement(motd: &str, port: u16) -> String {
+    format!("[MOTD]{motd}[/MOTD][AD]{port}[/AD]")
+}
+
+/// Controls what a [`LanAnnouncer`] sends.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct LanAnnouncerConfig {
+    /// The name shown in the LAN list. Defaults to `A Minecraft Server`.
+    pub motd: String,
+    /// The port players join on. Defaults to 25565.
+    pub port: u16,
+    /// How often to send the announcement. Defaults to 1.5 seconds, like the vanilla client.
+    /// Intervals shorter than a millisecond are rounded up to one.
+    pub interval: Duration,
+    /// Where to send the announcement. Defaults to [`LAN_MULTICAST_GROUP`] on [`LAN_PORT`].
+    pub target: SocketAddr,
+}
+
+impl Default for LanAnnouncerConfig {
+    fn default() -> Self {
+        Self {
+            motd: "A Minecraft Server".to_owned(),
+            port: 25565,
+            interval: Duration::from_millis(1500),
+            target: (LAN_MULTICAST_GROUP, LAN_PORT).into(),
+        }
+    }
+}
+
+impl LanAnnouncerConfig {
+    pub fn with_motd(mut self, motd: impl Into<String>) -> Self {
+        self.motd = motd.into();
+        self
+    }
+
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    pub fn with_target(mut self, target: SocketAddr) -> Self {
+        self.target = target;
+        self
+    }
+}
+
+/// Announces a Java Edition server to the local network in the background, so it shows up in clients' LAN
+/// lists. It stops when dropped.
+///
+/// Bedrock Edition clients find LAN games by broadcasting pings instead, so a
+/// [`BedrockStatusServer`](super::BedrockStatusServer) listening on port 19132 shows up without an announcer.
+#[derive(Debug)]
+pub struct LanAnnouncer {
+    task: JoinHandle<()>,
+}
+
+impl LanAnnouncer {
+    /// Starts sending announcements according to `config`.
+    pub async fn start(config: LanAnnouncerConfig) -> std::io::Result<Self> {
+        let bind: SocketAddr = match config.target {
+            SocketAddr::V4(_) => (Ipv4Addr::UNSPECIFIED, 0).into(),
+            SocketAddr::V6(_) => (std::net::Ipv6Addr::UNSPECIFIED, 0).into(),
+        };
+        let socket = UdpSocket::bind(bind).await?;
+        let announcement = lan_announcement(&config.motd, config.port);
+        info!(target = %config.target, port = config.port, "LAN announcer started");
+        let task = tokio::spawn(async move {
+            // `interval` panics on a zero period
+            let period = config.interval.max(Duration::from_millis(1));
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                if let Err(error) = socket.send_to(announcement.as_bytes(), config.target).await {
+                    debug!(%error, "Failed to send LAN announcement");
+                }
+            }
+        });
+        Ok(Self { task })
+    }
+}
+
+impl Drop for LanAnnouncer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn announces() {
+        let listener = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let config = LanAnnouncerConfig::default()
+            .with_motd("Headless")
+            .with_port(25566)
+            .with_interval(Duration::from_millis(10))
+            .with_target(listener.local_addr().unwrap());
+        let _announcer = LanAnnouncer::start(config).await.unwrap();
+
+        let mut buffer = [0; 64];
+        for _ in 0..2 {
+            let len = listener.recv(&mut buffer).await.unwrap();
+            assert_eq!(&buffer[..len], b"[MOTD]Headless[/MOTD][AD]25566[/AD]");
+        }
+    }
+
+    #[tokio::test]
+    async fn zero_interval() {
+        let listener = tokio::net::UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let config = LanAnnouncerConfig::default()
+            .with_interval(Duration::ZERO)
+            .with_target(listener.local_addr().unwrap());
+        let _announcer = LanAnnouncer::start(config).await.unwrap();
+
+        let mut buffer = [0; 64];
+        for _ in 0..2 {
+            listener.recv(&mut buffer).await.unwrap();
+        }
+    }
+}
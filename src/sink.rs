@@ -0,0 +1,123 @@
+//! Streaming the results of pinging many servers, one target at a time.
+
+use crate::PingReport;
+use core::fmt::Display;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// The result of pinging one target, as written to a [`ResultSink`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TargetResult {
+    /// The address that was pinged.
+    pub target: String,
+    /// The report, if the ping succeeded.
+    #[serde(flatten)]
+    pub report: Option<PingReport>,
+    /// Why the ping failed, if it did.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TargetResult {
+    pub fn success(target: impl Display, report: impl Into<PingReport>) -> Self {
+        Self {
+            target: target.to_string(),
+            report: Some(report.into()),
+            error: None,
+        }
+    }
+
+    pub fn failure(target: impl Display, error: impl Display) -> Self {
+        Self {
+            target: target.to_string(),
+            report: None,
+            error: Some(error.to_string()),
+        }
+    }
+}
+
+/// Somewhere to send results as each target finishes, so they don't all have to be kept in memory.
+pub trait ResultSink {
+    type Error;
+
+    fn write_result(&mut self, result: &TargetResult) -> Result<(), Self::Error>;
+
+    /// Makes sure every result written so far has reached its destination.
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// Writes each result as a line of JSON ([NDJSON](https://github.com/ndjson/ndjson-spec)).
+///
+/// ```
+/// use elytra_ping::sink::{NdjsonSink, ResultSink, TargetResult};
+///
+/// let mut sink = NdjsonSink::new(std::io::stdout());
+/// sink.write_result(&TargetResult::failure("mc.example.com:25565", "timed out")).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct NdjsonSink<W> {
+    writer: W,
+}
+
+impl<W: Write> NdjsonSink<W> {
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> ResultSink for NdjsonSink<W> {
+    type Error = io::Error;
+
+    fn write_result(&mut self, result: &TargetResult) -> Result<(), Self::Error> {
+        // serialize first so a failure doesn't leave half a line behind
+        let mut line = serde_json::to_vec(result)?;
+        line.push(b'\n');
+        self.writer.write_all(&line)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::JavaServerInfo;
+    use core::time::Duration;
+
+    #[test]
+    fn ndjson() {
+        let info: JavaServerInfo = r#"{ "description": "A Minecraft Server" }"#.parse().unwrap();
+        let results = [
+            TargetResult::success("localhost:25565", (info, Duration::from_millis(42))),
+            TargetResult::failure("localhost:25566", "connection refused"),
+        ];
+        let mut sink = NdjsonSink::new(Vec::new());
+        for result in &results {
+            sink.write_result(result).unwrap();
+        }
+        let output = String::from_utf8(sink.into_inner()).unwrap();
+
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 2);
+        let json: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(json["target"], "localhost:25565");
+        assert_eq!(json["edition"], "java");
+        assert_eq!(json["latency_ms"], 42);
+        assert_eq!(
+            lines[1],
+            r#"{"target":"localhost:25566","error":"connection refused"}"#
+        );
+        for (line, result) in lines.iter().zip(&results) {
+            assert_eq!(&serde_json::from_str::<TargetResult>(line).unwrap(), result);
+        }
+    }
+}
@@ -0,0 +1,576 @@
+//! An in-process mock Java Edition server, for testing code that pings servers without relying on the internet.
+//!
+//! ```
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! use elytra_ping::test_util::{MockServer, MockServerConfig};
+//!
+//! let server = MockServer::start(MockServerConfig::default()).await?;
+//! let (info, _latency) = elytra_ping::ping(server.addrs()).await?;
+//! assert_eq!(info.players.unwrap().online, 0);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::log::debug;
+use crate::mc_string::encode_mc_string;
+use crate::net::{TcpListener, TcpStream};
+use crate::protocol::{Frame, ProtocolError, ProtocolState, ServerState, SlpProtocol};
+use crate::varint::{VarIntBufMut, MAX_VAR_INT_LEN};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// The status sent by a [`MockServer`] by default.
+pub const DEFAULT_STATUS: &str = r#"{"version":{"name":"1.21","protocol":767},"players":{"max":20,"online":0},"description":"A Minecraft Server"}"#;
+
+/// A way for a [`MockServer`] to misbehave.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MockFault {
+    /// Close the connection as soon as the handshake is received.
+    CloseAfterHandshake,
+    /// Close the connection instead of responding to the ping request.
+    CloseBeforePing,
+    /// Send the first half of the status response, then close the connection.
+    TruncatedStatus,
+    /// Send these bytes instead of the status response.
+    RawStatus(Vec<u8>),
+}
+
+/// Controls how a [`MockServer`] responds to clients.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MockServerConfig {
+    /// The JSON sent in the status response.
+    pub status: String,
+    /// How long to wait before sending the status response.
+    pub status_delay: Duration,
+    /// How long to wait before responding to the ping request.
+    pub ping_delay: Duration,
+    pub fault: Option<MockFault>,
+    /// How many connections the fault applies to, starting from the first. If `None`, it applies to every connection.
+    pub faulty_connections: Option<usize>,
+    /// Whether to respond to logins with an Encryption Request, like an online-mode server, instead of Login Success.
+    pub online_mode: bool,
+    /// Whether to report the client's protocol version in the status, like a server running ViaVersion.
+    pub echo_protocol: bool,
+}
+
+impl Default for MockServerConfig {
+    fn default() -> Self {
+        Self {
+            status: DEFAULT_STATUS.to_owned(),
+            status_delay: Duration::ZERO,
+            ping_delay: Duration::ZERO,
+            fault: None,
+            faulty_connections: None,
+            online_mode: false,
+            echo_protocol: false,
+        }
+    }
+}
+
+impl MockServerConfig {
+    /// Creates a config for a server that responds with the given status JSON.
+    pub fn with_status(status: impl Into<String>) -> Self {
+        Self {
+            status: status.into(),
+            ..Default::default()
+        }
+    }
+}
+
+/// A Java Edition server running on a local ephemeral port, which stops when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    address: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    /// Starts a server on `127.0.0.1` that responds to every connection according to `config`.
+    pub async fn start(config: MockServerConfig) -> std::io::Result<Self> {
+        Self::listen(TcpListener::bind("127.0.0.1:0").await?, config)
+    }
+
+    /// Starts a server on all interfaces at `port`, such as on a host inside a turmoil simulation.
+    pub async fn start_on(port: u16, config: MockServerConfig) -> std::io::Result<Self> {
+        let address = SocketAddr::from(([0, 0, 0, 0], port));
+        Self::listen(TcpListener::bind(address).await?, config)
+    }
+
+    fn listen(listener: TcpListener, config: MockServerConfig) -> std::io::Result<Self> {
+        let address = listener.local_addr()?;
+        let config = Arc::new(config);
+        let task = tokio::spawn(async move {
+            let mut connections = 0;
+            while let Ok((stream, _)) = listener.accept().await {
+                let faulty = config
+                    .faulty_connections
+                    .map_or(true, |limit| connections < limit);
+                connections += 1;
+                let config = Arc::clone(&config);
+                tokio::spawn(async move {
+                    let fault = config.fault.as_ref().filter(|_| faulty);
+                    if let Err(error) = serve(stream, &config, fault).await {
+                        debug!(%error, "Mock server connection failed");
+                    }
+                });
+            }
+        });
+        Ok(Self { address, task })
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Returns the server's address in the form accepted by [`ping`](crate::ping) and [`connect`](crate::connect).
+    pub fn addrs(&self) -> (String, u16) {
+        (self.address.ip().to_string(), self.address.port())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve(
+    stream: TcpStream,
+    config: &MockServerConfig,
+    fault: Option<&MockFault>,
+) -> Result<(), ProtocolError> {
+    let mut connection = SlpProtocol::new(String::new(), 0, stream);
+    connection.set_server();
+    let Some(Frame::Handshake {
+        protocol, state, ..
+    }) = connection.read_frame(Some(ServerState::Handshake)).await?
+    else {
+        return Ok(());
+    };
+    if fault == Some(&MockFault::CloseAfterHandshake) {
+        return Ok(());
+    }
+    match ProtocolState::from_id(i32::from(state)) {
+        Some(ProtocolState::Status) => {}
+        Some(ProtocolState::Login | ProtocolState::Transfer) => {
+            return serve_login(connection, config).await
+        }
+        None => return Ok(()),
+    }
+    let status = if config.echo_protocol {
+        echo_protocol(&config.status, i32::from(protocol))
+    } else {
+        config.status.clone()
+    };
+
+    while let Some(frame) = connection.read_frame(Some(ServerState::Status)).await? {
+        match frame {
+            Frame::StatusRequest => {
+                tokio::time::sleep(config.status_delay).await;
+                match fault {
+                    Some(MockFault::TruncatedStatus) => {
+                        let packet = encode_status_packet(&status)?;
+                        connection.write_raw(&packet[..packet.len() / 2]).await?;
+                        return Ok(());
+                    }
+                    Some(MockFault::RawStatus(bytes)) => connection.write_raw(bytes).await?,
+                    _ => {
+                        connection
+                            .write_frame(Frame::StatusResponse {
+                                json: status.clone().into(),
+                            })
+                            .await?
+                    }
+                }
+            }
+            Frame::PingRequest { payload } => {
+                if fault == Some(&MockFault::CloseBeforePing) {
+                    return Ok(());
+                }
+                tokio::time::sleep(config.ping_delay).await;
+                connection
+                    .write_frame(Frame::PingResponse { payload })
+                    .await?;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the protocol version in a status with the client's.
+fn echo_protocol(status: &str, protocol: i32) -> String {
+    let Ok(mut status) = serde_json::from_str::<serde_json::Value>(status) else {
+        return status.to_owned();
+    };
+    if let Some(version) = status.get_mut("version") {
+        version["protocol"] = protocol.into();
+    }
+    status.to_string()
+}
+
+/// Responds to a Login Start packet, then closes the connection.
+async fn serve_login(
+    mut connection: SlpProtocol<TcpStream>,
+    config: &MockServerConfig,
+) -> Result<(), ProtocolError> {
+    if connection.read_packet().await?.is_none() {
+        return Ok(());
+    }
+    if config.online_mode {
+        // empty server id, public key and verify token, then "should authenticate"
+        connection.write_packet(0x01, &[0, 0, 0, 1]).await
+    } else {
+        let mut body = vec![0; 16];
+        body.extend_from_slice(&encode_mc_string(crate::PROBE_USERNAME)?);
+        body.push(0);
+        connection.write_packet(0x02, &body).await
+    }
+}
+
+fn encode_status_packet(json: &str) -> Result<Vec<u8>, ProtocolError> {
+    let mut body = vec![Frame::STATUS_RESPONSE_ID as u8];
+    body.extend_from_slice(&encode_mc_string(json)?);
+    let mut packet = Vec::with_capacity(body.len() + MAX_VAR_INT_LEN);
+    packet.put_var_int(body.len() as i32);
+    packet.extend_from_slice(&body);
+    Ok(packet)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::LoginProbe;
+    use crate::{
+        ping, ping_or_timeout, ping_outcome, ping_outcome_with_retries, PingError, PingPhase,
+    };
+
+    async fn ping_with(config: MockServerConfig) -> Result<(), PingError> {
+        let server = MockServer::start(config).await.unwrap();
+        ping_or_timeout(server.addrs(), Duration::from_secs(1))
+            .await
+            .map(|_| ())
+    }
+
+    fn failed_phase(result: Result<(), PingError>) -> Option<PingPhase> {
+        match result {
+            Err(PingError::Protocol { phase, .. }) => phase,
+            _ => None,
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_status() {
+        let server = MockServer::start(MockServerConfig::with_status(
+            r#"{"description":"Custom","players":{"max":5,"online":3}}"#,
+        ))
+        .await
+        .unwrap();
+        let (info, _) = ping(server.addrs()).await.unwrap();
+        assert_eq!(info.players.unwrap().online, 3);
+    }
+
+    #[tokio::test]
+    async fn address_inputs() {
+        let server = MockServer::start(MockServerConfig::default())
+            .await
+            .unwrap();
+        ping(server.address()).await.unwrap();
+        ping(server.address().to_string()).await.unwrap();
+        ping(("127.0.0.1", server.address().port())).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn faults() {
+        let config = |fault| MockServerConfig {
+            fault: Some(fault),
+            ..Default::default()
+        };
+        assert_eq!(
+            failed_phase(ping_with(config(MockFault::CloseAfterHandshake)).await),
+            Some(PingPhase::Status)
+        );
+        assert_eq!(
+            failed_phase(ping_with(config(MockFault::TruncatedStatus)).await),
+            Some(PingPhase::Status)
+        );
+        assert_eq!(
+            failed_phase(ping_with(config(MockFault::RawStatus(vec![2, 0x7f, 0]))).await),
+            Some(PingPhase::Status)
+        );
+        assert_eq!(
+            failed_phase(ping_with(config(MockFault::CloseBeforePing)).await),
+            Some(PingPhase::Latency)
+        );
+    }
+
+    #[tokio::test]
+    async fn slow_status_times_out() {
+        let result = ping_with(MockServerConfig {
+            status_delay: Duration::from_secs(5),
+            ..Default::default()
+        })
+        .await;
+        assert!(matches!(result, Err(PingError::Timeout { .. })));
+    }
+
+    #[cfg(feature = "metrics")]
+    #[tokio::test]
+    async fn metrics() {
+        use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+        use metrics_util::MetricKind;
+
+        // the recorder is only used on this thread, so it doesn't matter if a global recorder was installed
+        let recorder = DebuggingRecorder::new();
+        let snapshotter = recorder.snapshotter();
+        let _guard = metrics::set_default_local_recorder(&recorder);
+
+        ping_with(MockServerConfig::default()).await.unwrap();
+        let result = ping_with(MockServerConfig {
+            fault: Some(MockFault::CloseAfterHandshake),
+            ..Default::default()
+        })
+        .await;
+        assert!(result.is_err());
+
+        let metrics = snapshotter.snapshot().into_vec();
+        let value = |kind, name| {
+            metrics
+                .iter()
+                .find(|(key, ..)| key.kind() == kind && key.key().name() == name)
+                .map(|(.., value)| value)
+        };
+        assert_eq!(
+            value(MetricKind::Counter, crate::metrics::PINGS),
+            Some(&DebugValue::Counter(2))
+        );
+        assert_eq!(
+            value(MetricKind::Counter, crate::metrics::FAILURES),
+            Some(&DebugValue::Counter(1))
+        );
+        assert!(matches!(
+            value(MetricKind::Histogram, crate::metrics::LATENCY),
+            Some(DebugValue::Histogram(latencies)) if latencies.len() == 1
+        ));
+        assert!(matches!(
+            value(MetricKind::Counter, crate::metrics::BYTES_SENT),
+            Some(DebugValue::Counter(bytes)) if *bytes > 0
+        ));
+        // the mock server's replies aren't counted as sent to a server
+        let sent = value(MetricKind::Counter, crate::metrics::BYTES_SENT);
+        let received = value(MetricKind::Counter, crate::metrics::BYTES_RECEIVED);
+        assert!(matches!(
+            (sent, received),
+            (Some(DebugValue::Counter(sent)), Some(DebugValue::Counter(received))) if sent < received
+        ));
+    }
+
+    #[tokio::test]
+    async fn retries() {
+        let server = MockServer::start(MockServerConfig {
+            fault: Some(MockFault::CloseAfterHandshake),
+            faulty_connections: Some(2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let outcome = ping_outcome_with_retries(server.addrs(), 3, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(outcome.attempts, 3);
+
+        let server = MockServer::start(MockServerConfig {
+            fault: Some(MockFault::CloseAfterHandshake),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let result = ping_outcome_with_retries(server.addrs(), 3, Duration::from_secs(1)).await;
+        assert!(result.is_err());
+
+        // at least one attempt is made
+        let server = MockServer::start(MockServerConfig::default())
+            .await
+            .unwrap();
+        let outcome = ping_outcome_with_retries(server.addrs(), 0, Duration::from_secs(1))
+            .await
+            .unwrap();
+        assert_eq!(outcome.attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn no_retry_after_invalid_response() {
+        // the second connection would succeed, but an invalid status isn't worth trying again
+        let server = MockServer::start(MockServerConfig {
+            fault: Some(MockFault::RawStatus(vec![3, 0x00, 1, b'{'])),
+            faulty_connections: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let error = ping_outcome_with_retries(server.addrs(), 3, Duration::from_secs(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            PingError::Protocol {
+                source: crate::protocol::ProtocolError::JsonParse { .. },
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn poller_backs_off() {
+        let server = MockServer::start(MockServerConfig {
+            fault: Some(MockFault::CloseAfterHandshake),
+            faulty_connections: Some(2),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let backoff = Duration::from_millis(50);
+        let mut poller = crate::StatusPoller::new(server.address())
+            .with_interval(Duration::ZERO)
+            .with_backoff(backoff, Duration::from_secs(1));
+
+        let start = std::time::Instant::now();
+        assert!(poller.next_status().await.is_err());
+        assert!(poller.next_status().await.is_err());
+        poller.next_status().await.unwrap();
+        assert!(start.elapsed() >= backoff * 3);
+        poller.next_status().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn status_gauges() {
+        let server = MockServer::start(MockServerConfig::with_status(
+            r#"{"description":"Gauges","players":{"max":5,"online":3}}"#,
+        ))
+        .await
+        .unwrap();
+        let mut poller = crate::StatusPoller::new(server.address());
+        let mut gauges = crate::prometheus::StatusGauges::new();
+        let result = poller.next_status().await;
+        gauges.record(poller.target(), &result);
+
+        let rendered = gauges.to_string();
+        let target = poller.target().to_string();
+        assert!(rendered.contains(&format!("elytra_ping_online{{target=\"{target}\"}} 1\n")));
+        assert!(rendered.contains(&format!(
+            "elytra_ping_players_online{{target=\"{target}\"}} 3\n"
+        )));
+        assert!(rendered.contains(&format!("elytra_ping_latency_ms{{target=\"{target}\"}} ")));
+    }
+
+    #[tokio::test]
+    async fn health_check() {
+        use crate::health::{health_check, DegradedReason, HealthStatus, HealthThresholds};
+
+        let server = MockServer::start(MockServerConfig::with_status(
+            r#"{"description":"Full","players":{"max":5,"online":5}}"#,
+        ))
+        .await
+        .unwrap();
+        let thresholds = HealthThresholds::default().with_timeout(Duration::from_secs(1));
+        assert_eq!(
+            health_check(server.address(), &thresholds).await,
+            HealthStatus::Healthy
+        );
+        let status = health_check(
+            server.address(),
+            &thresholds.with_min_players(10).with_full_is_degraded(true),
+        )
+        .await;
+        assert_eq!(
+            status,
+            HealthStatus::Degraded {
+                reasons: vec![
+                    DegradedReason::TooFewPlayers { online: 5, min: 10 },
+                    DegradedReason::Full { online: 5, max: 5 },
+                ]
+            }
+        );
+        assert_eq!(
+            status.to_string(),
+            "degraded: 5 players online is below 10, full with 5/5 players"
+        );
+
+        let server = MockServer::start(MockServerConfig {
+            status_delay: Duration::from_secs(5),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let status = health_check(server.address(), &thresholds).await;
+        assert_eq!(status.to_string(), "unreachable: timed out");
+        assert!(!status.is_reachable());
+    }
+
+    #[tokio::test]
+    async fn mimic_vanilla() {
+        let server = MockServer::start(MockServerConfig::default())
+            .await
+            .unwrap();
+        let options = crate::JavaPingOptions::default().with_mimic_vanilla(true);
+        let outcome = crate::ping_outcome_with_options(server.address(), options)
+            .await
+            .unwrap();
+        assert_eq!(outcome.info.players.unwrap().max, 20);
+    }
+
+    #[tokio::test]
+    async fn online_mode() {
+        for online_mode in [false, true] {
+            let server = MockServer::start(MockServerConfig {
+                online_mode,
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+            let probe = crate::probe_online_mode(server.address(), Duration::from_secs(1))
+                .await
+                .unwrap();
+            let expected = if online_mode {
+                LoginProbe::OnlineMode
+            } else {
+                LoginProbe::OfflineMode
+            };
+            assert_eq!(probe, expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn timings() {
+        let server = MockServer::start(MockServerConfig {
+            status_delay: Duration::from_millis(50),
+            ping_delay: Duration::from_millis(100),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+        let outcome = ping_outcome(server.addrs()).await.unwrap();
+        let timings = outcome.timings;
+        assert!(timings.handshake_to_status >= Duration::from_millis(50));
+        assert!(timings.ping_rtt >= Duration::from_millis(100));
+        assert_eq!(timings.ping_rtt, outcome.latency);
+        assert!(timings.total() >= Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn resolution_trace() {
+        let server = MockServer::start(MockServerConfig::default())
+            .await
+            .unwrap();
+        let outcome = ping_outcome(server.addrs()).await.unwrap();
+        let trace = outcome.connection.trace;
+        assert_eq!(trace.srv_record, None);
+        assert_eq!(trace.resolved_host, server.addrs());
+        assert_eq!(trace.addresses, vec![server.address()]);
+    }
+}
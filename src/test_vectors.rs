@@ -0,0 +1,98 @@
+//! Known-good packets and status responses, for testing other implementations of the Server List Ping protocol
+//! against the same data Elytra Ping is tested with.
+//!
+//! Each packet includes its length prefix, exactly as it's sent over the connection.
+
+/// A handshake for protocol 767 (1.21) to `localhost:25565` that asks to switch to the status state.
+pub const HANDSHAKE: &[u8] = b"\x10\x00\xff\x05\x09localhost\x63\xdd\x01";
+
+/// A status request.
+pub const STATUS_REQUEST: &[u8] = b"\x01\x00";
+
+/// A status response containing [`MINIMAL_STATUS`].
+pub const STATUS_RESPONSE: &[u8] = b"\x16\x00\x14{\"description\":\"Hi\"}";
+
+/// The payload of [`PING_REQUEST`] and [`PING_RESPONSE`], which is a Unix timestamp in milliseconds like the
+/// vanilla client sends.
+pub const PING_PAYLOAD: i64 = 1_700_000_000_000;
+
+/// A ping request with [`PING_PAYLOAD`].
+pub const PING_REQUEST: &[u8] = b"\x09\x01\x00\x00\x01\x8b\xcf\xe5\x68\x00";
+
+/// The ping response to [`PING_REQUEST`]. It echoes the payload with the same packet id, so the bytes are the
+/// same.
+pub const PING_RESPONSE: &[u8] = PING_REQUEST;
+
+/// The smallest status a server can send, with only a description.
+pub const MINIMAL_STATUS: &str = r#"{"description":"Hi"}"#;
+
+/// A status from a vanilla 1.21 server.
+pub const VANILLA_STATUS: &str = r#"{"version":{"name":"1.21","protocol":767},"enforcesSecureChat":true,"description":"A Minecraft Server","players":{"max":20,"online":0}}"#;
+
+/// A status from a Paper server with a formatted description and a player sample, including an anonymous player.
+pub const PAPER_STATUS: &str = r##"{"version":{"name":"Paper 1.20.4","protocol":765},"enforcesSecureChat":false,"description":{"text":"","extra":[{"text":"Survival ","color":"green","bold":true},{"text":"| ","color":"dark_gray"},{"text":"Season 4","color":"#ffaa00"}]},"players":{"max":100,"online":2,"sample":[{"id":"069a79f4-44e9-4726-a5be-fca90e38aaf5","name":"Notch"},{"id":"00000000-0000-0000-0000-000000000000","name":"Anonymous Player"}]},"previewsChat":false}"##;
+
+/// A status from a BungeeCord proxy, which formats its description with legacy `§` codes.
+pub const BUNGEE_STATUS: &str = r#"{"version":{"name":"BungeeCord 1.8.x-1.20.x","protocol":763},"players":{"max":500,"online":153},"description":"§6§lA Network §r§7- §aNow open!"}"#;
+
+/// A status from a proxy that hides its version behind a protocol number of -1 and its players behind the
+/// version name.
+pub const HIDDEN_VERSION_STATUS: &str = r#"{"version":{"name":"§cMaintenance","protocol":-1},"players":{"max":0,"online":0},"description":"Back soon"}"#;
+
+/// A status from a Forge 1.20.1 server, which lists its mods in `forgeData`.
+pub const FORGE_STATUS: &str = r#"{"version":{"name":"1.20.1","protocol":763},"description":{"text":"A Forge server"},"players":{"max":20,"online":0},"forgeData":{"channels":[],"mods":[{"modId":"forge","modmarker":"47.2.0"},{"modId":"jei","modmarker":"15.2.0.27"}],"fmlNetworkVersion":3},"preventsChatReports":true}"#;
+
+/// A status from a Forge 1.12.2 server, which lists its mods in `modinfo`.
+pub const LEGACY_FORGE_STATUS: &str = r#"{"description":{"text":"A Minecraft Server"},"players":{"max":20,"online":0},"version":{"name":"1.12.2","protocol":340},"modinfo":{"type":"FML","modList":[{"modid":"minecraft","version":"1.12.2"},{"modid":"forge","version":"14.23.5.2860"}]}}"#;
+
+/// Every status in this module.
+pub const STATUSES: &[&str] = &[
+    MINIMAL_STATUS,
+    VANILLA_STATUS,
+    PAPER_STATUS,
+    BUNGEE_STATUS,
+    HIDDEN_VERSION_STATUS,
+    FORGE_STATUS,
+    LEGACY_FORGE_STATUS,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::JavaServerInfo;
+    use crate::protocol::Frame;
+    use crate::varint::VarInt;
+    use bytes::BytesMut;
+
+    fn encode(frame: Frame) -> BytesMut {
+        let mut buf = BytesMut::new();
+        frame.encode_into(&mut buf).unwrap();
+        buf
+    }
+
+    #[test]
+    fn packets() {
+        let handshake = Frame::Handshake {
+            protocol: VarInt(767),
+            address: "localhost".into(),
+            port: 25565,
+            state: VarInt(1),
+        };
+        assert_eq!(encode(handshake), HANDSHAKE);
+        assert_eq!(encode(Frame::StatusRequest), STATUS_REQUEST);
+        let json = MINIMAL_STATUS.into();
+        assert_eq!(encode(Frame::StatusResponse { json }), STATUS_RESPONSE);
+        let payload = PING_PAYLOAD;
+        assert_eq!(encode(Frame::PingRequest { payload }), PING_REQUEST);
+        assert_eq!(encode(Frame::PingResponse { payload }), PING_RESPONSE);
+    }
+
+    #[test]
+    fn statuses() {
+        for status in STATUSES {
+            status.parse::<JavaServerInfo>().unwrap();
+        }
+        let info: JavaServerInfo = LEGACY_FORGE_STATUS.parse().unwrap();
+        assert_eq!(info.mod_count(), 2);
+    }
+}
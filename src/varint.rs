@@ -0,0 +1,198 @@
+//! The variable-length integers used throughout the Java Edition protocol.
+//!
+//! Reading and writing is done through extension traits on [`Buf`] and [`BufMut`], so they work with
+//! `std::io::Cursor`, `&[u8]`, `Vec<u8>` and [`BytesMut`](bytes::BytesMut) alike.
+//!
+//! ```
+//! use elytra_ping::varint::{VarIntBuf, VarIntBufMut};
+//!
+//! let mut packet = Vec::new();
+//! packet.put_var_int(767);
+//! assert_eq!(packet, [0xff, 0x05]);
+//! assert_eq!((&packet[..]).get_var_int().unwrap(), 767);
+//! ```
+
+use bytes::{Buf, BufMut};
+use snafu::{Backtrace, Snafu};
+
+/// The most bytes a VarInt can take up.
+pub const MAX_VAR_INT_LEN: usize = 5;
+/// The most bytes a VarLong can take up.
+pub const MAX_VAR_LONG_LEN: usize = 10;
+
+const SEGMENT_BITS: u8 = 0x7f;
+const CONTINUE_BIT: u8 = 0x80;
+
+#[derive(Snafu, Debug)]
+pub enum VarIntError {
+    /// The buffer ends before the number does, so more data must be read before it can be decoded.
+    #[snafu(display("incomplete variable-length integer"))]
+    Incomplete { backtrace: Backtrace },
+    /// The number continues past its maximum length.
+    #[snafu(display("variable-length integer is longer than {max} bytes"))]
+    TooLong { max: usize, backtrace: Backtrace },
+}
+
+/// An `i32` that is sent as a VarInt.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarInt(pub i32);
+
+impl From<i32> for VarInt {
+    fn from(value: i32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<VarInt> for i32 {
+    fn from(value: VarInt) -> Self {
+        value.0
+    }
+}
+
+/// An `i64` that is sent as a VarLong.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct VarLong(pub i64);
+
+impl From<i64> for VarLong {
+    fn from(value: i64) -> Self {
+        Self(value)
+    }
+}
+
+impl From<VarLong> for i64 {
+    fn from(value: VarLong) -> Self {
+        value.0
+    }
+}
+
+/// Reads VarInts and VarLongs from a buffer.
+///
+/// If the buffer ends before the number does, [`VarIntError::Incomplete`] is returned and the buffer may have
+/// been partially consumed.
+pub trait VarIntBuf: Buf {
+    fn get_var_int(&mut self) -> Result<i32, VarIntError> {
+        Ok(get_var(self, MAX_VAR_INT_LEN)? as u32 as i32)
+    }
+
+    fn get_var_long(&mut self) -> Result<i64, VarIntError> {
+        Ok(get_var(self, MAX_VAR_LONG_LEN)? as i64)
+    }
+}
+
+impl<B: Buf + ?Sized> VarIntBuf for B {}
+
+/// Writes VarInts and VarLongs to a buffer.
+pub trait VarIntBufMut: BufMut {
+    fn put_var_int(&mut self, value: i32) {
+        put_var(self, value as u32 as u64);
+    }
+
+    fn put_var_long(&mut self, value: i64) {
+        put_var(self, value as u64);
+    }
+}
+
+impl<B: BufMut + ?Sized> VarIntBufMut for B {}
+
+/// Returns how many bytes `value` takes up as a VarInt.
+pub fn var_int_len(value: i32) -> usize {
+    var_len(value as u32 as u64)
+}
+
+/// Returns how many bytes `value` takes up as a VarLong.
+pub fn var_long_len(value: i64) -> usize {
+    var_len(value as u64)
+}
+
+fn get_var<B: Buf + ?Sized>(buf: &mut B, max_len: usize) -> Result<u64, VarIntError> {
+    let mut value = 0;
+    for position in 0..max_len {
+        if !buf.has_remaining() {
+            return IncompleteSnafu.fail();
+        }
+        let byte = buf.get_u8();
+        value |= u64::from(byte & SEGMENT_BITS) << (7 * position);
+        if byte & CONTINUE_BIT == 0 {
+            return Ok(value);
+        }
+    }
+    TooLongSnafu { max: max_len }.fail()
+}
+
+fn put_var<B: BufMut + ?Sized>(buf: &mut B, mut value: u64) {
+    loop {
+        if value & !u64::from(SEGMENT_BITS) == 0 {
+            buf.put_u8(value as u8);
+            return;
+        }
+        buf.put_u8(value as u8 & SEGMENT_BITS | CONTINUE_BIT);
+        value >>= 7;
+    }
+}
+
+fn var_len(value: u64) -> usize {
+    // each byte holds 7 bits, and zero still takes a byte
+    (64 - (value | 1).leading_zeros() as usize + 6) / 7
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn var_int_vectors() {
+        let vectors: [(i32, &[u8]); 8] = [
+            (0, &[0x00]),
+            (1, &[0x01]),
+            (127, &[0x7f]),
+            (128, &[0x80, 0x01]),
+            (25565, &[0xdd, 0xc7, 0x01]),
+            (2147483647, &[0xff, 0xff, 0xff, 0xff, 0x07]),
+            (-1, &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+            (-2147483648, &[0x80, 0x80, 0x80, 0x80, 0x08]),
+        ];
+        for (value, bytes) in vectors {
+            let mut encoded = Vec::new();
+            encoded.put_var_int(value);
+            assert_eq!(encoded, bytes);
+            assert_eq!(var_int_len(value), bytes.len());
+            assert_eq!((&encoded[..]).get_var_int().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn var_long_vectors() {
+        let vectors: [(i64, &[u8]); 4] = [
+            (0, &[0x00]),
+            (2147483648, &[0x80, 0x80, 0x80, 0x80, 0x08]),
+            (
+                -1,
+                &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+            ),
+            (
+                i64::MIN,
+                &[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x01],
+            ),
+        ];
+        for (value, bytes) in vectors {
+            let mut encoded = Vec::new();
+            encoded.put_var_long(value);
+            assert_eq!(encoded, bytes);
+            assert_eq!(var_long_len(value), bytes.len());
+            assert_eq!((&encoded[..]).get_var_long().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(matches!(
+            (&[0x80, 0x80][..]).get_var_int(),
+            Err(VarIntError::Incomplete { .. })
+        ));
+        assert!(matches!(
+            (&[0xff; 6][..]).get_var_int(),
+            Err(VarIntError::TooLong { max: 5, .. })
+        ));
+    }
+}